@@ -0,0 +1,291 @@
+//! Serializing a parsed file's AST into a plain JSON tree of
+//! [`AstNode`]s, for downstream tools (including non-Rust ones) that want
+//! the parsed structure without linking against `goscript-parser` or this
+//! crate itself.
+//!
+//! The vendored parser's AST is arena-keyed (`FuncDeclKey`, `IdentKey`,
+//! ...) rather than a plain tree, so every node here is resolved against
+//! the project's [`AstObjects`] at serialization time rather than at
+//! parse time — the same trade every other plugin in this crate makes
+//! (see e.g. [`crate::go_parser::print`]).
+
+use goscript_parser::ast::{self, Decl, Node, Spec, Stmt};
+
+use crate::go_parser::print::{format_expr, format_stmt};
+use crate::go_parser::{AstObjects, ParseDirResult, Pos};
+
+/// One node in the serialized AST: its kind (`"FuncDecl"`, `"IfStmt"`,
+/// ...), a name when the node has one worth surfacing (a declaration's
+/// identifier, an expression's rendered text), its source span, and its
+/// children in source order.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct AstNode {
+    pub kind: String,
+    pub name: Option<String>,
+    pub start: Pos,
+    pub end: Pos,
+    pub children: Vec<AstNode>,
+}
+
+impl AstNode {
+    fn leaf(kind: impl Into<String>, name: Option<String>, start: Pos, end: Pos) -> Self {
+        AstNode {
+            kind: kind.into(),
+            name,
+            start,
+            end,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// The whole file as one [`AstNode`] tree: a `"File"` root named after the
+/// package clause, with one child per top-level declaration.
+pub fn file_to_ast_node(file: &ast::File, objects: &AstObjects) -> AstNode {
+    AstNode {
+        kind: "File".to_string(),
+        name: Some(objects.idents[file.name].name.clone()),
+        start: file.pos(objects),
+        end: file.end(objects),
+        children: file.decls.iter().map(|decl| decl_to_node(decl, objects)).collect(),
+    }
+}
+
+fn decl_to_node(decl: &Decl, objects: &AstObjects) -> AstNode {
+    let start = decl.pos(objects);
+    let end = decl.end(objects);
+    match decl {
+        Decl::Func(key) => {
+            let fdecl = &objects.fdecls[*key];
+            let name = objects.idents[fdecl.name].name.clone();
+            let children = match &fdecl.body {
+                Some(body) => body.list.iter().map(|stmt| stmt_to_node(stmt, objects)).collect(),
+                None => Vec::new(),
+            };
+            AstNode {
+                kind: "FuncDecl".to_string(),
+                name: Some(name),
+                start,
+                end,
+                children,
+            }
+        }
+        Decl::Gen(gen_decl) => AstNode {
+            kind: format!("GenDecl({})", gen_decl.token.text()),
+            name: None,
+            start,
+            end,
+            children: gen_decl.specs.iter().map(|key| spec_to_node(&objects.specs[*key], objects)).collect(),
+        },
+        Decl::Bad(_) => AstNode::leaf("BadDecl", None, start, end),
+    }
+}
+
+fn spec_to_node(spec: &Spec, objects: &AstObjects) -> AstNode {
+    let start = spec.pos(objects);
+    let end = spec.end(objects);
+    match spec {
+        Spec::Import(import) => {
+            AstNode::leaf("ImportSpec", Some(import.path.token.to_string()), start, end)
+        }
+        Spec::Value(value) => {
+            let names = value.names.iter().map(|n| objects.idents[*n].name.clone()).collect::<Vec<_>>();
+            AstNode::leaf("ValueSpec", Some(names.join(", ")), start, end)
+        }
+        Spec::Type(typ) => {
+            AstNode::leaf("TypeSpec", Some(objects.idents[typ.name].name.clone()), start, end)
+        }
+    }
+}
+
+/// A single statement, plus (for statements with a nested body — `if`,
+/// `for`, `range`, `switch`, `case`, a label, ...) its children in source
+/// order. Leaf statements (assignments, returns, ...) carry the same
+/// rendered text [`crate::go_parser::print::format_stmt`] gives a CFG
+/// block, as `name`, rather than modeling every expression as its own
+/// subtree.
+fn stmt_to_node(stmt: &Stmt, objects: &AstObjects) -> AstNode {
+    let start = stmt.pos(objects);
+    let end = stmt.end(objects);
+    let kind = stmt_kind(stmt);
+
+    let children = match stmt {
+        Stmt::Block(b) => b.list.iter().map(|s| stmt_to_node(s, objects)).collect(),
+        Stmt::If(i) => {
+            let mut children = Vec::new();
+            if let Some(init) = &i.init {
+                children.push(stmt_to_node(init, objects));
+            }
+            children.extend(i.body.list.iter().map(|s| stmt_to_node(s, objects)));
+            if let Some(els) = &i.els {
+                children.push(stmt_to_node(els, objects));
+            }
+            children
+        }
+        Stmt::For(f) => {
+            let mut children = Vec::new();
+            if let Some(init) = &f.init {
+                children.push(stmt_to_node(init, objects));
+            }
+            children.extend(f.body.list.iter().map(|s| stmt_to_node(s, objects)));
+            if let Some(post) = &f.post {
+                children.push(stmt_to_node(post, objects));
+            }
+            children
+        }
+        Stmt::Range(r) => r.body.list.iter().map(|s| stmt_to_node(s, objects)).collect(),
+        Stmt::Switch(sw) => sw.body.list.iter().map(|s| stmt_to_node(s, objects)).collect(),
+        Stmt::TypeSwitch(sw) => sw.body.list.iter().map(|s| stmt_to_node(s, objects)).collect(),
+        Stmt::Select(sel) => sel.body.list.iter().map(|s| stmt_to_node(s, objects)).collect(),
+        Stmt::Case(case) => case.body.iter().map(|s| stmt_to_node(s, objects)).collect(),
+        Stmt::Comm(comm) => {
+            let mut children = Vec::new();
+            if let Some(comm_stmt) = &comm.comm {
+                children.push(stmt_to_node(comm_stmt, objects));
+            }
+            children.extend(comm.body.iter().map(|s| stmt_to_node(s, objects)));
+            children
+        }
+        Stmt::Labeled(key) => {
+            let labeled = &objects.l_stmts[*key];
+            vec![stmt_to_node(&labeled.stmt, objects)]
+        }
+        _ => Vec::new(),
+    };
+
+    let name = stmt_name(stmt, objects);
+    AstNode { kind: kind.to_string(), name, start, end, children }
+}
+
+/// A short, stable name for a statement's AST node kind — the same
+/// spelling as the vendored parser's own `Stmt` variant names, so a
+/// downstream consumer can match on `kind` without guessing.
+fn stmt_kind(stmt: &Stmt) -> &'static str {
+    match stmt {
+        Stmt::Bad(_) => "BadStmt",
+        Stmt::Decl(_) => "DeclStmt",
+        Stmt::Empty(_) => "EmptyStmt",
+        Stmt::Labeled(_) => "LabeledStmt",
+        Stmt::Expr(_) => "ExprStmt",
+        Stmt::Send(_) => "SendStmt",
+        Stmt::IncDec(_) => "IncDecStmt",
+        Stmt::Assign(_) => "AssignStmt",
+        Stmt::Go(_) => "GoStmt",
+        Stmt::Defer(_) => "DeferStmt",
+        Stmt::Return(_) => "ReturnStmt",
+        Stmt::Branch(_) => "BranchStmt",
+        Stmt::Block(_) => "BlockStmt",
+        Stmt::If(_) => "IfStmt",
+        Stmt::Case(_) => "CaseClause",
+        Stmt::Switch(_) => "SwitchStmt",
+        Stmt::TypeSwitch(_) => "TypeSwitchStmt",
+        Stmt::Comm(_) => "CommClause",
+        Stmt::Select(_) => "SelectStmt",
+        Stmt::For(_) => "ForStmt",
+        Stmt::Range(_) => "RangeStmt",
+    }
+}
+
+/// The `name` a statement's [`AstNode`] carries: for statements
+/// [`format_stmt`] can render on one line (assignments, returns,
+/// conditions, ...), that rendering; `None` for pure structural nodes
+/// (a bare block, a `case`'s body) whose meaning is entirely in their
+/// children.
+fn stmt_name(stmt: &Stmt, objects: &AstObjects) -> Option<String> {
+    match stmt {
+        Stmt::Block(_) => None,
+        Stmt::Labeled(key) => Some(objects.idents[objects.l_stmts[*key].label].name.clone()),
+        Stmt::Case(case) => match &case.list {
+            Some(exprs) if !exprs.is_empty() => {
+                Some(exprs.iter().map(|e| format_expr(e, objects)).collect::<Vec<_>>().join(", "))
+            }
+            _ => None,
+        },
+        _ => {
+            let text = format_stmt(stmt, objects);
+            (!text.is_empty()).then_some(text)
+        }
+    }
+}
+
+/// Every parsed file in `parsed`, as `(package_dir, file_name, AstNode)`
+/// triples in `packages`/`files` order — the shape a plugin function
+/// wraps directly as its result.
+pub fn export_ast(parsed: &ParseDirResult) -> Vec<(String, String, AstNode)> {
+    let mut out = Vec::new();
+    for (pkg_dir, pkg) in &parsed.packages {
+        for (file_name, pf) in &pkg.files {
+            out.push((pkg_dir.clone(), file_name.clone(), file_to_ast_node(&pf.ast, &parsed.objects)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    fn parse_one(src: &str) -> ParseDirResult {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-ast-export-test-{}-{n}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::File::create(dir.join("a.go"))
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    #[test]
+    fn a_function_declaration_appears_with_its_name_and_expected_statement_kinds() {
+        let parsed = parse_one(
+            "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn x\n\t}\n\treturn 0\n}\n",
+        );
+        let exported = export_ast(&parsed);
+        assert_eq!(exported.len(), 1);
+        let (_, file_name, file_node) = &exported[0];
+        assert_eq!(file_name, "a.go");
+        assert_eq!(file_node.kind, "File");
+        assert_eq!(file_node.name.as_deref(), Some("main"));
+
+        let func_node = file_node.children.iter().find(|n| n.kind == "FuncDecl").unwrap();
+        assert_eq!(func_node.name.as_deref(), Some("f"));
+
+        let kinds: Vec<&str> = func_node
+            .children
+            .iter()
+            .map(|n| n.kind.as_str())
+            .filter(|k| *k != "EmptyStmt")
+            .collect();
+        assert_eq!(kinds, vec!["IfStmt", "ReturnStmt"]);
+
+        let if_node = &func_node.children[0];
+        assert_eq!(if_node.name.as_deref(), Some("if x > 0"));
+        let if_kinds: Vec<&str> = if_node
+            .children
+            .iter()
+            .map(|n| n.kind.as_str())
+            .filter(|k| *k != "EmptyStmt")
+            .collect();
+        assert_eq!(if_kinds, vec!["ReturnStmt"]);
+    }
+
+    #[test]
+    fn json_serialization_round_trips_kind_and_name() {
+        let parsed = parse_one("package main\n\nfunc f() {\n\tx := 1\n\t_ = x\n}\n");
+        let exported = export_ast(&parsed);
+        let (_, _, file_node) = &exported[0];
+
+        let json = serde_json::to_value(file_node).unwrap();
+        assert_eq!(json["kind"], "File");
+        assert_eq!(json["name"], "main");
+        assert_eq!(json["children"][0]["kind"], "FuncDecl");
+    }
+}
@@ -0,0 +1,421 @@
+//! Generic search helpers over a parsed function body's statement and
+//! expression trees.
+//!
+//! The vendored `goscript-parser` has no traversal API beyond its own
+//! `Visitor` trait, which requires implementing every node kind just to
+//! walk a few of them — every analysis in this crate so far has instead
+//! hand-rolled its own recursive walk over the handful of `Stmt`
+//! variants it cares about (see
+//! [`crate::complexity::analyze_statement_for_decision_point`]). This
+//! gives plugin authors one reusable walk, driven by a predicate rather
+//! than a full `Visitor` impl, instead of another one-off.
+
+use goscript_parser::ast::{Expr, Stmt};
+use goscript_parser::objects::Objects as AstObjects;
+
+/// Calls `visit` once for every statement in `stmts`, then recurses into
+/// every nested block a statement can contain (`if`/`for`/`range`/
+/// `switch`/`case`/... bodies, a labeled statement's inner statement) —
+/// a pre-order walk of the whole statement tree rooted at `stmts`.
+pub fn walk_stmts<'a>(stmts: &'a [Stmt], objects: &'a AstObjects, visit: &mut impl FnMut(&'a Stmt)) {
+    for stmt in stmts {
+        visit(stmt);
+        match stmt {
+            Stmt::Block(b) => walk_stmts(&b.list, objects, visit),
+            Stmt::If(i) => {
+                if let Some(init) = &i.init {
+                    walk_stmts(std::slice::from_ref(init), objects, visit);
+                }
+                walk_stmts(&i.body.list, objects, visit);
+                if let Some(els) = &i.els {
+                    walk_stmts(std::slice::from_ref(els), objects, visit);
+                }
+            }
+            Stmt::For(f) => {
+                if let Some(init) = &f.init {
+                    walk_stmts(std::slice::from_ref(init), objects, visit);
+                }
+                if let Some(post) = &f.post {
+                    walk_stmts(std::slice::from_ref(post), objects, visit);
+                }
+                walk_stmts(&f.body.list, objects, visit);
+            }
+            Stmt::Range(r) => walk_stmts(&r.body.list, objects, visit),
+            Stmt::Switch(sw) => walk_stmts(&sw.body.list, objects, visit),
+            Stmt::TypeSwitch(sw) => walk_stmts(&sw.body.list, objects, visit),
+            Stmt::Select(sel) => walk_stmts(&sel.body.list, objects, visit),
+            Stmt::Case(case) => walk_stmts(&case.body, objects, visit),
+            Stmt::Comm(comm) => {
+                if let Some(comm_stmt) = &comm.comm {
+                    walk_stmts(std::slice::from_ref(comm_stmt), objects, visit);
+                }
+                walk_stmts(&comm.body, objects, visit);
+            }
+            Stmt::Labeled(key) => {
+                let labeled = &objects.l_stmts[*key];
+                walk_stmts(std::slice::from_ref(&labeled.stmt), objects, visit);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Like [`walk_stmts`], but visits a statement's nested blocks before the
+/// statement itself — useful for a caller that needs to fold up results
+/// from the inside out (e.g. "does every path through this `if` return"
+/// needs its branches' answers before it can answer for the `if`).
+pub fn walk_stmts_post_order<'a>(
+    stmts: &'a [Stmt],
+    objects: &'a AstObjects,
+    visit: &mut impl FnMut(&'a Stmt),
+) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Block(b) => walk_stmts_post_order(&b.list, objects, visit),
+            Stmt::If(i) => {
+                if let Some(init) = &i.init {
+                    walk_stmts_post_order(std::slice::from_ref(init), objects, visit);
+                }
+                walk_stmts_post_order(&i.body.list, objects, visit);
+                if let Some(els) = &i.els {
+                    walk_stmts_post_order(std::slice::from_ref(els), objects, visit);
+                }
+            }
+            Stmt::For(f) => {
+                if let Some(init) = &f.init {
+                    walk_stmts_post_order(std::slice::from_ref(init), objects, visit);
+                }
+                if let Some(post) = &f.post {
+                    walk_stmts_post_order(std::slice::from_ref(post), objects, visit);
+                }
+                walk_stmts_post_order(&f.body.list, objects, visit);
+            }
+            Stmt::Range(r) => walk_stmts_post_order(&r.body.list, objects, visit),
+            Stmt::Switch(sw) => walk_stmts_post_order(&sw.body.list, objects, visit),
+            Stmt::TypeSwitch(sw) => walk_stmts_post_order(&sw.body.list, objects, visit),
+            Stmt::Select(sel) => walk_stmts_post_order(&sel.body.list, objects, visit),
+            Stmt::Case(case) => walk_stmts_post_order(&case.body, objects, visit),
+            Stmt::Comm(comm) => {
+                if let Some(comm_stmt) = &comm.comm {
+                    walk_stmts_post_order(std::slice::from_ref(comm_stmt), objects, visit);
+                }
+                walk_stmts_post_order(&comm.body, objects, visit);
+            }
+            Stmt::Labeled(key) => {
+                let labeled = &objects.l_stmts[*key];
+                walk_stmts_post_order(std::slice::from_ref(&labeled.stmt), objects, visit);
+            }
+            _ => {}
+        }
+        visit(stmt);
+    }
+}
+
+/// Like [`walk_stmts`], but visits every statement at the current nesting
+/// depth before descending into any of their nested blocks — a
+/// breadth-first walk, level by level, rather than [`walk_stmts`]'s
+/// depth-first one.
+pub fn walk_stmts_breadth_first<'a>(
+    stmts: &'a [Stmt],
+    objects: &'a AstObjects,
+    visit: &mut impl FnMut(&'a Stmt),
+) {
+    let mut level: Vec<&'a Stmt> = stmts.iter().collect();
+    while !level.is_empty() {
+        let mut next = Vec::new();
+        for stmt in level {
+            visit(stmt);
+            match stmt {
+                Stmt::Block(b) => next.extend(&b.list),
+                Stmt::If(i) => {
+                    next.extend(&i.init);
+                    next.extend(&i.body.list);
+                    next.extend(&i.els);
+                }
+                Stmt::For(f) => {
+                    next.extend(&f.init);
+                    next.extend(&f.post);
+                    next.extend(&f.body.list);
+                }
+                Stmt::Range(r) => next.extend(&r.body.list),
+                Stmt::Switch(sw) => next.extend(&sw.body.list),
+                Stmt::TypeSwitch(sw) => next.extend(&sw.body.list),
+                Stmt::Select(sel) => next.extend(&sel.body.list),
+                Stmt::Case(case) => next.extend(&case.body),
+                Stmt::Comm(comm) => {
+                    next.extend(&comm.comm);
+                    next.extend(&comm.body);
+                }
+                Stmt::Labeled(key) => next.push(&objects.l_stmts[*key].stmt),
+                _ => {}
+            }
+        }
+        level = next;
+    }
+}
+
+/// Every statement in `stmts` (including nested ones) for which
+/// `predicate` returns true, in the same order [`walk_stmts`] visits
+/// them — e.g. `find_stmts_by(body, objects, |s| matches!(s, Stmt::If(_)))`
+/// to find every `if`, nested or not.
+pub fn find_stmts_by<'a>(
+    stmts: &'a [Stmt],
+    objects: &'a AstObjects,
+    predicate: impl Fn(&Stmt) -> bool,
+) -> Vec<&'a Stmt> {
+    let mut found = Vec::new();
+    walk_stmts(stmts, objects, &mut |stmt| {
+        if predicate(stmt) {
+            found.push(stmt);
+        }
+    });
+    found
+}
+
+/// The expressions a statement carries directly (its condition, its
+/// assigned values, its call target, ...) — not statements it contains,
+/// which [`walk_stmts`] already reaches on its own.
+fn direct_exprs(stmt: &Stmt, objects: &AstObjects) -> Vec<Expr> {
+    match stmt {
+        Stmt::Expr(e) => vec![(**e).clone()],
+        Stmt::Send(s) => vec![s.chan.clone(), s.val.clone()],
+        Stmt::IncDec(s) => vec![s.expr.clone()],
+        Stmt::Assign(key) => {
+            let a = &objects.a_stmts[*key];
+            a.lhs.iter().chain(&a.rhs).cloned().collect()
+        }
+        Stmt::Go(s) => vec![s.call.clone()],
+        Stmt::Defer(s) => vec![s.call.clone()],
+        Stmt::Return(s) => s.results.clone(),
+        Stmt::If(i) => vec![i.cond.clone()],
+        Stmt::For(f) => f.cond.clone().into_iter().collect(),
+        Stmt::Switch(sw) => sw.tag.clone().into_iter().collect(),
+        Stmt::Case(case) => case.list.clone().unwrap_or_default(),
+        Stmt::Range(r) => [r.key.clone(), r.val.clone(), Some(r.expr.clone())]
+            .into_iter()
+            .flatten()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Calls `visit` once for `expr`, then recurses into every sub-
+/// expression it contains (a call's function and arguments, a binary
+/// expression's two sides, a closure literal's own body, ...) — a
+/// pre-order walk of the expression tree rooted at `expr`. A
+/// `FuncLit`'s body is walked with [`walk_stmts`] plus [`direct_exprs`],
+/// so a call, channel op, or `recover()` written inside an inline
+/// `defer func() { ... }()` or `go func() { ... }()` closure is reached
+/// the same way one written directly in the enclosing function would be.
+pub fn walk_expr(expr: &Expr, objects: &AstObjects, visit: &mut impl FnMut(&Expr)) {
+    visit(expr);
+    match expr {
+        Expr::Paren(p) => walk_expr(&p.expr, objects, visit),
+        Expr::Selector(s) => walk_expr(&s.expr, objects, visit),
+        Expr::Index(i) => {
+            walk_expr(&i.expr, objects, visit);
+            walk_expr(&i.index, objects, visit);
+        }
+        Expr::Slice(s) => {
+            walk_expr(&s.expr, objects, visit);
+            for sub in [&s.low, &s.high, &s.max].into_iter().flatten() {
+                walk_expr(sub, objects, visit);
+            }
+        }
+        Expr::TypeAssert(t) => {
+            walk_expr(&t.expr, objects, visit);
+            if let Some(typ) = &t.typ {
+                walk_expr(typ, objects, visit);
+            }
+        }
+        Expr::Call(c) => {
+            walk_expr(&c.func, objects, visit);
+            for arg in &c.args {
+                walk_expr(arg, objects, visit);
+            }
+        }
+        Expr::Star(s) => walk_expr(&s.expr, objects, visit),
+        Expr::Unary(u) => walk_expr(&u.expr, objects, visit),
+        Expr::Binary(b) => {
+            walk_expr(&b.expr_a, objects, visit);
+            walk_expr(&b.expr_b, objects, visit);
+        }
+        Expr::KeyValue(kv) => {
+            walk_expr(&kv.key, objects, visit);
+            walk_expr(&kv.val, objects, visit);
+        }
+        Expr::FuncLit(lit) => {
+            walk_stmts(&lit.body.list, objects, &mut |stmt| {
+                for sub in direct_exprs(stmt, objects) {
+                    walk_expr(&sub, objects, visit);
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Every expression reachable from `stmts` (and their nested statements
+/// and sub-expressions, including closure bodies) for which `predicate`
+/// returns true, cloned out — e.g.
+/// `find_exprs_by(body, objects, |e| matches!(e, Expr::Call(_)))` to
+/// collect every call expression in a function, for a caller that needs
+/// the expressions themselves rather than just [`count_exprs_by`]'s count.
+pub fn find_exprs_by(
+    stmts: &[Stmt],
+    objects: &AstObjects,
+    predicate: impl Fn(&Expr) -> bool,
+) -> Vec<Expr> {
+    let mut found = Vec::new();
+    walk_stmts(stmts, objects, &mut |stmt| {
+        for expr in direct_exprs(stmt, objects) {
+            walk_expr(&expr, objects, &mut |e| {
+                if predicate(e) {
+                    found.push(e.clone());
+                }
+            });
+        }
+    });
+    found
+}
+
+/// Counts every expression reachable from `stmts` (and their nested
+/// statements and sub-expressions, including closure bodies) for which
+/// `predicate` returns true — e.g.
+/// `count_exprs_by(body, objects, |e| matches!(e, Expr::Call(_)))` to
+/// count every call expression in a function.
+pub fn count_exprs_by(
+    stmts: &[Stmt],
+    objects: &AstObjects,
+    predicate: impl Fn(&Expr) -> bool,
+) -> usize {
+    let mut count = 0;
+    walk_stmts(stmts, objects, &mut |stmt| {
+        for expr in direct_exprs(stmt, objects) {
+            walk_expr(&expr, objects, &mut |e| {
+                if predicate(e) {
+                    count += 1;
+                }
+            });
+        }
+    });
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goscript_parser::ast;
+    use std::io::Write;
+
+    fn parse_one(src: &str) -> crate::go_parser::ParseDirResult {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-ast-search-test-{}-{n}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::File::create(dir.join("a.go"))
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    #[test]
+    fn finds_every_if_stmt_including_nested_ones() {
+        let parsed = parse_one(
+            "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\tif x > 10 {\n\t\t\treturn 2\n\t\t}\n\t\treturn 1\n\t}\n\treturn 0\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let ast::Decl::Func(key) = pf.ast.decls[0] else { panic!("expected a func decl") };
+        let fdecl = &parsed.objects.fdecls[key];
+        let body = fdecl.body.as_ref().unwrap();
+
+        let ifs = find_stmts_by(&body.list, &parsed.objects, |s| matches!(s, Stmt::If(_)));
+        assert_eq!(ifs.len(), 2);
+    }
+
+    #[test]
+    fn counts_call_exprs_in_assignments_and_conditions() {
+        let parsed = parse_one(
+            "package main\n\nfunc f() int {\n\tx := g(h())\n\tif g(x) > 0 {\n\t\treturn x\n\t}\n\treturn 0\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let ast::Decl::Func(key) = pf.ast.decls[0] else { panic!("expected a func decl") };
+        let fdecl = &parsed.objects.fdecls[key];
+        let body = fdecl.body.as_ref().unwrap();
+
+        let calls = count_exprs_by(&body.list, &parsed.objects, |e| matches!(e, Expr::Call(_)));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn post_order_visits_a_nested_if_before_the_enclosing_one() {
+        let parsed = parse_one(
+            "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\tif x > 10 {\n\t\t\treturn 2\n\t\t}\n\t\treturn 1\n\t}\n\treturn 0\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let ast::Decl::Func(key) = pf.ast.decls[0] else { panic!("expected a func decl") };
+        let fdecl = &parsed.objects.fdecls[key];
+        let body = fdecl.body.as_ref().unwrap();
+
+        let mut order = Vec::new();
+        walk_stmts_post_order(&body.list, &parsed.objects, &mut |stmt| {
+            order.push(matches!(stmt, Stmt::If(_)));
+        });
+        let if_positions: Vec<usize> =
+            order.iter().enumerate().filter(|(_, is_if)| **is_if).map(|(i, _)| i).collect();
+        assert_eq!(if_positions.len(), 2);
+        assert!(
+            if_positions[0] < if_positions[1],
+            "the nested if should be visited before the outer one in post-order"
+        );
+    }
+
+    #[test]
+    fn breadth_first_visits_the_outer_if_before_the_nested_one() {
+        let parsed = parse_one(
+            "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\tif x > 10 {\n\t\t\treturn 2\n\t\t}\n\t\treturn 1\n\t}\n\treturn 0\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let ast::Decl::Func(key) = pf.ast.decls[0] else { panic!("expected a func decl") };
+        let fdecl = &parsed.objects.fdecls[key];
+        let body = fdecl.body.as_ref().unwrap();
+
+        let mut order = Vec::new();
+        walk_stmts_breadth_first(&body.list, &parsed.objects, &mut |stmt| {
+            order.push(matches!(stmt, Stmt::If(_)));
+        });
+        let if_positions: Vec<usize> =
+            order.iter().enumerate().filter(|(_, is_if)| **is_if).map(|(i, _)| i).collect();
+        assert_eq!(if_positions.len(), 2);
+        assert!(
+            if_positions[0] < if_positions[1],
+            "the outer if should be visited before the nested one in breadth-first order"
+        );
+    }
+
+    #[test]
+    fn counts_call_exprs_inside_a_deferred_closures_body() {
+        let parsed = parse_one(
+            "package main\n\nfunc f() {\n\tdefer func() {\n\t\trecover()\n\t}()\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let ast::Decl::Func(key) = pf.ast.decls[0] else { panic!("expected a func decl") };
+        let fdecl = &parsed.objects.fdecls[key];
+        let body = fdecl.body.as_ref().unwrap();
+
+        let calls = count_exprs_by(&body.list, &parsed.objects, |e| matches!(e, Expr::Call(_)));
+        // The closure's own call (`recover()`) plus the call that invokes
+        // the closure itself (`func() {...}()`).
+        assert_eq!(calls, 2);
+    }
+}
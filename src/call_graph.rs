@@ -0,0 +1,386 @@
+//! A heuristic call graph: which of a project's own declared functions
+//! call which others, and the fan-in/fan-out coupling figures derived
+//! from it.
+//!
+//! Resolving a call site's callee to a specific declared function has no
+//! type checker behind it here, the same way `interfaces`'s method-set
+//! matching doesn't: a callee is reduced to a bare name (the identifier
+//! itself for `f()`, the trailing name for a selector like `x.M()` or
+//! `pkg.F()`) and matched against the functions declared in the *same
+//! package* as the call site. That can't tell a qualified package call
+//! (`pkg.F()`) from a method call on some unrelated local variable
+//! (`x.F()`) — both look identical without types — and it won't resolve
+//! a call into another package's exported function at all. It's "good
+//! enough" for a project's own cross-function coupling the same way
+//! [`crate::interfaces::implementors_of`] is good enough for interface
+//! satisfaction, without claiming full soundness.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use goscript_parser::ast::{Decl, Expr};
+
+use crate::ast_search;
+use crate::go_parser::ParseDirResult;
+use crate::model::FunctionId;
+
+/// A resolved caller/callee edge: `from` contains a call site whose
+/// callee matched `to`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub struct CallEdge {
+    pub from: FunctionId,
+    pub to: FunctionId,
+}
+
+/// Every resolved call edge found across a parsed project.
+#[derive(Debug, Default, Clone)]
+pub struct CallGraph {
+    pub edges: BTreeSet<CallEdge>,
+}
+
+impl CallGraph {
+    /// The number of distinct functions `id` calls.
+    pub fn fan_out(&self, id: &FunctionId) -> usize {
+        self.edges.iter().filter(|edge| &edge.from == id).count()
+    }
+
+    /// The number of distinct functions that call `id`.
+    pub fn fan_in(&self, id: &FunctionId) -> usize {
+        self.edges.iter().filter(|edge| &edge.to == id).count()
+    }
+}
+
+/// The bare name a call expression's callee resolves to for matching
+/// purposes: the identifier itself for a direct call, or the trailing
+/// name for a selector-qualified one.
+fn callee_name(expr: &Expr, objects: &crate::go_parser::AstObjects) -> Option<String> {
+    match expr {
+        Expr::Ident(key) => Some(objects.idents[*key].name.clone()),
+        Expr::Selector(sel) => Some(objects.idents[sel.sel].name.clone()),
+        _ => None,
+    }
+}
+
+/// Every function declared anywhere in `parsed`, indexed by package name
+/// then by function name — the project's own lookup table for resolving
+/// a call site's callee name back to a [`FunctionId`].
+fn declared_functions(parsed: &ParseDirResult) -> BTreeMap<String, BTreeMap<String, FunctionId>> {
+    let mut by_package: BTreeMap<String, BTreeMap<String, FunctionId>> = BTreeMap::new();
+    for pkg in parsed.packages.values() {
+        let index = by_package.entry(pkg.name.clone()).or_default();
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                index.insert(
+                    name.clone(),
+                    FunctionId::new(pkg.name.clone(), file_name.clone(), name),
+                );
+            }
+        }
+    }
+    by_package
+}
+
+/// Builds the call graph for every function declared across `parsed`.
+pub fn build(parsed: &ParseDirResult) -> CallGraph {
+    let declared = declared_functions(parsed);
+    let mut graph = CallGraph::default();
+
+    for pkg in parsed.packages.values() {
+        let Some(index) = declared.get(&pkg.name) else { continue };
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+                let caller_name = parsed.objects.idents[fdecl.name].name.clone();
+                let from = FunctionId::new(pkg.name.clone(), file_name.clone(), caller_name.clone());
+
+                let calls = ast_search::find_exprs_by(&body.list, &parsed.objects, |e| {
+                    matches!(e, Expr::Call(_))
+                });
+                for call in calls {
+                    let Expr::Call(call) = call else { continue };
+                    let Some(name) = callee_name(&call.func, &parsed.objects) else { continue };
+                    if let Some(to) = index.get(&name) {
+                        graph.edges.insert(CallEdge {
+                            from: from.clone(),
+                            to: to.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+/// Renders `graph` as GraphML, for opening in a general-purpose graph
+/// tool like Gephi or yEd rather than Graphviz — the call-graph
+/// counterpart to [`crate::cfg_plugin::to_graphml`], with the same
+/// `id`/`label` node data keys and directed-edge shape. There's no
+/// `line` data key here, unlike the CFG version: a node is a whole
+/// function rather than a single statement, and [`FunctionId`] doesn't
+/// carry a declaration line to report.
+pub fn to_graphml(graph: &CallGraph) -> String {
+    let mut nodes: BTreeMap<&FunctionId, usize> = BTreeMap::new();
+    for edge in &graph.edges {
+        let next_index = nodes.len();
+        nodes.entry(&edge.from).or_insert(next_index);
+        let next_index = nodes.len();
+        nodes.entry(&edge.to).or_insert(next_index);
+    }
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    out.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    out.push_str("  <graph id=\"call-graph\" edgedefault=\"directed\">\n");
+
+    for (function, index) in &nodes {
+        out.push_str(&format!("    <node id=\"n{index}\">\n"));
+        out.push_str(&format!(
+            "      <data key=\"label\">{}</data>\n",
+            crate::cfg_plugin::escape_xml(&function.to_string())
+        ));
+        out.push_str("    </node>\n");
+    }
+
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "    <edge source=\"n{}\" target=\"n{}\"/>\n",
+            nodes[&edge.from], nodes[&edge.to]
+        ));
+    }
+
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
+
+/// User-facing knobs for [`analyze_coupling`].
+#[derive(Debug, Clone, Default)]
+pub struct CouplingOptions {
+    /// When set, any function whose fan-out exceeds this is listed in
+    /// the report's `high_fan_out`.
+    pub max_fan_out: Option<usize>,
+    /// When set, any function whose fan-in exceeds this is listed in
+    /// the report's `high_fan_in`.
+    pub max_fan_in: Option<usize>,
+}
+
+/// Fan-in/fan-out figures for a single function.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub struct Coupling {
+    pub function: FunctionId,
+    /// Distinct functions that call this one.
+    pub fan_in: usize,
+    /// Distinct functions this one calls.
+    pub fan_out: usize,
+}
+
+/// The result of [`analyze_coupling`]: fan-in/fan-out for every function
+/// that calls or is called by at least one other function in the
+/// project's own call graph, plus the subsets whose fan-in/fan-out
+/// exceeded `CouplingOptions`'s thresholds — likely refactoring targets,
+/// either because they depend on too much (`high_fan_out`) or because
+/// too much depends on them (`high_fan_in`).
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct CouplingReport {
+    pub functions: Vec<Coupling>,
+    pub high_fan_out: Vec<FunctionId>,
+    pub high_fan_in: Vec<FunctionId>,
+}
+
+/// Computes fan-in/fan-out for every function with at least one resolved
+/// call edge in `parsed`, flagging any that exceed `options`'s
+/// thresholds.
+pub fn analyze_coupling(parsed: &ParseDirResult, options: &CouplingOptions) -> CouplingReport {
+    let graph = build(parsed);
+
+    let mut functions = BTreeSet::new();
+    for edge in &graph.edges {
+        functions.insert(edge.from.clone());
+        functions.insert(edge.to.clone());
+    }
+
+    let mut report = CouplingReport::default();
+    for function in functions {
+        let fan_in = graph.fan_in(&function);
+        let fan_out = graph.fan_out(&function);
+
+        if options.max_fan_out.is_some_and(|max| fan_out > max) {
+            report.high_fan_out.push(function.clone());
+        }
+        if options.max_fan_in.is_some_and(|max| fan_in > max) {
+            report.high_fan_in.push(function.clone());
+        }
+
+        report.functions.push(Coupling {
+            function,
+            fan_in,
+            fan_out,
+        });
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(dir: &Path, name: &str, src: &str) {
+        fs::write(dir.join(name), src).unwrap();
+    }
+
+    #[test]
+    fn a_utility_function_called_by_three_others_has_fan_in_three() {
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-call-graph-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package util\n\
+             func helper(x int) int { return x * 2 }\n\
+             func a() int { return helper(1) }\n\
+             func b() int { return helper(2) + helper(3) }\n\
+             func c() int { return helper(4) }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let graph = build(&parsed);
+        let helper = FunctionId::new("util", "a.go", "helper");
+        let a = FunctionId::new("util", "a.go", "a");
+        let b = FunctionId::new("util", "a.go", "b");
+        let c = FunctionId::new("util", "a.go", "c");
+
+        assert_eq!(graph.fan_in(&helper), 3);
+        assert_eq!(graph.fan_out(&helper), 0);
+        assert_eq!(graph.fan_out(&a), 1);
+        // `b` calls `helper` twice, but fan-out counts distinct callees.
+        assert_eq!(graph.fan_out(&b), 1);
+        assert_eq!(graph.fan_out(&c), 1);
+        assert_eq!(graph.fan_in(&a), 0);
+    }
+
+    #[test]
+    fn a_call_into_a_different_package_is_not_resolved() {
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-call-graph-cross-pkg-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let pkg_a = dir.join("a");
+        let pkg_b = dir.join("b");
+        fs::create_dir_all(&pkg_a).unwrap();
+        fs::create_dir_all(&pkg_b).unwrap();
+        write(&pkg_a, "a.go", "package a\nfunc F() int { return 1 }\n");
+        write(
+            &pkg_b,
+            "b.go",
+            "package b\nimport \"example.com/proj/a\"\nfunc G() int { return a.F() }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let graph = build(&parsed);
+        let f = FunctionId::new("a", "a.go", "F");
+        assert_eq!(graph.fan_in(&f), 0);
+    }
+
+    #[test]
+    fn a_high_fan_in_utility_is_flagged_when_a_threshold_is_set() {
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-call-graph-coupling-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package util\n\
+             func helper(x int) int { return x * 2 }\n\
+             func a() int { return helper(1) }\n\
+             func b() int { return helper(2) }\n\
+             func c() int { return helper(3) }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let report = analyze_coupling(&parsed, &CouplingOptions { max_fan_in: Some(2), max_fan_out: None });
+        let helper = FunctionId::new("util", "a.go", "helper");
+        assert_eq!(report.high_fan_in, vec![helper.clone()]);
+        assert!(report.high_fan_out.is_empty());
+        let coupling = report.functions.iter().find(|c| c.function == helper).unwrap();
+        assert_eq!(coupling.fan_in, 3);
+        assert_eq!(coupling.fan_out, 0);
+    }
+
+    /// A minimal well-formedness check: every opening tag has a matching
+    /// close, and tags never close out of order. Not a full XML validator,
+    /// but enough to catch a malformed `to_graphml` output.
+    fn xml_is_well_formed(xml: &str) -> bool {
+        let mut depth: i32 = 0;
+        let mut pos = 0;
+        while let Some(rel) = xml[pos..].find('<') {
+            let start = pos + rel;
+            let Some(rel_end) = xml[start..].find('>') else {
+                return false;
+            };
+            let end = start + rel_end;
+            let tag = &xml[start + 1..end];
+            pos = end + 1;
+            if tag.starts_with('?') || tag.starts_with('!') || tag.ends_with('/') {
+                continue;
+            }
+            if tag.starts_with('/') {
+                depth -= 1;
+                if depth < 0 {
+                    return false;
+                }
+            } else {
+                depth += 1;
+            }
+        }
+        depth == 0
+    }
+
+    #[test]
+    fn to_graphml_emits_well_formed_xml_with_the_expected_node_and_edge_counts() {
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-call-graph-graphml-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package util\n\
+             func helper(x int) int { return x * 2 }\n\
+             func a() int { return helper(1) }\n\
+             func b() int { return helper(2) }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let graph = build(&parsed);
+        let xml = to_graphml(&graph);
+
+        assert!(xml_is_well_formed(&xml));
+        // Three distinct functions appear in an edge (helper, a, b); two edges (a->helper, b->helper).
+        assert_eq!(xml.matches("<node ").count(), 3);
+        assert_eq!(xml.matches("<edge ").count(), 2);
+    }
+}
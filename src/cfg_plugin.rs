@@ -0,0 +1,1945 @@
+//! Builds control-flow graphs (CFGs) for Go function bodies.
+//!
+//! One [`ControlFlowGraph`] per function: a sequence of [`BasicBlock`]s
+//! linked by `successors`/`predecessors`, entered at `entry` and (assuming
+//! the function returns normally on at least one path) eventually reaching
+//! `exit`. Code that can never run (e.g. statements after an unconditional
+//! `return`) is dropped from the graph by default; set
+//! [`CfgBuildOptions::keep_unreachable`] to keep it instead, as its own
+//! disconnected [`BasicBlock`]s with `reachable: false`.
+
+pub mod iface;
+
+use std::collections::BTreeMap;
+
+use goscript_parser::ast::{self, Node, Stmt};
+use goscript_parser::objects::Objects as AstObjects;
+use goscript_parser::token::Token;
+
+use crate::go_parser::line_of;
+use crate::go_parser::print::format_stmt;
+
+/// A single statement as it appears in a basic block: the AST node itself,
+/// plus a human-readable rendering of it (see [`crate::go_parser::print`]).
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub stmt: ast::Stmt,
+    pub text: String,
+}
+
+/// A maximal straight-line run of statements: control only ever enters at
+/// the top and leaves at the bottom.
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    pub statements: Vec<Statement>,
+    pub successors: Vec<usize>,
+    pub predecessors: Vec<usize>,
+    /// Whether `entry` can reach this block at all. Always `true` unless
+    /// [`CfgBuildOptions::keep_unreachable`] was set and this block is
+    /// dead code (statements after an unconditional `return`/`break`/
+    /// `continue`) that would otherwise have been dropped outright rather
+    /// than kept as a block with no predecessors.
+    pub reachable: bool,
+}
+
+impl Default for BasicBlock {
+    fn default() -> Self {
+        BasicBlock {
+            statements: Vec::new(),
+            successors: Vec::new(),
+            predecessors: Vec::new(),
+            reachable: true,
+        }
+    }
+}
+
+/// The control-flow graph of a single function body.
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph {
+    pub entry: usize,
+    pub exit: usize,
+    pub blocks: Vec<BasicBlock>,
+}
+
+/// Invariant violations found by [`ControlFlowGraph::validate`]. Building a
+/// report like this instead of printing warnings as they're found lets
+/// callers (and tests) assert on what went wrong instead of scraping stderr.
+/// A non-empty report doesn't stop anything — it's up to the caller to
+/// decide whether a violation matters for what they're doing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CfgValidation {
+    /// `(block, successor)` pairs where `successor` isn't a valid block id.
+    pub dangling_successors: Vec<(usize, usize)>,
+    /// Blocks other than `exit` itself with no successors at all — a block that
+    /// looks like it should fall through somewhere but doesn't.
+    pub sink_blocks: Vec<usize>,
+    /// Blocks not reachable from `entry` by following successor edges.
+    pub unreachable: Vec<usize>,
+}
+
+impl CfgValidation {
+    /// Whether every check passed.
+    pub fn is_valid(&self) -> bool {
+        self.dangling_successors.is_empty() && self.sink_blocks.is_empty() && self.unreachable.is_empty()
+    }
+}
+
+impl ControlFlowGraph {
+    /// Number of edges in the graph, counting each link once.
+    pub fn edge_count(&self) -> usize {
+        self.blocks.iter().map(|b| b.successors.len()).sum()
+    }
+
+    /// McCabe cyclomatic complexity computed directly from the graph's
+    /// shape: `edges - nodes + 2`, restricted to the blocks reachable from
+    /// `entry` (a graph built with [`CfgBuildOptions::keep_unreachable`]
+    /// shouldn't have dead code inflating the count of a function nobody
+    /// can actually diverge through). This is the classic `E - N + 2P`
+    /// formula with `P` (connected components) fixed at 1, since the
+    /// reachable set is by definition one component rooted at `entry` —
+    /// which also covers the empty-function case for free: a body with no
+    /// statements is just `entry` linked straight to `exit`, so `1 - 2 + 2`
+    /// still comes out to 1, the same base complexity every function has
+    /// before any branch is counted.
+    ///
+    /// This is a structural approximation, not a drop-in replacement for
+    /// [`crate::complexity::analyze_function_complexity`]'s own count: that
+    /// walk also credits `&&`/`||` inside a condition as extra decision
+    /// points and lets callers choose how `switch`/`select` arms count
+    /// (`SwitchCounting`), neither of which shows up as extra blocks or
+    /// edges here.
+    pub fn cyclomatic_complexity(&self) -> usize {
+        let reachable = self.reachable_from(self.entry);
+        let nodes = reachable.len();
+        let edges: usize = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(id, _)| reachable.contains(id))
+            .map(|(_, block)| block.successors.iter().filter(|s| reachable.contains(s)).count())
+            .sum();
+        edges + 2 - nodes
+    }
+
+    /// Checks this graph's structural invariants: every successor id must
+    /// point at a real block, every block other than `exit` should have a
+    /// way out, and every block should be reachable from `entry`. Doesn't
+    /// panic or print on failure — the violations found are returned for
+    /// the caller to act on (or ignore).
+    pub fn validate(&self) -> CfgValidation {
+        let len = self.blocks.len();
+
+        let mut dangling_successors = Vec::new();
+        for (id, block) in self.blocks.iter().enumerate() {
+            for &successor in &block.successors {
+                if successor >= len {
+                    dangling_successors.push((id, successor));
+                }
+            }
+        }
+
+        let mut seen = vec![false; len];
+        if self.entry < len {
+            let mut stack = vec![self.entry];
+            while let Some(block) = stack.pop() {
+                if seen[block] {
+                    continue;
+                }
+                seen[block] = true;
+                for &successor in &self.blocks[block].successors {
+                    if successor < len && !seen[successor] {
+                        stack.push(successor);
+                    }
+                }
+            }
+        }
+        let unreachable = (0..len).filter(|&id| !seen[id]).collect();
+
+        let sink_blocks = (0..len)
+            .filter(|&id| id != self.exit && self.blocks[id].successors.is_empty())
+            .collect();
+
+        CfgValidation {
+            dangling_successors,
+            sink_blocks,
+            unreachable,
+        }
+    }
+
+    /// Every block id reachable from `start` by following `successors`
+    /// edges, `start` itself included.
+    pub fn reachable_from(&self, start: usize) -> std::collections::HashSet<usize> {
+        let mut seen = std::collections::HashSet::new();
+        let mut stack = vec![start];
+        while let Some(block) = stack.pop() {
+            if !seen.insert(block) {
+                continue;
+            }
+            stack.extend(&self.blocks[block].successors);
+        }
+        seen
+    }
+
+    /// Whether `block` is reachable from `entry`.
+    pub fn is_reachable(&self, block: usize) -> bool {
+        self.reachable_from(self.entry).contains(&block)
+    }
+
+    /// Removes every non-entry, non-exit block with no statements whose
+    /// only successor is `exit`, rewiring its predecessors to point
+    /// straight at `exit` instead. Leaves `entry` and blocks that carry
+    /// statements (however trivial) alone — this only clears out the
+    /// synthetic empty joins optimized control flow tends to leave behind
+    /// on the way to `exit`, not anything a reader wrote.
+    pub fn collapse_trivial_exit_blocks(&mut self) {
+        let trivial: std::collections::HashSet<usize> = (0..self.blocks.len())
+            .filter(|&id| {
+                id != self.entry
+                    && id != self.exit
+                    && self.blocks[id].statements.is_empty()
+                    && self.blocks[id].successors == [self.exit]
+            })
+            .collect();
+        if trivial.is_empty() {
+            return;
+        }
+
+        // Rewrite every edge that pointed at a collapsed block to point
+        // at `exit` instead, before the blocks themselves are dropped.
+        for block in &mut self.blocks {
+            for successor in &mut block.successors {
+                if trivial.contains(successor) {
+                    *successor = self.exit;
+                }
+            }
+            block.successors.dedup();
+            for predecessor in &mut block.predecessors {
+                if trivial.contains(predecessor) {
+                    *predecessor = self.exit;
+                }
+            }
+            block.predecessors.dedup();
+        }
+        self.blocks[self.exit].predecessors.retain(|&p| p != self.exit);
+
+        let mut new_id = vec![0usize; self.blocks.len()];
+        let mut kept = Vec::with_capacity(self.blocks.len() - trivial.len());
+        for (old, block) in std::mem::take(&mut self.blocks).into_iter().enumerate() {
+            if trivial.contains(&old) {
+                continue;
+            }
+            new_id[old] = kept.len();
+            kept.push(block);
+        }
+        for block in &mut kept {
+            for successor in &mut block.successors {
+                *successor = new_id[*successor];
+            }
+            for predecessor in &mut block.predecessors {
+                *predecessor = new_id[*predecessor];
+            }
+        }
+        self.entry = new_id[self.entry];
+        self.exit = new_id[self.exit];
+        self.blocks = kept;
+    }
+
+    /// Points every sink block other than `exit` (what
+    /// [`Self::validate`] would report as [`CfgValidation::sink_blocks`])
+    /// straight at `exit`, so `exit` is the graph's only sink. `return`
+    /// already links to `exit` directly as the builder sees it, and
+    /// falling off the end of a function's body does too, so this has
+    /// nothing to do for ordinary code — it only matters for a graph an
+    /// optimization pass rewired into leaving some other block with
+    /// nowhere to go, or a `break`/`continue` outside any loop or switch,
+    /// which isn't valid Go but is syntactically parseable.
+    pub fn normalize_single_exit(&mut self) {
+        let sinks: Vec<usize> = (0..self.blocks.len())
+            .filter(|&id| id != self.exit && self.blocks[id].successors.is_empty())
+            .collect();
+        for sink in sinks {
+            self.blocks[sink].successors.push(self.exit);
+            self.blocks[self.exit].predecessors.push(sink);
+        }
+    }
+
+    /// Renumbers every block in deterministic DFS pre-order from `entry`,
+    /// so two builds of the same function produce identical ids and a
+    /// block's id stays meaningful even after something removes other
+    /// blocks — unlike ids from build order, which depend on the order
+    /// control-flow constructs happened to be visited while building.
+    ///
+    /// A block's successors are visited in their existing order, so the
+    /// "then" branch of an `if` is numbered before the "else" branch.
+    /// Blocks unreachable from `entry` (normally none, but
+    /// [`CfgBuildOptions::keep_unreachable`] can introduce them) keep
+    /// their relative order, appended after every reachable block — they
+    /// still get new, contiguous ids, just last, rather than being
+    /// silently dropped.
+    pub fn renumber(&mut self) {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut order = Vec::with_capacity(self.blocks.len());
+        let mut stack = vec![self.entry];
+        while let Some(block) = stack.pop() {
+            if visited[block] {
+                continue;
+            }
+            visited[block] = true;
+            order.push(block);
+            for &successor in self.blocks[block].successors.iter().rev() {
+                if !visited[successor] {
+                    stack.push(successor);
+                }
+            }
+        }
+        for (old, &was_visited) in visited.iter().enumerate() {
+            if !was_visited {
+                order.push(old);
+            }
+        }
+
+        let mut new_id = vec![0; self.blocks.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_id[old] = new;
+        }
+
+        let mut renumbered = vec![BasicBlock::default(); self.blocks.len()];
+        for (old, block) in std::mem::take(&mut self.blocks).into_iter().enumerate() {
+            renumbered[new_id[old]] = BasicBlock {
+                statements: block.statements,
+                successors: block.successors.iter().map(|&s| new_id[s]).collect(),
+                predecessors: block.predecessors.iter().map(|&p| new_id[p]).collect(),
+                reachable: block.reachable,
+            };
+        }
+        self.blocks = renumbered;
+        self.entry = new_id[self.entry];
+        self.exit = new_id[self.exit];
+    }
+}
+
+/// Either-or target for `break`/`continue`: loops accept both, `switch`
+/// and `select` only accept `break`.
+enum BreakableCtx {
+    Loop { continue_to: usize, break_to: usize },
+    Switch { break_to: usize },
+}
+
+struct Builder<'a> {
+    objects: &'a AstObjects,
+    blocks: Vec<BasicBlock>,
+    exit: usize,
+    breakable: Vec<BreakableCtx>,
+    /// Whether dead code (statements after a divergent one) should be kept
+    /// as disconnected, `reachable: false` blocks instead of dropped — see
+    /// [`CfgBuildOptions::keep_unreachable`].
+    keep_unreachable: bool,
+    /// Set for the duration of building a statement list once flow inside
+    /// it has already diverged, so every block [`Self::new_block`] creates
+    /// from then on — including ones nested control flow (`if`, `for`, …)
+    /// inside the dead code creates for itself — is correctly marked
+    /// unreachable too, not just the first one.
+    in_dead_code: bool,
+}
+
+impl<'a> Builder<'a> {
+    fn new(objects: &'a AstObjects, keep_unreachable: bool) -> Self {
+        let mut b = Builder {
+            objects,
+            blocks: Vec::new(),
+            exit: 0,
+            breakable: Vec::new(),
+            keep_unreachable,
+            in_dead_code: false,
+        };
+        b.new_block(); // entry, always block 0
+        b.exit = b.new_block();
+        b
+    }
+
+    fn new_block(&mut self) -> usize {
+        self.blocks.push(BasicBlock {
+            reachable: !self.in_dead_code,
+            ..BasicBlock::default()
+        });
+        self.blocks.len() - 1
+    }
+
+    fn link(&mut self, from: usize, to: usize) {
+        if !self.blocks[from].successors.contains(&to) {
+            self.blocks[from].successors.push(to);
+        }
+        if !self.blocks[to].predecessors.contains(&from) {
+            self.blocks[to].predecessors.push(from);
+        }
+    }
+
+    fn add_stmt(&mut self, block: usize, stmt: &Stmt) {
+        self.blocks[block].statements.push(Statement {
+            stmt: stmt.clone(),
+            text: format_stmt(stmt, self.objects),
+        });
+    }
+
+    /// Builds a statement list in sequence, short-circuiting once flow
+    /// diverges (e.g. after a `return`) — unless `keep_unreachable` is set,
+    /// in which case the remaining statements are built into a fresh,
+    /// disconnected block chain marked `reachable: false` rather than
+    /// dropped. Returns the block execution continues from, or `None` if
+    /// every path out of `stmts` diverged (and `keep_unreachable` is off).
+    fn build_stmts(&mut self, stmts: &[Stmt], start: Option<usize>) -> Option<usize> {
+        let was_dead = self.in_dead_code;
+        let mut current = start;
+        for stmt in stmts {
+            current = match current {
+                Some(cur) => self.build_stmt(stmt, cur),
+                None if self.keep_unreachable => {
+                    self.in_dead_code = true;
+                    let dead = self.new_block();
+                    self.build_stmt(stmt, dead)
+                }
+                None => break,
+            };
+        }
+        self.in_dead_code = was_dead;
+        current
+    }
+
+    fn build_stmt(&mut self, stmt: &Stmt, cur: usize) -> Option<usize> {
+        match stmt {
+            Stmt::Block(b) => self.build_stmts(&b.list, Some(cur)),
+            Stmt::If(i) => self.build_if(stmt, i, cur),
+            Stmt::For(f) => self.build_for(stmt, f, cur),
+            Stmt::Range(r) => self.build_range(stmt, r, cur),
+            Stmt::Switch(sw) => self.build_switch(stmt, &sw.body.list, cur),
+            Stmt::TypeSwitch(sw) => self.build_switch(stmt, &sw.body.list, cur),
+            Stmt::Select(sel) => self.build_select(stmt, &sel.body.list, cur),
+            Stmt::Return(_) => {
+                self.add_stmt(cur, stmt);
+                self.link(cur, self.exit);
+                None
+            }
+            Stmt::Branch(b) => self.build_branch(stmt, b, cur),
+            Stmt::Labeled(key) => {
+                let labeled = &self.objects.l_stmts[*key].stmt.clone();
+                self.build_stmt(labeled, cur)
+            }
+            _ => {
+                self.add_stmt(cur, stmt);
+                Some(cur)
+            }
+        }
+    }
+
+    fn build_branch(&mut self, stmt: &Stmt, b: &ast::BranchStmt, cur: usize) -> Option<usize> {
+        self.add_stmt(cur, stmt);
+        match b.token {
+            Token::BREAK => {
+                let target = self.breakable.last().map(|ctx| match ctx {
+                    BreakableCtx::Loop { break_to, .. } => *break_to,
+                    BreakableCtx::Switch { break_to } => *break_to,
+                });
+                if let Some(t) = target {
+                    self.link(cur, t);
+                }
+                None
+            }
+            Token::CONTINUE => {
+                let target = self.breakable.iter().rev().find_map(|ctx| match ctx {
+                    BreakableCtx::Loop { continue_to, .. } => Some(*continue_to),
+                    BreakableCtx::Switch { .. } => None,
+                });
+                if let Some(t) = target {
+                    self.link(cur, t);
+                }
+                None
+            }
+            // `goto`/`fallthrough` aren't modeled precisely; treat them as
+            // falling through so the rest of the CFG still makes sense.
+            _ => Some(cur),
+        }
+    }
+
+    fn build_if(&mut self, stmt: &Stmt, i: &ast::IfStmt, cur: usize) -> Option<usize> {
+        let cond_block = match &i.init {
+            Some(init) => self.build_stmt(init, cur).unwrap_or(cur),
+            None => cur,
+        };
+        self.add_stmt(cond_block, stmt);
+
+        let then_start = self.new_block();
+        self.link(cond_block, then_start);
+        let then_end = self.build_stmts(&i.body.list, Some(then_start));
+
+        let else_end = match &i.els {
+            Some(els) => {
+                let else_start = self.new_block();
+                self.link(cond_block, else_start);
+                self.build_stmt(els, else_start)
+            }
+            None => Some(cond_block),
+        };
+
+        match (then_end, else_end) {
+            (None, None) => None,
+            _ => {
+                let merge = self.new_block();
+                if let Some(e) = then_end {
+                    self.link(e, merge);
+                }
+                if let Some(e) = else_end {
+                    self.link(e, merge);
+                }
+                Some(merge)
+            }
+        }
+    }
+
+    fn build_for(&mut self, stmt: &Stmt, f: &ast::ForStmt, cur: usize) -> Option<usize> {
+        let head = match &f.init {
+            Some(init) => self.build_stmt(init, cur).unwrap_or(cur),
+            None => cur,
+        };
+        let cond_block = self.new_block();
+        self.link(head, cond_block);
+        self.add_stmt(cond_block, stmt);
+
+        let body_start = self.new_block();
+        self.link(cond_block, body_start);
+        let after = self.new_block();
+
+        self.breakable.push(BreakableCtx::Loop {
+            continue_to: cond_block,
+            break_to: after,
+        });
+        let body_end = self.build_stmts(&f.body.list, Some(body_start));
+        let back = match (&f.post, body_end) {
+            (Some(post), Some(b)) => self.build_stmt(post, b),
+            (None, Some(b)) => Some(b),
+            (_, None) => None,
+        };
+        self.breakable.pop();
+
+        if let Some(b) = back {
+            self.link(b, cond_block);
+        }
+        // `for {}` and `for true {}` never exit on their own, so `after`
+        // is only reachable via a `break` (already linked above while
+        // building the body) — not via an edge straight out of
+        // `cond_block`, the way a real condition would get one. Modeling
+        // this precisely is what lets a reachability check on `after`
+        // tell an infinite loop apart from a merely long-running one.
+        if !is_unconditionally_true(&f.cond, self.objects) {
+            self.link(cond_block, after);
+        }
+        Some(after)
+    }
+
+    fn build_range(&mut self, stmt: &Stmt, r: &ast::RangeStmt, cur: usize) -> Option<usize> {
+        let cond_block = self.new_block();
+        self.link(cur, cond_block);
+        self.add_stmt(cond_block, stmt);
+
+        let body_start = self.new_block();
+        self.link(cond_block, body_start);
+        let after = self.new_block();
+
+        self.breakable.push(BreakableCtx::Loop {
+            continue_to: cond_block,
+            break_to: after,
+        });
+        let body_end = self.build_stmts(&r.body.list, Some(body_start));
+        self.breakable.pop();
+
+        if let Some(b) = body_end {
+            self.link(b, cond_block);
+        }
+        self.link(cond_block, after);
+        Some(after)
+    }
+
+    fn build_switch(&mut self, stmt: &Stmt, clauses: &[Stmt], cur: usize) -> Option<usize> {
+        self.add_stmt(cur, stmt);
+        let after = self.new_block();
+        let mut has_default = false;
+        let mut ends = Vec::new();
+
+        self.breakable.push(BreakableCtx::Switch { break_to: after });
+        for clause in clauses {
+            let Stmt::Case(case) = clause else { continue };
+            if case.list.is_none() {
+                has_default = true;
+            }
+            let case_block = self.new_block();
+            self.link(cur, case_block);
+            ends.push(self.build_stmts(&case.body, Some(case_block)));
+        }
+        self.breakable.pop();
+
+        if !has_default {
+            self.link(cur, after);
+        }
+        for e in ends.into_iter().flatten() {
+            self.link(e, after);
+        }
+        Some(after)
+    }
+
+    fn build_select(&mut self, stmt: &Stmt, clauses: &[Stmt], cur: usize) -> Option<usize> {
+        self.add_stmt(cur, stmt);
+        let after = self.new_block();
+        let mut has_default = false;
+        let mut ends = Vec::new();
+
+        self.breakable.push(BreakableCtx::Switch { break_to: after });
+        for clause in clauses {
+            let Stmt::Comm(comm) = clause else { continue };
+            if comm.comm.is_none() {
+                has_default = true;
+            }
+            let comm_block = self.new_block();
+            self.link(cur, comm_block);
+            ends.push(self.build_stmts(&comm.body, Some(comm_block)));
+        }
+        self.breakable.pop();
+
+        if !has_default {
+            self.link(cur, after);
+        }
+        for e in ends.into_iter().flatten() {
+            self.link(e, after);
+        }
+        Some(after)
+    }
+}
+
+/// Whether a `for` loop's condition is missing (`for {}`) or the literal
+/// `true` (`for true {}`), the two syntactic ways to write a loop that
+/// never exits on its own.
+fn is_unconditionally_true(cond: &Option<ast::Expr>, objects: &AstObjects) -> bool {
+    match cond {
+        None => true,
+        Some(ast::Expr::Ident(key)) => objects.idents[*key].name == "true",
+        Some(_) => false,
+    }
+}
+
+/// Options controlling how [`build_cfg_with_options`] shapes the graph it
+/// returns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CfgBuildOptions {
+    /// Collapse an empty block (no statements) whose only successor is
+    /// `exit`, redirecting its predecessors straight to `exit` instead of
+    /// through it. Optimized `if`/`switch` bodies without an `else`/
+    /// `default` branch tend to leave several of these dangling en route
+    /// to `exit`, which clutter a DOT rendering without adding
+    /// information. Off by default: it changes block ids and count,
+    /// which a caller that already keys off of them (a saved DOT graph,
+    /// a snapshot test) wouldn't expect.
+    pub collapse_trivial_exit_blocks: bool,
+    /// Guarantee `exit` is the graph's only sink by rerouting any other
+    /// sink block straight to it — see [`ControlFlowGraph::normalize_single_exit`].
+    /// Off by default for the same reason as `collapse_trivial_exit_blocks`:
+    /// it can add edges a caller keying off the raw block count/shape
+    /// wouldn't expect, and ordinary code never needs it in the first
+    /// place.
+    pub single_exit: bool,
+    /// Keep statements after a divergent one (`return`, an unconditional
+    /// `break`/`continue`) instead of dropping them: they're built into
+    /// their own disconnected [`BasicBlock`]s with `reachable: false`
+    /// rather than being silently discarded. Metrics that only care about
+    /// live code (cyclomatic complexity, reachable-path length) should
+    /// leave this off; a viewer that wants to show a reader their own dead
+    /// code — dimmed, say — needs it on. Off by default: it changes block
+    /// ids and count, and most callers built against the pruned graph
+    /// don't expect blocks with no predecessors to show up at all.
+    pub keep_unreachable: bool,
+}
+
+/// Builds the CFG for a single function body.
+pub fn build_cfg(body: &ast::BlockStmt, objects: &AstObjects) -> ControlFlowGraph {
+    build_cfg_with_options(body, objects, CfgBuildOptions::default())
+}
+
+/// Builds the CFG for a single function body, per `options`. See
+/// [`build_cfg`] for the common case.
+pub fn build_cfg_with_options(
+    body: &ast::BlockStmt,
+    objects: &AstObjects,
+    options: CfgBuildOptions,
+) -> ControlFlowGraph {
+    let mut builder = Builder::new(objects, options.keep_unreachable);
+    let end = builder.build_stmts(&body.list, Some(0));
+    if let Some(e) = end {
+        builder.link(e, builder.exit);
+    }
+    let mut cfg = ControlFlowGraph {
+        entry: 0,
+        exit: builder.exit,
+        blocks: builder.blocks,
+    };
+    if options.collapse_trivial_exit_blocks {
+        cfg.collapse_trivial_exit_blocks();
+    }
+    if options.single_exit {
+        cfg.normalize_single_exit();
+    }
+    cfg.renumber();
+    cfg
+}
+
+/// A method's receiver, rendered the way Go itself writes a method
+/// expression: `T.M` for a value receiver, `(*T).M` for a pointer
+/// receiver (the parens are needed there since `*T.M` would parse as
+/// `*(T.M)`).
+fn receiver_prefix(fdecl: &ast::FuncDecl, objects: &AstObjects) -> Option<String> {
+    let recv_field_key = fdecl.recv.as_ref()?.list.first()?;
+    match &objects.fields[*recv_field_key].typ {
+        ast::Expr::Ident(key) => Some(objects.idents[*key].name.clone()),
+        ast::Expr::Star(star) => match &star.expr {
+            ast::Expr::Ident(key) => Some(format!("(*{})", objects.idents[*key].name)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The keyed CFG for one function declaration, or `None` for a
+/// declaration with no body (an external/assembly-linked function).
+fn cfg_entry_for_decl(decl: &ast::Decl, objects: &AstObjects) -> Option<(String, ControlFlowGraph)> {
+    let ast::Decl::Func(key) = decl else { return None };
+    let fdecl = &objects.fdecls[*key];
+    let body = fdecl.body.as_ref()?;
+    let name = objects.idents[fdecl.name].name.clone();
+    let key = match receiver_prefix(fdecl, objects) {
+        Some(prefix) => format!("{prefix}.{name}"),
+        None => name,
+    };
+    Some((key, build_cfg(body, objects)))
+}
+
+/// Builds a CFG for every function declared directly in `file` (methods
+/// included), keyed by function name — or, for a method, by
+/// [`receiver_prefix`] plus name (e.g. `(*T).M`), so that methods of the
+/// same name on different receiver types don't collide in the map.
+///
+/// Each function's CFG only reads `objects`, which reads like an
+/// embarrassingly parallel loop over `file.decls` — but `objects`'
+/// `ast::Expr` includes `Rc`-holding variants (e.g. `ChanType`), which
+/// makes the vendored AST `!Sync`, so it can't actually be shared across
+/// [`std::thread::scope`] worker threads without unsound casting. Fixing
+/// that would mean the vendored `goscript-parser` switching those `Rc`s
+/// to `Arc`, which is out of scope here; this stays a single sequential
+/// walk until that's done upstream.
+pub fn build_cfgs_for_file(
+    file: &ast::File,
+    objects: &AstObjects,
+) -> BTreeMap<String, ControlFlowGraph> {
+    file.decls.iter().filter_map(|decl| cfg_entry_for_decl(decl, objects)).collect()
+}
+
+/// Builds a CFG for every function in every file of every package in
+/// `parsed`, keyed by [`crate::model::FunctionId`] rather than
+/// [`build_cfgs_for_file`]'s bare name — the project-wide view a plugin
+/// function wraps, since a single project can (and normally does) have
+/// more than one file.
+pub fn build_cfgs_for_project(
+    parsed: &crate::go_parser::ParseDirResult,
+) -> BTreeMap<crate::model::FunctionId, ControlFlowGraph> {
+    let mut cfgs = BTreeMap::new();
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for (name, cfg) in build_cfgs_for_file(&pf.ast, &parsed.objects) {
+                let function = crate::model::FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+                cfgs.insert(function, cfg);
+            }
+        }
+    }
+    cfgs
+}
+
+/// Caps [`build_cfgs_for_file_with_guards`] puts on what it's willing to
+/// build a CFG for, so a pathological input (a generated file with tens
+/// of thousands of functions, or one absurdly long function) is skipped
+/// with a warning instead of stalling the whole run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CfgGuardOptions {
+    /// Skip every function in the file, returning a warning instead of
+    /// any CFGs, once the file declares more functions than this.
+    /// `None` means unlimited.
+    pub max_functions_per_file: Option<usize>,
+    /// Skip a single function, returning a warning instead of its CFG,
+    /// once its body has more than this many statements (nested ones
+    /// included). `None` means unlimited.
+    pub max_statements_per_function: Option<usize>,
+}
+
+/// The number of statements in `stmts`, nested ones included — the same
+/// count [`CfgGuardOptions::max_statements_per_function`] is checked
+/// against.
+fn count_statements(stmts: &[Stmt], objects: &AstObjects) -> usize {
+    let mut count = 0;
+    crate::ast_search::walk_stmts(stmts, objects, &mut |_| count += 1);
+    count
+}
+
+/// Builds a CFG for every function declared directly in `file`, the same
+/// as [`build_cfgs_for_file`], except a file or function that trips one
+/// of `guards`' limits is skipped with a warning message instead of
+/// being built. See [`build_cfgs_for_file`] for the common,
+/// unguarded case.
+pub fn build_cfgs_for_file_with_guards(
+    file: &ast::File,
+    objects: &AstObjects,
+    guards: CfgGuardOptions,
+) -> (BTreeMap<String, ControlFlowGraph>, Vec<String>) {
+    let mut warnings = Vec::new();
+    let func_count = file.decls.iter().filter(|d| matches!(d, ast::Decl::Func(_))).count();
+    if let Some(max) = guards.max_functions_per_file
+        && func_count > max
+    {
+        warnings.push(format!(
+            "skipped {func_count} functions: exceeds the configured limit of {max} per file"
+        ));
+        return (BTreeMap::new(), warnings);
+    }
+
+    let mut cfgs = BTreeMap::new();
+    for decl in &file.decls {
+        let ast::Decl::Func(key) = decl else { continue };
+        let fdecl = &objects.fdecls[*key];
+        let Some(body) = fdecl.body.as_ref() else { continue };
+        let name = objects.idents[fdecl.name].name.clone();
+        let key_name = match receiver_prefix(fdecl, objects) {
+            Some(prefix) => format!("{prefix}.{name}"),
+            None => name,
+        };
+
+        if let Some(max) = guards.max_statements_per_function {
+            let stmt_count = count_statements(&body.list, objects);
+            if stmt_count > max {
+                warnings.push(format!(
+                    "{key_name}: skipped, {stmt_count} statements exceeds the configured limit of {max}"
+                ));
+                continue;
+            }
+        }
+        cfgs.insert(key_name, build_cfg(body, objects));
+    }
+    (cfgs, warnings)
+}
+
+/// [`build_cfgs_for_project`]'s result, plus any warnings
+/// [`build_cfgs_for_project_with_guards`] recorded while skipping a file
+/// or function that tripped a [`CfgGuardOptions`] limit.
+#[derive(Debug, Clone, Default)]
+pub struct CfgBuildResult {
+    pub cfgs: BTreeMap<crate::model::FunctionId, ControlFlowGraph>,
+    pub warnings: Vec<String>,
+}
+
+/// Builds a CFG for every function in every file of every package in
+/// `parsed`, the same as [`build_cfgs_for_project`], except a file or
+/// function that trips one of `guards`' limits is skipped with a warning
+/// instead of being built. See [`build_cfgs_for_project`] for the
+/// common, unguarded case.
+pub fn build_cfgs_for_project_with_guards(
+    parsed: &crate::go_parser::ParseDirResult,
+    guards: CfgGuardOptions,
+) -> CfgBuildResult {
+    let mut result = CfgBuildResult::default();
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            let (cfgs, warnings) = build_cfgs_for_file_with_guards(&pf.ast, &parsed.objects, guards);
+            for (name, cfg) in cfgs {
+                let function = crate::model::FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+                result.cfgs.insert(function, cfg);
+            }
+            result.warnings.extend(warnings);
+        }
+    }
+    result
+}
+
+/// Escapes text for use inside GraphML character data.
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Renders `cfg` as GraphML, for opening in a general-purpose graph tool
+/// like Gephi or yEd rather than Graphviz. Each node carries `id`
+/// (`n<block index>`), `label` (that block's statements, Go-like text
+/// joined by newlines), and `line` (the source line of its first
+/// statement, omitted for a block with none, e.g. the CFG's exit node).
+///
+/// This only covers a single function's CFG, unlike `gocyclo`-style
+/// per-function views; see [`crate::call_graph::to_graphml`] for the
+/// project-wide equivalent over the call graph, which reuses
+/// [`escape_xml`] and the same node/edge shape.
+pub fn to_graphml(cfg: &ControlFlowGraph, objects: &AstObjects, source: &str, base: usize) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    out.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"line\" for=\"node\" attr.name=\"line\" attr.type=\"int\"/>\n");
+    out.push_str("  <graph id=\"cfg\" edgedefault=\"directed\">\n");
+
+    for (index, block) in cfg.blocks.iter().enumerate() {
+        let label = block
+            .statements
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        out.push_str(&format!("    <node id=\"n{index}\">\n"));
+        out.push_str(&format!(
+            "      <data key=\"label\">{}</data>\n",
+            escape_xml(&label)
+        ));
+        if let Some(first) = block.statements.first() {
+            let line = line_of(source, base, first.stmt.pos(objects));
+            out.push_str(&format!("      <data key=\"line\">{line}</data>\n"));
+        }
+        out.push_str("    </node>\n");
+    }
+
+    for (index, block) in cfg.blocks.iter().enumerate() {
+        for &successor in &block.successors {
+            out.push_str(&format!(
+                "    <edge source=\"n{index}\" target=\"n{successor}\"/>\n"
+            ));
+        }
+    }
+
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
+
+/// The 1-based line range `block`'s statements span within `source`
+/// (given `base`, `source`'s own offset into the shared `FileSet`), or
+/// `None` for a block with no statements of its own (`entry`/`exit`, or
+/// an empty straight-line run).
+fn block_line_range(
+    block: &BasicBlock,
+    objects: &AstObjects,
+    source: &str,
+    base: usize,
+) -> Option<(usize, usize)> {
+    let mut stmts = block.statements.iter();
+    let first = stmts.next()?;
+    let mut min = line_of(source, base, first.stmt.pos(objects));
+    let mut max = line_of(source, base, first.stmt.end(objects).saturating_sub(1));
+    for s in stmts {
+        min = min.min(line_of(source, base, s.stmt.pos(objects)));
+        max = max.max(line_of(source, base, s.stmt.end(objects).saturating_sub(1)));
+    }
+    Some((min, max))
+}
+
+/// The id of the block whose statements' line range contains `line`, for
+/// an editor integration that wants "which block is the cursor in".
+///
+/// This is a free function taking `source`/`base` rather than an
+/// inherent `&self` method: a statement's `Pos` is only meaningful
+/// relative to the source text and `FileSet` offset it was parsed from,
+/// neither of which `ControlFlowGraph` itself keeps around — the same
+/// reason [`to_graphml`] takes them as parameters instead of storing
+/// them on the graph.
+///
+/// When more than one block's range contains `line` (a statement's own
+/// line is also within its enclosing straight-line run's overall span),
+/// the block with the smallest (most specific) range wins, so a line
+/// inside an `if`'s `then` body resolves to that inner block rather than
+/// whichever surrounding block happens to also span it.
+pub fn block_at_line(
+    cfg: &ControlFlowGraph,
+    objects: &AstObjects,
+    source: &str,
+    base: usize,
+    line: usize,
+) -> Option<usize> {
+    cfg.blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(id, block)| block_line_range(block, objects, source, base).map(|range| (id, range)))
+        .filter(|(_, (start, end))| *start <= line && line <= *end)
+        .min_by_key(|(_, (start, end))| end - start)
+        .map(|(id, _)| id)
+}
+
+/// Full text of each block whose label [`to_dot`] truncated, keyed by
+/// block index — a sidecar a caller can render as tooltip/hover text
+/// without the DOT source itself growing unreadable.
+pub type DotFullText = BTreeMap<usize, String>;
+
+/// Renders `cfg` as a Graphviz DOT digraph, one node per block (the
+/// entry and exit drawn as `doublecircle`s, everything else a `box`),
+/// one edge per successor link.
+///
+/// A block's label is its statements' text joined by `\n`, truncated to
+/// `max_label_len` characters with a trailing `...` when longer — a
+/// function with a long literal or call chain can otherwise produce a
+/// label wide enough that Graphviz renders it unreadably or not at all.
+/// Any block that got truncated has its full text recorded in the
+/// returned map instead, so a caller (an editor hover, a web viewer)
+/// can still show it on demand.
+///
+/// A block with `reachable: false` (see [`CfgBuildOptions::keep_unreachable`])
+/// is drawn dashed and dimmed, rather than looking identical to the live
+/// code around it.
+///
+/// There's no kernel wiring for this yet, same as [`to_graphml`] — left
+/// for when per-function CFG plugin functions exist to hang it off of.
+pub fn to_dot(cfg: &ControlFlowGraph, max_label_len: usize) -> (String, DotFullText) {
+    let mut out = String::from("digraph cfg {\n");
+    let mut full_text = BTreeMap::new();
+
+    for (index, block) in cfg.blocks.iter().enumerate() {
+        let label = block
+            .statements
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\\n");
+        let shape = if index == cfg.entry || index == cfg.exit {
+            "doublecircle"
+        } else {
+            "box"
+        };
+        let rendered = if label.chars().count() > max_label_len {
+            let short: String = label.chars().take(max_label_len).collect();
+            full_text.insert(index, label);
+            format!("{short}...")
+        } else {
+            label
+        };
+        let style = if block.reachable {
+            String::new()
+        } else {
+            ", style=dashed, fontcolor=gray50".to_string()
+        };
+        out.push_str(&format!("    {index} [shape={shape}, label={rendered:?}{style}];\n"));
+    }
+
+    for (index, block) in cfg.blocks.iter().enumerate() {
+        for &successor in &block.successors {
+            out.push_str(&format!("    {index} -> {successor};\n"));
+        }
+    }
+
+    out.push_str("}\n");
+    (out, full_text)
+}
+
+/// Replaces every character in `name` that isn't valid in an unquoted DOT
+/// identifier with `_` (this also covers the unicode letters/digits Go
+/// allows in identifiers but DOT doesn't), and prefixes a leading digit
+/// with `_` (a bare digit can't start an unquoted DOT identifier either).
+/// Either change is lossy — `a.b` and `a_b` would otherwise both sanitize
+/// to `a_b`, silently merging two functions' clusters/node ids — so
+/// whenever a character was actually replaced or a digit prefixed, a
+/// short hash of the *original* `name` is appended to keep distinct
+/// inputs mapped to distinct ids. Names that were already valid DOT
+/// identifiers (the common case: plain ASCII Go names) pass through
+/// unchanged, so existing `.dot` output doesn't churn for no reason.
+fn sanitize_dot_id(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut lossy = false;
+
+    for (i, c) in name.chars().enumerate() {
+        if i == 0 && c.is_ascii_digit() {
+            out.push('_');
+            lossy = true;
+        }
+        if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c);
+        } else {
+            out.push('_');
+            lossy = true;
+        }
+    }
+
+    if lossy {
+        out.push('_');
+        out.push_str(&short_hash(name));
+    }
+    out
+}
+
+/// A short, stable hex digest of `s` (FNV-1a, truncated to 6 hex digits),
+/// used by [`sanitize_dot_id`] to disambiguate names that collide after
+/// lossy sanitization. Not cryptographic — collision-resistance for a
+/// handful of function names per project is all this needs.
+fn short_hash(s: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in s.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:06x}", hash & 0xff_ffff)
+}
+
+/// Full text of each block whose label [`to_dot_combined`] truncated,
+/// keyed by that block's namespaced node id (e.g. `"f_2"`) rather than
+/// the bare block index [`DotFullText`] uses, since a combined file can
+/// have the same index in more than one function.
+pub type CombinedDotFullText = BTreeMap<String, String>;
+
+/// Combines every function in `cfgs` into one Graphviz DOT digraph, each
+/// drawn in its own `cluster_<function>` subgraph so Graphviz renders
+/// them as visibly separate regions with a navigable label, instead of
+/// one undifferentiated pile of nodes.
+///
+/// Block ids are namespaced per function (`<sanitized function
+/// name>_<block index>`) rather than reused as each [`ControlFlowGraph`]'s
+/// own 0-based indices: [`to_dot`]'s indices are only unique within a
+/// single function's own graph, and reusing them as-is here would make
+/// block `0` of one function collide with block `0` of every other,
+/// letting an edge appear to cross between clusters that aren't actually
+/// connected.
+///
+/// As with [`to_dot`], a block with `reachable: false` is drawn dashed
+/// and dimmed.
+pub fn to_dot_combined(
+    cfgs: &BTreeMap<String, ControlFlowGraph>,
+    max_label_len: usize,
+) -> (String, CombinedDotFullText) {
+    let mut out = String::from("digraph cfg {\n");
+    let mut full_text = BTreeMap::new();
+
+    for (name, cfg) in cfgs {
+        let prefix = sanitize_dot_id(name);
+        out.push_str(&format!("  subgraph cluster_{prefix} {{\n"));
+        out.push_str(&format!("    label={name:?};\n"));
+        for (index, block) in cfg.blocks.iter().enumerate() {
+            let node_id = format!("{prefix}_{index}");
+            let label = block
+                .statements
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\\n");
+            let shape = if index == cfg.entry || index == cfg.exit {
+                "doublecircle"
+            } else {
+                "box"
+            };
+            let rendered = if label.chars().count() > max_label_len {
+                let short: String = label.chars().take(max_label_len).collect();
+                full_text.insert(node_id.clone(), label);
+                format!("{short}...")
+            } else {
+                label
+            };
+            let style = if block.reachable {
+                String::new()
+            } else {
+                ", style=dashed, fontcolor=gray50".to_string()
+            };
+            out.push_str(&format!("    {node_id} [shape={shape}, label={rendered:?}{style}];\n"));
+        }
+        out.push_str("  }\n");
+    }
+
+    for (name, cfg) in cfgs {
+        let prefix = sanitize_dot_id(name);
+        for (index, block) in cfg.blocks.iter().enumerate() {
+            for &successor in &block.successors {
+                out.push_str(&format!("  {prefix}_{index} -> {prefix}_{successor};\n"));
+            }
+        }
+    }
+
+    out.push_str("}\n");
+    (out, full_text)
+}
+
+/// Every back edge in `cfg`'s successor graph — an edge `(from, to)`
+/// where `to` is still on the current DFS stack when `from`'s successors
+/// are visited, i.e. it closes a cycle back to an ancestor rather than
+/// descending further. This is exactly how a loop shows up in a CFG
+/// built by [`build_cfg`] (see `build_for`'s own `back` edge), so
+/// removing these edges turns the graph into a DAG with the loop body
+/// visited once instead of traversed forever.
+fn back_edges(cfg: &ControlFlowGraph) -> std::collections::HashSet<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        OnStack,
+        Done,
+    }
+
+    fn visit(
+        node: usize,
+        cfg: &ControlFlowGraph,
+        state: &mut [State],
+        back: &mut std::collections::HashSet<(usize, usize)>,
+    ) {
+        state[node] = State::OnStack;
+        for &successor in &cfg.blocks[node].successors {
+            match state[successor] {
+                State::Unvisited => visit(successor, cfg, state, back),
+                State::OnStack => {
+                    back.insert((node, successor));
+                }
+                State::Done => {}
+            }
+        }
+        state[node] = State::Done;
+    }
+
+    let mut state = vec![State::Unvisited; cfg.blocks.len()];
+    let mut back = std::collections::HashSet::new();
+    if cfg.entry < cfg.blocks.len() {
+        visit(cfg.entry, cfg, &mut state, &mut back);
+    }
+    back
+}
+
+/// The number of blocks (`entry` and `exit` both included) on the
+/// longest simple path from `entry` to `exit`, with every loop's back
+/// edge ([`back_edges`]) removed first so a loop body is only ever
+/// counted once no matter how many times it could actually run. A proxy
+/// for a function's worst-case straight-line length, complementing
+/// cyclomatic complexity (which counts decision points, not length).
+/// `0` if `exit` isn't reachable from `entry` once back edges are gone
+/// (e.g. a loop with no break out of it).
+pub fn longest_acyclic_path_len(cfg: &ControlFlowGraph) -> usize {
+    let back = back_edges(cfg);
+    let mut memo: Vec<Option<usize>> = vec![None; cfg.blocks.len()];
+
+    fn longest_from(
+        node: usize,
+        exit: usize,
+        cfg: &ControlFlowGraph,
+        back: &std::collections::HashSet<(usize, usize)>,
+        memo: &mut [Option<usize>],
+    ) -> usize {
+        if let Some(cached) = memo[node] {
+            return cached;
+        }
+        let result = if node == exit {
+            1
+        } else {
+            cfg.blocks[node]
+                .successors
+                .iter()
+                .filter(|&&successor| !back.contains(&(node, successor)))
+                .map(|&successor| longest_from(successor, exit, cfg, back, memo))
+                .filter(|&len| len > 0)
+                .max()
+                .map_or(0, |len| len + 1)
+        };
+        memo[node] = Some(result);
+        result
+    }
+
+    if cfg.entry >= cfg.blocks.len() || cfg.exit >= cfg.blocks.len() {
+        return 0;
+    }
+    longest_from(cfg.entry, cfg.exit, cfg, &back, &mut memo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn parse_one(src: &str) -> crate::go_parser::ParseDirResult {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-cfg-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::File::create(dir.join("a.go"))
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    #[test]
+    fn collapsing_trivial_exit_blocks_removes_empty_joins_without_changing_reachability() {
+        let src = "package main\nfunc f(x int) {\n\tif x > 0 {\n\t\tif x > 10 {\n\t\t\treturn\n\t\t}\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let body = match &pf.ast.decls[0] {
+            ast::Decl::Func(key) => parsed.objects.fdecls[*key].body.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+
+        let plain = build_cfg(body, &parsed.objects);
+        let collapsed =
+            build_cfg_with_options(
+                body,
+                &parsed.objects,
+                CfgBuildOptions { collapse_trivial_exit_blocks: true, ..CfgBuildOptions::default() },
+            );
+
+        assert!(collapsed.blocks.len() < plain.blocks.len());
+        assert!(collapsed.validate().is_valid());
+        assert!(collapsed.is_reachable(collapsed.exit));
+    }
+
+    #[test]
+    fn single_exit_normalization_leaves_exactly_one_sink_and_every_return_reaches_it() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let body = match &pf.ast.decls[0] {
+            ast::Decl::Func(key) => parsed.objects.fdecls[*key].body.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+
+        let cfg =
+            build_cfg_with_options(body, &parsed.objects, CfgBuildOptions { single_exit: true, ..CfgBuildOptions::default() });
+
+        let sinks: Vec<usize> = (0..cfg.blocks.len()).filter(|&id| cfg.blocks[id].successors.is_empty()).collect();
+        assert_eq!(sinks, vec![cfg.exit]);
+        assert!(cfg.validate().is_valid());
+        for (id, block) in cfg.blocks.iter().enumerate() {
+            if id != cfg.exit && block.statements.iter().any(|s| matches!(s.stmt, Stmt::Return(_))) {
+                assert!(block.successors.contains(&cfg.exit), "block {id} with a return should reach exit");
+            }
+        }
+    }
+
+    #[test]
+    fn keep_unreachable_surfaces_dead_code_after_a_return_as_its_own_block() {
+        let src = "package main\nfunc f(x int) int {\n\treturn x\n\ty := 1\n\t_ = y\n}\n";
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let body = match &pf.ast.decls[0] {
+            ast::Decl::Func(key) => parsed.objects.fdecls[*key].body.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+
+        let plain = build_cfg(body, &parsed.objects);
+        assert!(
+            plain.blocks.iter().all(|b| !b.statements.iter().any(|s| s.text == "y := 1")),
+            "dead code should be dropped when keep_unreachable is off"
+        );
+
+        let kept = build_cfg_with_options(
+            body,
+            &parsed.objects,
+            CfgBuildOptions { keep_unreachable: true, ..CfgBuildOptions::default() },
+        );
+        let dead_block = kept
+            .blocks
+            .iter()
+            .position(|b| b.statements.iter().any(|s| s.text == "y := 1"))
+            .expect("dead code should appear as its own block when keep_unreachable is on");
+
+        assert!(!kept.blocks[dead_block].reachable);
+        assert!(kept.blocks[dead_block].predecessors.is_empty());
+        assert!(!kept.is_reachable(dead_block));
+    }
+
+    #[test]
+    fn build_cfgs_for_file_matches_building_each_declaration_one_at_a_time() {
+        let mut src = String::from("package main\n\n");
+        for i in 0..20 {
+            src.push_str(&format!("func f{i}(x int) int {{\n\tif x > {i} {{\n\t\treturn x\n\t}}\n\treturn {i}\n}}\n\n"));
+        }
+        let parsed = parse_one(&src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+
+        let batch = build_cfgs_for_file(&pf.ast, &parsed.objects);
+        let one_at_a_time: BTreeMap<String, ControlFlowGraph> = pf
+            .ast
+            .decls
+            .iter()
+            .filter_map(|decl| cfg_entry_for_decl(decl, &parsed.objects))
+            .collect();
+
+        assert_eq!(batch.len(), 20);
+        for (name, cfg) in &one_at_a_time {
+            assert_eq!(cfg.blocks.len(), batch[name].blocks.len());
+        }
+    }
+
+    #[test]
+    fn a_file_over_the_function_limit_is_skipped_entirely_with_a_warning() {
+        let mut src = String::from("package main\n\n");
+        for i in 0..20 {
+            src.push_str(&format!("func f{i}() {{}}\n"));
+        }
+        let parsed = parse_one(&src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+
+        let (cfgs, warnings) = build_cfgs_for_file_with_guards(
+            &pf.ast,
+            &parsed.objects,
+            CfgGuardOptions { max_functions_per_file: Some(5), ..CfgGuardOptions::default() },
+        );
+
+        assert!(cfgs.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("20"));
+    }
+
+    #[test]
+    fn a_function_over_the_statement_limit_is_skipped_but_its_siblings_are_not() {
+        let src = "package main\nfunc small() {\n\tx := 1\n\t_ = x\n}\nfunc big() {\n\tx := 1\n\tx = x + 1\n\tx = x + 1\n\tx = x + 1\n\t_ = x\n}\n";
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+
+        let (cfgs, warnings) = build_cfgs_for_file_with_guards(
+            &pf.ast,
+            &parsed.objects,
+            CfgGuardOptions { max_statements_per_function: Some(2), ..CfgGuardOptions::default() },
+        );
+
+        assert!(cfgs.contains_key("small"));
+        assert!(!cfgs.contains_key("big"));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("big"));
+    }
+
+    #[test]
+    fn block_at_line_resolves_lines_inside_an_if_body_and_a_loop_body_to_the_right_block() {
+        let src = "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\tx = 1\n\t} else {\n\t\tx = 2\n\t}\n\tfor i := 0; i < x; i++ {\n\t\tx = x - 1\n\t}\n\treturn x\n}\n";
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let body = match &pf.ast.decls[0] {
+            ast::Decl::Func(key) => parsed.objects.fdecls[*key].body.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        let cfg = build_cfg(body, &parsed.objects);
+
+        let then_block = cfg
+            .blocks
+            .iter()
+            .position(|b| b.statements.iter().any(|s| s.text == "x = 1"))
+            .expect("a block containing the then branch's assignment");
+        let loop_body_block = cfg
+            .blocks
+            .iter()
+            .position(|b| b.statements.iter().any(|s| s.text == "x = x - 1"))
+            .expect("a block containing the loop body's assignment");
+
+        // Line 5 is `x = 1`, inside the `if`'s then body.
+        assert_eq!(block_at_line(&cfg, &parsed.objects, &pf.source, pf.base, 5), Some(then_block));
+        // Line 10 is `x = x - 1`, inside the `for` loop's body.
+        assert_eq!(
+            block_at_line(&cfg, &parsed.objects, &pf.source, pf.base, 10),
+            Some(loop_body_block)
+        );
+    }
+
+    #[test]
+    fn block_at_line_returns_none_for_a_line_with_no_statement_of_its_own() {
+        let src = "package main\n\nfunc f() {\n\tx := 1\n\t_ = x\n}\n";
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let body = match &pf.ast.decls[0] {
+            ast::Decl::Func(key) => parsed.objects.fdecls[*key].body.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        let cfg = build_cfg(body, &parsed.objects);
+
+        // Line 3 is the function signature, no statement.
+        assert_eq!(block_at_line(&cfg, &parsed.objects, &pf.source, pf.base, 3), None);
+    }
+
+    #[test]
+    fn assignment_block_renders_go_like_text() {
+        let parsed = parse_one("package main\nfunc f() {\n\tx := 10\n}\n");
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+        let cfg = &cfgs["f"];
+        let text = &cfg.blocks[cfg.entry].statements[0].text;
+        assert_eq!(text, "x := 10");
+    }
+
+    /// A hand-rolled well-formedness check (balanced, properly nested
+    /// tags) so this test doesn't need to pull in an XML crate just to
+    /// confirm the exporter's own output parses.
+    fn xml_is_well_formed(xml: &str) -> bool {
+        let mut depth: i32 = 0;
+        let mut pos = 0;
+        while let Some(rel) = xml[pos..].find('<') {
+            let start = pos + rel;
+            let Some(rel_end) = xml[start..].find('>') else {
+                return false;
+            };
+            let end = start + rel_end;
+            let tag = &xml[start + 1..end];
+            pos = end + 1;
+            if tag.starts_with('?') || tag.starts_with('!') || tag.ends_with('/') {
+                continue;
+            }
+            if tag.starts_with('/') {
+                depth -= 1;
+                if depth < 0 {
+                    return false;
+                }
+            } else {
+                depth += 1;
+            }
+        }
+        depth == 0
+    }
+
+    #[test]
+    fn graphml_export_is_well_formed_with_matching_node_and_edge_counts() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 0\n\t}\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+        let cfg = &cfgs["f"];
+
+        let xml = to_graphml(cfg, &parsed.objects, &pf.source, pf.base);
+        assert!(xml_is_well_formed(&xml));
+        assert_eq!(xml.matches("<node ").count(), cfg.blocks.len());
+        assert_eq!(xml.matches("<edge ").count(), cfg.edge_count());
+    }
+
+    #[test]
+    fn long_statements_are_truncated_in_dot_and_kept_in_full_in_the_sidecar() {
+        let parsed = parse_one(
+            "package main\nfunc f() {\n\tresultOfAVeryLongComputation := someReallyLongFunctionNameThatGoesOnForAWhile(1, 2, 3)\n\t_ = resultOfAVeryLongComputation\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+        let cfg = &cfgs["f"];
+
+        let full_label = cfg.blocks[cfg.entry]
+            .statements
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\\n");
+
+        let (dot, full_text) = to_dot(cfg, 20);
+        assert!(!dot.contains(&full_label), "dot should not contain the untruncated label");
+        assert!(dot.contains("..."));
+        assert_eq!(full_text[&cfg.entry], full_label);
+    }
+
+    #[test]
+    fn short_statements_are_left_untouched_with_no_sidecar_entry() {
+        let parsed = parse_one("package main\nfunc f() {\n\tx := 10\n}\n");
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+        let cfg = &cfgs["f"];
+
+        let (dot, full_text) = to_dot(cfg, 60);
+        assert!(dot.contains("x := 10"));
+        assert!(full_text.is_empty());
+    }
+
+    #[test]
+    fn unreachable_blocks_are_drawn_dashed_in_dot_and_dot_combined() {
+        let parsed = parse_one("package main\nfunc f() {\n\treturn\n\tx := 1\n\t_ = x\n}\n");
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let objects = &parsed.objects;
+        let ast::Decl::Func(key) = pf.ast.decls[0] else { unreachable!() };
+        let body = objects.fdecls[key].body.as_ref().unwrap();
+        let cfg = build_cfg_with_options(
+            body,
+            objects,
+            CfgBuildOptions { keep_unreachable: true, ..CfgBuildOptions::default() },
+        );
+        let dead_block = cfg.blocks.iter().position(|b| !b.reachable).expect("dead code block");
+
+        let (dot, _) = to_dot(&cfg, 60);
+        assert!(dot.contains(&format!("{dead_block} [shape=box, label=")));
+        assert!(
+            dot.lines().find(|line| line.trim_start().starts_with(&format!("{dead_block} ["))).unwrap()
+                .contains("style=dashed")
+        );
+
+        let cfgs = BTreeMap::from([("f".to_string(), cfg)]);
+        let (combined, _) = to_dot_combined(&cfgs, 60);
+        assert!(
+            combined.lines().find(|line| line.contains(&format!("_{dead_block} ["))).unwrap()
+                .contains("style=dashed")
+        );
+    }
+
+    #[test]
+    fn methods_with_the_same_name_on_different_receivers_both_keep_their_cfg() {
+        let parsed = parse_one(
+            "package main\n\ntype A struct{}\ntype B struct{}\n\nfunc (a *A) Do() {\n\tx := 1\n\t_ = x\n}\n\nfunc (b B) Do() {\n\ty := 2\n\t_ = y\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+
+        assert_eq!(cfgs.len(), 2);
+        assert_eq!(cfgs["(*A).Do"].blocks[cfgs["(*A).Do"].entry].statements[0].text, "x := 1");
+        assert_eq!(cfgs["B.Do"].blocks[cfgs["B.Do"].entry].statements[0].text, "y := 2");
+    }
+
+    #[test]
+    fn if_else_with_both_branches_returning_links_each_branch_straight_to_exit() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 0\n\t}\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+        let cfg = &cfgs["f"];
+        // Both branches return, so nothing should flow into a merge block;
+        // the graph should have no path from entry to exit other than via
+        // the two return statements.
+        assert_eq!(cfg.blocks[cfg.exit].predecessors.len(), 2);
+    }
+
+    #[test]
+    fn ids_are_contiguous_and_identical_across_repeated_builds() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\tfor x > 0 {\n\t\tx = x - 1\n\t}\n\treturn x\n}\n";
+
+        let build = || {
+            let parsed = parse_one(src);
+            let pkg = parsed.packages.values().next().unwrap();
+            let pf = pkg.files.values().next().unwrap();
+            build_cfgs_for_file(&pf.ast, &parsed.objects)["f"].clone()
+        };
+
+        let first = build();
+        let second = build();
+
+        // Every successor/predecessor should be a valid index into `blocks`
+        // — i.e. ids are contiguous from 0, with no gaps left by renumbering.
+        for block in &first.blocks {
+            for &id in block.successors.iter().chain(&block.predecessors) {
+                assert!(id < first.blocks.len());
+            }
+        }
+
+        assert_eq!(first.blocks.len(), second.blocks.len());
+        assert_eq!(first.entry, second.entry);
+        assert_eq!(first.exit, second.exit);
+        for (a, b) in first.blocks.iter().zip(&second.blocks) {
+            assert_eq!(a.statements.iter().map(|s| &s.text).collect::<Vec<_>>(), b.statements.iter().map(|s| &s.text).collect::<Vec<_>>());
+            assert_eq!(a.successors, b.successors);
+            assert_eq!(a.predecessors, b.predecessors);
+        }
+    }
+
+    #[test]
+    fn renumber_appends_a_block_unreachable_from_entry_last() {
+        // entry(0) -> exit(1), plus an orphan(2) that nothing points to —
+        // built out of DFS pre-order so renumbering has actual work to do.
+        let mut cfg = ControlFlowGraph {
+            entry: 0,
+            exit: 1,
+            blocks: vec![
+                BasicBlock {
+                    successors: vec![1],
+                    ..Default::default()
+                },
+                BasicBlock {
+                    predecessors: vec![0],
+                    ..Default::default()
+                },
+                BasicBlock::default(),
+            ],
+        };
+
+        cfg.renumber();
+
+        assert_eq!(cfg.blocks.len(), 3);
+        assert_eq!(cfg.entry, 0);
+        assert_eq!(cfg.exit, 1);
+        // The orphan block, unreachable from entry, keeps its relative
+        // place at the end rather than being dropped.
+        assert!(cfg.blocks[2].statements.is_empty());
+        assert!(cfg.blocks[2].successors.is_empty());
+        assert!(cfg.blocks[2].predecessors.is_empty());
+        assert_eq!(cfg.blocks[0].successors, vec![1]);
+        assert_eq!(cfg.blocks[1].predecessors, vec![0]);
+    }
+
+    #[test]
+    fn reachability_excludes_a_block_with_no_path_from_entry() {
+        // entry(0) -> exit(1), plus an orphan(2) that nothing points to.
+        let cfg = ControlFlowGraph {
+            entry: 0,
+            exit: 1,
+            blocks: vec![
+                BasicBlock {
+                    successors: vec![1],
+                    ..Default::default()
+                },
+                BasicBlock {
+                    predecessors: vec![0],
+                    ..Default::default()
+                },
+                BasicBlock::default(),
+            ],
+        };
+
+        assert!(cfg.is_reachable(cfg.entry));
+        assert!(cfg.is_reachable(cfg.exit));
+        assert!(!cfg.is_reachable(2));
+
+        let reachable = cfg.reachable_from(cfg.entry);
+        assert_eq!(reachable, [0, 1].into_iter().collect());
+
+        // validate() re-derives reachability with its own bounds-checked
+        // traversal (it has to tolerate a graph with dangling successors,
+        // which reachable_from doesn't) — the two should still agree on a
+        // well-formed graph like this one.
+        assert_eq!(cfg.validate().unreachable, vec![2]);
+    }
+
+    #[test]
+    fn validate_flags_a_deliberately_broken_graph() {
+        // entry(0) points at a dangling successor (5); exit(1) is never
+        // linked to, so it's unreachable; orphan(2) is both unreachable
+        // and a sink (no successors, and it isn't `exit`).
+        let cfg = ControlFlowGraph {
+            entry: 0,
+            exit: 1,
+            blocks: vec![
+                BasicBlock {
+                    successors: vec![5],
+                    ..Default::default()
+                },
+                BasicBlock::default(),
+                BasicBlock::default(),
+            ],
+        };
+
+        let report = cfg.validate();
+        assert!(!report.is_valid());
+        assert_eq!(report.dangling_successors, vec![(0, 5)]);
+        assert_eq!(report.sink_blocks, vec![2]);
+        assert_eq!(report.unreachable, vec![1, 2]);
+    }
+
+    #[test]
+    fn validate_reports_every_dangling_successor_not_just_the_first() {
+        // entry(0) has two dangling successors (5 and 6).
+        let cfg = ControlFlowGraph {
+            entry: 0,
+            exit: 1,
+            blocks: vec![
+                BasicBlock {
+                    successors: vec![5, 6],
+                    ..Default::default()
+                },
+                BasicBlock::default(),
+            ],
+        };
+
+        let report = cfg.validate();
+        assert_eq!(report.dangling_successors, vec![(0, 5), (0, 6)]);
+    }
+
+    #[test]
+    fn validate_accepts_a_well_formed_graph() {
+        let cfg = ControlFlowGraph {
+            entry: 0,
+            exit: 1,
+            blocks: vec![
+                BasicBlock {
+                    successors: vec![1],
+                    ..Default::default()
+                },
+                BasicBlock {
+                    predecessors: vec![0],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        assert_eq!(cfg.validate(), CfgValidation::default());
+        assert!(cfg.validate().is_valid());
+    }
+
+    #[test]
+    fn a_loop_free_functions_longest_path_equals_its_block_count() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) int {\n\ty := x + 1\n\tz := y + 1\n\treturn z\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfg = &build_cfgs_for_file(&pf.ast, &parsed.objects)["f"];
+
+        assert_eq!(longest_acyclic_path_len(cfg), cfg.blocks.len());
+    }
+
+    #[test]
+    fn a_branchy_function_reports_the_longer_arm() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\ta := 1\n\t\tb := 2\n\t\tc := 3\n\t\t_ = a\n\t\t_ = b\n\t\t_ = c\n\t} else {\n\t\td := 1\n\t}\n\treturn x\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfg = &build_cfgs_for_file(&pf.ast, &parsed.objects)["f"];
+
+        // entry -> then-branch (one block, since it's straight-line) ->
+        // merge -> exit is the longest of the two arms.
+        assert_eq!(longest_acyclic_path_len(cfg), 4);
+    }
+
+    #[test]
+    fn a_loop_body_is_only_counted_once_not_traversed_forever() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) int {\n\tfor x > 0 {\n\t\tx = x - 1\n\t}\n\treturn x\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfg = &build_cfgs_for_file(&pf.ast, &parsed.objects)["f"];
+
+        let longest = longest_acyclic_path_len(cfg);
+        assert!(longest > 0);
+        assert!(longest <= cfg.blocks.len());
+    }
+
+    #[test]
+    fn sanitize_dot_id_keeps_names_that_differ_only_in_a_replaced_character_distinct() {
+        let a = sanitize_dot_id("a.b");
+        let b = sanitize_dot_id("a_b");
+        assert_ne!(a, b, "`a.b` and `a_b` must not collide after sanitization");
+        assert!(a.starts_with("a_b_"));
+        assert_eq!(b, "a_b");
+    }
+
+    #[test]
+    fn sanitize_dot_id_prefixes_a_leading_digit() {
+        let sanitized = sanitize_dot_id("1foo");
+        assert!(sanitized.starts_with("_1foo_"));
+    }
+
+    #[test]
+    fn combined_dot_namespaces_block_ids_so_no_edge_crosses_clusters() {
+        let parsed = parse_one(
+            "package main\nfunc f() {\n\tx := 1\n\t_ = x\n}\nfunc g() {\n\ty := 1\n\tif y > 0 {\n\t\ty = 2\n\t}\n\t_ = y\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+
+        let (dot, _full_text) = to_dot_combined(&cfgs, 60);
+
+        assert!(dot.contains("cluster_f"));
+        assert!(dot.contains("cluster_g"));
+
+        for line in dot.lines() {
+            let Some((lhs, rhs)) = line.trim().split_once("->") else {
+                continue;
+            };
+            let from_prefix = lhs.trim().rsplit_once('_').unwrap().0;
+            let to_prefix = rhs.trim().trim_end_matches(';').rsplit_once('_').unwrap().0;
+            assert_eq!(
+                from_prefix, to_prefix,
+                "edge {line} crosses between clusters"
+            );
+        }
+    }
+
+    #[test]
+    fn combined_dot_truncates_long_labels_with_a_namespaced_sidecar_entry() {
+        let parsed = parse_one(
+            "package main\nfunc f() {\n\tsome_rather_long_variable_name := 123456789\n\t_ = some_rather_long_variable_name\n}\n",
+        );
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let cfgs = build_cfgs_for_file(&pf.ast, &parsed.objects);
+        let cfg = &cfgs["f"];
+
+        let full_label = cfg.blocks[cfg.entry]
+            .statements
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\\n");
+
+        let (dot, full_text) = to_dot_combined(&cfgs, 10);
+        assert!(!dot.contains(&full_label));
+        assert_eq!(full_text[&format!("f_{}", cfg.entry)], full_label);
+    }
+
+    #[test]
+    fn combined_dot_of_an_empty_project_is_a_valid_empty_digraph() {
+        let cfgs: BTreeMap<String, ControlFlowGraph> = BTreeMap::new();
+        let (dot, full_text) = to_dot_combined(&cfgs, 60);
+        assert_eq!(dot, "digraph cfg {\n}\n");
+        assert!(full_text.is_empty());
+    }
+
+    fn cfg_of(src: &str) -> ControlFlowGraph {
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let body = match &pf.ast.decls[0] {
+            ast::Decl::Func(key) => parsed.objects.fdecls[*key].body.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        build_cfg(body, &parsed.objects)
+    }
+
+    #[test]
+    fn cyclomatic_complexity_of_an_empty_function_is_one() {
+        let cfg = cfg_of("package main\nfunc f() {\n}\n");
+        assert_eq!(cfg.cyclomatic_complexity(), 1);
+    }
+
+    #[test]
+    fn cyclomatic_complexity_of_straight_line_code_is_one() {
+        let cfg = cfg_of("package main\nfunc f(x int) int {\n\ty := x + 1\n\treturn y\n}\n");
+        assert_eq!(cfg.cyclomatic_complexity(), 1);
+    }
+
+    #[test]
+    fn cyclomatic_complexity_of_a_single_if_is_two() {
+        let cfg = cfg_of("package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n");
+        assert_eq!(cfg.cyclomatic_complexity(), 2);
+    }
+
+    #[test]
+    fn cyclomatic_complexity_of_nested_ifs_is_three() {
+        let cfg = cfg_of(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\tif x > 10 {\n\t\t\treturn 2\n\t\t}\n\t\treturn 1\n\t}\n\treturn 0\n}\n",
+        );
+        assert_eq!(cfg.cyclomatic_complexity(), 3);
+    }
+
+    #[test]
+    fn keep_unreachable_dead_code_does_not_inflate_cyclomatic_complexity() {
+        let src = "package main\nfunc f(x int) int {\n\treturn 0\n\tif x > 0 {\n\t\treturn 1\n\t}\n}\n";
+        let plain = cfg_of(src);
+        let parsed = parse_one(src);
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let body = match &pf.ast.decls[0] {
+            ast::Decl::Func(key) => parsed.objects.fdecls[*key].body.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        let with_dead_code = build_cfg_with_options(
+            body,
+            &parsed.objects,
+            CfgBuildOptions { keep_unreachable: true, ..CfgBuildOptions::default() },
+        );
+
+        assert_eq!(plain.cyclomatic_complexity(), 1);
+        assert_eq!(with_dead_code.cyclomatic_complexity(), plain.cyclomatic_complexity());
+    }
+}
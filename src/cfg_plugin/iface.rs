@@ -0,0 +1,124 @@
+//! Plugin-function wrapper around [`build_cfgs_for_project`](super::build_cfgs_for_project).
+//!
+//! Depends on [`go_parser::iface::parse_project`](crate::go_parser::iface),
+//! the shared parsed-project dependency root, rather than parsing `path`
+//! again itself.
+
+use crate::go_parser::ParseDirResult;
+use crate::go_parser::iface::parse_project_id;
+use crate::kernel::{PluginFunction, QualPfId, UserParamSpec, UserParams};
+
+use super::{CfgBuildResult, CfgGuardOptions, build_cfgs_for_project_with_guards};
+
+fn usize_param(params: &UserParams, name: &str) -> Result<Option<usize>, String> {
+    match params.get::<String>(name) {
+        Some(value) => value.parse().map(Some).map_err(|_| format!("invalid {name} `{value}`")),
+        None => Ok(None),
+    }
+}
+
+/// `cfg.build_cfgs`: every function's [`super::ControlFlowGraph`] across
+/// the project, keyed by [`crate::model::FunctionId`], plus any warnings
+/// about functions or files skipped via [`CfgGuardOptions`]. Depends on
+/// `project.parse`. Honors the optional `max_functions_per_file` and
+/// `max_statements_per_function` user parameters the same way
+/// [`CfgGuardOptions`] does.
+pub fn build_cfgs_id() -> QualPfId {
+    QualPfId::new("cfg", "build_cfgs")
+}
+
+pub fn build_cfgs_pf() -> PluginFunction {
+    let dep = parse_project_id();
+    PluginFunction::new(build_cfgs_id(), vec![dep.clone()], move |results, params| {
+        let parsed = results.try_get::<ParseDirResult>(&dep).map_err(|e| e.to_string())?;
+        let guards = CfgGuardOptions {
+            max_functions_per_file: usize_param(params, "max_functions_per_file")?,
+            max_statements_per_function: usize_param(params, "max_statements_per_function")?,
+        };
+        Ok::<CfgBuildResult, String>(build_cfgs_for_project_with_guards(parsed, guards))
+    })
+    .with_user_params(vec![
+        UserParamSpec::optional("max_functions_per_file"),
+        UserParamSpec::optional("max_statements_per_function"),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go_parser::iface::parse_project_pf;
+    use crate::kernel::{Pipeline, UserParams};
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn tempdir_with(src: &str) -> std::path::PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-cfg-iface-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::File::create(dir.join("a.go")).unwrap().write_all(src.as_bytes()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn build_cfgs_pf_reuses_the_shared_parse_and_finds_every_function() {
+        let dir = tempdir_with("package main\nfunc f() {}\nfunc g(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n");
+
+        let pipeline = Pipeline::new(vec![parse_project_pf(), build_cfgs_pf()]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+
+        let output = pipeline.run(&params).unwrap();
+        let result = output.results.get::<CfgBuildResult>(&build_cfgs_id()).unwrap();
+
+        assert_eq!(result.cfgs.len(), 2);
+        assert!(result.cfgs.keys().any(|f| f.name == "f"));
+        assert!(result.cfgs.keys().any(|f| f.name == "g"));
+        assert!(result.warnings.is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn build_cfgs_pf_honors_the_max_statements_per_function_guard() {
+        let dir = tempdir_with(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n",
+        );
+
+        let pipeline = Pipeline::new(vec![parse_project_pf(), build_cfgs_pf()]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+        params.set("max_statements_per_function", "1".to_string());
+
+        let output = pipeline.run(&params).unwrap();
+        let result = output.results.get::<CfgBuildResult>(&build_cfgs_id()).unwrap();
+
+        assert!(result.cfgs.is_empty());
+        assert_eq!(result.warnings.len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn max_functions_per_file_is_shared_with_project_parse_so_the_file_is_already_gone_by_the_cfg_stage() {
+        let dir = tempdir_with("package main\nfunc f() {}\nfunc g() {}\n");
+
+        let pipeline = Pipeline::new(vec![parse_project_pf(), build_cfgs_pf()]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+        params.set("max_functions_per_file", "1".to_string());
+
+        let output = pipeline.run(&params).unwrap();
+        let parsed = output.results.get::<ParseDirResult>(&parse_project_id()).unwrap();
+        let result = output.results.get::<CfgBuildResult>(&build_cfgs_id()).unwrap();
+
+        assert_eq!(parsed.warnings.len(), 1);
+        assert!(result.cfgs.is_empty());
+        assert!(result.warnings.is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
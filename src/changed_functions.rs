@@ -0,0 +1,151 @@
+//! "Only review what changed": matches a git diff's hunks against parsed
+//! function spans, so a report like [`crate::complexity`] can be
+//! restricted to functions a change actually touched instead of
+//! re-analyzing a whole project on every review.
+
+use goscript_parser::ast::{self, Decl, Node};
+
+use crate::git_metrics::{GitMetricsError, Repo, diff_refs};
+use crate::go_parser::{ParseDirResult, line_of};
+use crate::model::FunctionId;
+
+/// A function from a parsed project, flagged with whether a diff's hunks
+/// overlapped its declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionChangeStatus {
+    pub function: FunctionId,
+    pub touched: bool,
+}
+
+/// A function declaration's line span (1-based, inclusive), for
+/// comparing against a diff's [`crate::git_metrics::LineRange`]s.
+fn function_line_span(
+    fdecl: &ast::FuncDecl,
+    objects: &goscript_parser::objects::Objects,
+    source: &str,
+    base: usize,
+) -> (usize, usize) {
+    let start = line_of(source, base, fdecl.pos(objects));
+    let end_pos = match &fdecl.body {
+        Some(body) => body.end(),
+        None => fdecl.typ.end(objects),
+    };
+    (start, line_of(source, base, end_pos))
+}
+
+/// For every function in `parsed`, whether it overlaps a hunk from
+/// diffing `old_ref` against `new_ref` in `repo`.
+///
+/// Matches a function to the diff by its file name — which only lines up
+/// with the diff's paths (both relative to the repository root) when
+/// `parsed` was produced by parsing that same repository's root
+/// directory. Parsing a subdirectory instead means every function comes
+/// back untouched, since none of its file names will match a diff path.
+pub fn changed_functions(
+    parsed: &ParseDirResult,
+    repo: &Repo,
+    old_ref: &str,
+    new_ref: &str,
+) -> Result<Vec<FunctionChangeStatus>, GitMetricsError> {
+    let diffs = diff_refs(repo, old_ref, new_ref)?;
+
+    let mut out = Vec::new();
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            let hunks = diffs
+                .iter()
+                .find(|diff| &diff.path == file_name)
+                .map(|diff| diff.hunks.as_slice())
+                .unwrap_or(&[]);
+
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function = FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+
+                let (start, end) = function_line_span(fdecl, &parsed.objects, &pf.source, pf.base);
+                let touched = hunks
+                    .iter()
+                    .any(|hunk| hunk.start <= end && start <= hunk.end);
+
+                out.push(FunctionChangeStatus { function, touched });
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_metrics::{CommitWalkOptions, read_repo};
+    use std::path::Path;
+
+    fn tempfile_dir() -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-changed-functions-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_and_commit(repo: &git2::Repository, root: &Path, path: &str, contents: &str, message: &str) {
+        std::fs::write(root.join(path), contents).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree_id = index.write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let sig = git2::Signature::now("author", "author@example.com").unwrap();
+        let parent = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .and_then(|oid| repo.find_commit(oid).ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn only_the_function_touched_by_the_diff_is_reported_as_changed() {
+        let dir = tempfile_dir();
+        let git_repo = git2::Repository::init(&dir).unwrap();
+
+        write_and_commit(
+            &git_repo,
+            &dir,
+            "a.go",
+            "package main\n\nfunc Foo() int {\n\treturn 1\n}\n\nfunc Bar() int {\n\treturn 2\n}\n",
+            "initial",
+        );
+        let old_ref = git_repo.head().unwrap().target().unwrap().to_string();
+
+        write_and_commit(
+            &git_repo,
+            &dir,
+            "a.go",
+            "package main\n\nfunc Foo() int {\n\treturn 100\n}\n\nfunc Bar() int {\n\treturn 2\n}\n",
+            "change Foo",
+        );
+        let new_ref = git_repo.head().unwrap().target().unwrap().to_string();
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        let repo = read_repo(&dir).unwrap();
+        let _ = CommitWalkOptions::default();
+
+        let statuses = changed_functions(&parsed, &repo, &old_ref, &new_ref).unwrap();
+        let touched: Vec<&FunctionChangeStatus> = statuses.iter().filter(|s| s.touched).collect();
+
+        assert_eq!(touched.len(), 1);
+        assert_eq!(touched[0].function.name, "Foo");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
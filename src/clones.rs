@@ -0,0 +1,189 @@
+//! Structural clone detection over [`crate::cfg_plugin`]'s CFGs: two
+//! functions whose control-flow shape — block count, the out-degree of
+//! each block, and the sequence of statement kinds inside each block —
+//! is identical are flagged as suspected duplicates, even if every
+//! variable was renamed between them. Renaming a variable doesn't change
+//! a statement's *kind* (an `Assign` is still an `Assign`), so hashing
+//! kinds instead of the statements' own rendered text is what makes this
+//! blind to the difference [`crate::changed_functions`]'s line-level diff
+//! would catch.
+//!
+//! This is shape equality, not behavioral equivalence — two functions
+//! that happen to share a CFG shape by coincidence (e.g. two trivial
+//! one-line getters) cluster together too. That's the same tradeoff
+//! [`crate::interfaces`]'s heuristic method-set matching makes: useful
+//! signal without a real equivalence check behind it.
+
+use std::collections::BTreeMap;
+
+use goscript_parser::ast::Stmt;
+
+use crate::cfg_plugin::{self, ControlFlowGraph};
+use crate::go_parser::ParseDirResult;
+use crate::model::FunctionId;
+
+/// A function's CFG shape, reduced to exactly what [`analyze_clones`]
+/// compares: two functions with an identical signature are reported as
+/// clones of each other.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CfgShapeSignature {
+    pub block_count: usize,
+    /// Each block's out-degree (`successors.len()`), in block order.
+    pub out_degrees: Vec<usize>,
+    /// Each block's statements, reduced to their kind (`"Assign"`,
+    /// `"If"`, ...) and concatenated in block order — a variable
+    /// renamed between two otherwise-identical functions doesn't change
+    /// this at all.
+    pub statement_kinds: Vec<&'static str>,
+}
+
+/// The name of `stmt`'s variant, e.g. `"Assign"` for `Stmt::Assign(_)`.
+fn statement_kind(stmt: &Stmt) -> &'static str {
+    match stmt {
+        Stmt::Bad(_) => "Bad",
+        Stmt::Decl(_) => "Decl",
+        Stmt::Empty(_) => "Empty",
+        Stmt::Labeled(_) => "Labeled",
+        Stmt::Expr(_) => "Expr",
+        Stmt::Send(_) => "Send",
+        Stmt::IncDec(_) => "IncDec",
+        Stmt::Assign(_) => "Assign",
+        Stmt::Go(_) => "Go",
+        Stmt::Defer(_) => "Defer",
+        Stmt::Return(_) => "Return",
+        Stmt::Branch(_) => "Branch",
+        Stmt::Block(_) => "Block",
+        Stmt::If(_) => "If",
+        Stmt::Case(_) => "Case",
+        Stmt::Switch(_) => "Switch",
+        Stmt::TypeSwitch(_) => "TypeSwitch",
+        Stmt::Comm(_) => "Comm",
+        Stmt::Select(_) => "Select",
+        Stmt::For(_) => "For",
+        Stmt::Range(_) => "Range",
+    }
+}
+
+/// Computes `cfg`'s [`CfgShapeSignature`].
+pub fn structural_signature(cfg: &ControlFlowGraph) -> CfgShapeSignature {
+    CfgShapeSignature {
+        block_count: cfg.blocks.len(),
+        out_degrees: cfg.blocks.iter().map(|b| b.successors.len()).collect(),
+        statement_kinds: cfg
+            .blocks
+            .iter()
+            .flat_map(|b| b.statements.iter().map(|s| statement_kind(&s.stmt)))
+            .collect(),
+    }
+}
+
+/// A group of functions that share a [`CfgShapeSignature`] — suspected
+/// clones of each other.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CloneCluster {
+    pub signature: String,
+    pub functions: Vec<FunctionId>,
+}
+
+/// The result of [`analyze_clones`]: every cluster of two or more
+/// functions sharing a CFG shape. A function whose shape is unique
+/// across the project isn't a clone of anything, so it's left out
+/// entirely rather than reported as a cluster of one.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct CloneReport {
+    pub clusters: Vec<CloneCluster>,
+}
+
+fn cfgs_with_ids(parsed: &ParseDirResult) -> Vec<(FunctionId, ControlFlowGraph)> {
+    let mut out = Vec::new();
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for (name, cfg) in cfg_plugin::build_cfgs_for_file(&pf.ast, &parsed.objects) {
+                out.push((FunctionId::new(pkg.name.clone(), file_name.clone(), name), cfg));
+            }
+        }
+    }
+    out
+}
+
+/// Groups every function declared in `parsed` by [`CfgShapeSignature`],
+/// reporting every group with two or more members as a [`CloneCluster`].
+pub fn analyze_clones(parsed: &ParseDirResult) -> CloneReport {
+    let mut by_signature: BTreeMap<CfgShapeSignature, Vec<FunctionId>> = BTreeMap::new();
+    for (function, cfg) in cfgs_with_ids(parsed) {
+        by_signature.entry(structural_signature(&cfg)).or_default().push(function);
+    }
+
+    let mut clusters = Vec::new();
+    for (signature, mut functions) in by_signature {
+        if functions.len() < 2 {
+            continue;
+        }
+        functions.sort();
+        clusters.push(CloneCluster {
+            signature: format!(
+                "{} blocks, {} statements",
+                signature.block_count,
+                signature.statement_kinds.len()
+            ),
+            functions,
+        });
+    }
+    clusters.sort_by(|a, b| a.functions.cmp(&b.functions));
+
+    CloneReport { clusters }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(dir: &Path, name: &str, src: &str) {
+        fs::write(dir.join(name), src).unwrap();
+    }
+
+    #[test]
+    fn two_structurally_identical_functions_cluster_together_and_a_distinct_one_does_not() {
+        let dir = std::env::temp_dir().join(format!("skanujkod-clones-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package main\n\
+             func addOne(x int) int {\n\
+             \tif x > 0 {\n\
+             \t\treturn x + 1\n\
+             \t}\n\
+             \treturn 0\n\
+             }\n\
+             \n\
+             func incrementCount(n int) int {\n\
+             \tif n > 0 {\n\
+             \t\treturn n + 1\n\
+             \t}\n\
+             \treturn 0\n\
+             }\n\
+             \n\
+             func sumRange(lo, hi int) int {\n\
+             \ttotal := 0\n\
+             \tfor i := lo; i < hi; i++ {\n\
+             \t\ttotal += i\n\
+             \t}\n\
+             \treturn total\n\
+             }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let report = analyze_clones(&parsed);
+        assert_eq!(report.clusters.len(), 1);
+
+        let cluster = &report.clusters[0];
+        let names: Vec<&str> = cluster.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["addOne", "incrementCount"]);
+        assert!(!names.contains(&"sumRange"));
+    }
+}
@@ -0,0 +1,345 @@
+//! The `All` command: run every analysis that doesn't need its own
+//! extra input (a coverage profile, an interface name, ...) against one
+//! project and write each one's report to its own file under a shared
+//! output directory, plus a top-level index of what got written.
+//!
+//! Report naming used to be ad hoc (`complexity_report.json`,
+//! `branch_coverage_report.json`, ...) picked per plugin as it was
+//! added, which risked two plugins colliding on a name and left a
+//! caller with no single place to discover what a run actually
+//! produced. This standardizes both: every report lands at
+//! `<output_dir>/<analysis>/report.<ext>`, and `<output_dir>/index.json`
+//! lists each one's analysis name, path, and format.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::call_graph::{self, CouplingOptions, CouplingReport};
+use crate::clones::{self, CloneCluster};
+use crate::complexity::{self, ComplexityOptions};
+use crate::features;
+use crate::func_counts;
+use crate::go_parser::{self, ParseDirError};
+use crate::imports::{self, ImportAnalysisOptions};
+use crate::lints::{self, EmptyBranchOptions};
+
+/// One report `run_all_analyses` wrote, as listed in `index.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
+pub struct ReportEntry {
+    /// The plugin-style name of the analysis that produced this report
+    /// (`"complexity"`, `"func_counts"`, `"lints"`, `"comment_markers"`,
+    /// `"diagnostics"`, `"features"`, `"function_length"`, `"imports"`,
+    /// `"import_cycles"`, `"clones"`, `"coupling"`, `"empty_branches"`,
+    /// `"excessive_returns"`, `"constant_conditions"`).
+    pub analysis: String,
+    /// Path to the report file, relative to the output directory passed
+    /// to `run_all_analyses`.
+    pub path: PathBuf,
+    /// The report's file format (currently always `"json"` — kept as a
+    /// field rather than inferred from `path`'s extension so a future
+    /// analysis can write e.g. HTML without breaking the index's shape).
+    pub format: String,
+}
+
+/// The top-level manifest `run_all_analyses` writes to
+/// `<output_dir>/index.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, serde::Deserialize)]
+pub struct AllAnalysesIndex {
+    pub reports: Vec<ReportEntry>,
+}
+
+#[derive(Debug)]
+pub enum RunAllError {
+    Parse(ParseDirError),
+    Io(PathBuf, std::io::Error),
+    Json(String, serde_json::Error),
+}
+
+impl std::fmt::Display for RunAllError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunAllError::Parse(err) => write!(f, "{err}"),
+            RunAllError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            RunAllError::Json(analysis, err) => write!(f, "{analysis}: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RunAllError {}
+
+/// Renders `value` as pretty JSON — the pure part of what a report
+/// "generator" does, split out from [`write_report`]'s file handling so
+/// a caller (or a test) can get a report's exact content without a
+/// temp file, and so a future `--json`-to-stdout mode has something to
+/// print directly.
+fn render_report(analysis: &str, value: &impl Serialize) -> Result<String, RunAllError> {
+    serde_json::to_string_pretty(value).map_err(|err| RunAllError::Json(analysis.to_string(), err))
+}
+
+/// Writes `value` as pretty JSON to `<output_dir>/<analysis>/report.json`,
+/// creating the `<analysis>` subdirectory as needed, and returns the
+/// [`ReportEntry`] to list it under in the index.
+fn write_report(
+    output_dir: &Path,
+    analysis: &str,
+    value: &impl Serialize,
+) -> Result<ReportEntry, RunAllError> {
+    let dir = output_dir.join(analysis);
+    fs::create_dir_all(&dir).map_err(|err| RunAllError::Io(dir.clone(), err))?;
+
+    let relative_path = Path::new(analysis).join("report.json");
+    let full_path = output_dir.join(&relative_path);
+    let json = render_report(analysis, value)?;
+    fs::write(&full_path, json).map_err(|err| RunAllError::Io(full_path, err))?;
+
+    Ok(ReportEntry {
+        analysis: analysis.to_string(),
+        path: relative_path,
+        format: "json".to_string(),
+    })
+}
+
+/// Adds `other`'s counts into `base` in place — `by_file`/`by_package`
+/// entries add rather than overwrite, so two projects with a
+/// same-named file or package directory (e.g. both have a root-level
+/// `main.go`) still contribute both their counts instead of one
+/// clobbering the other.
+fn merge_func_counts(base: &mut func_counts::FuncCounts, other: func_counts::FuncCounts) {
+    base.total += other.total;
+    base.exported += other.exported;
+    base.unexported += other.unexported;
+    base.methods += other.methods;
+    base.free_functions += other.free_functions;
+    for (file_name, count) in other.by_file {
+        *base.by_file.entry(file_name).or_insert(0) += count;
+    }
+    for (pkg_dir, count) in other.by_package {
+        *base.by_package.entry(pkg_dir).or_insert(0) += count;
+    }
+}
+
+/// Adds `other`'s per-function profiles and per-feature counts into
+/// `base` in place, the same union-not-overwrite approach
+/// [`merge_func_counts`] takes.
+fn merge_feature_summary(base: &mut features::FeatureSummary, other: features::FeatureSummary) {
+    base.functions.extend(other.functions);
+    for (feature, count) in other.counts {
+        *base.counts.entry(feature).or_insert(0) += count;
+    }
+}
+
+/// Adds `other`'s edges and external-import buckets into `base` in
+/// place, the same union-not-overwrite approach [`merge_func_counts`]
+/// takes — two projects that both have a package at the same directory
+/// key should keep both projects' external imports for it, not one
+/// overwrite the other's.
+fn merge_import_graph(base: &mut imports::ImportGraph, other: imports::ImportGraph) {
+    base.edges.extend(other.edges);
+    for (pkg_dir, external) in other.external {
+        base.external.entry(pkg_dir).or_default().extend(external);
+    }
+}
+
+/// Adds `other`'s fan-in/fan-out findings into `base` in place, the same
+/// union-not-overwrite approach [`merge_func_counts`] takes.
+fn merge_coupling_report(base: &mut CouplingReport, other: CouplingReport) {
+    base.functions.extend(other.functions);
+    base.high_fan_out.extend(other.high_fan_out);
+    base.high_fan_in.extend(other.high_fan_in);
+}
+
+/// Runs every analysis that needs nothing beyond a project path
+/// (complexity, function length, function counts, the trivial-loop,
+/// empty-branch, excessive-returns, and constant-condition lints,
+/// comment marker findings, editor diagnostics, language feature usage,
+/// structural clone detection, call-graph coupling, and the import
+/// graph plus its cycle detection — branch coverage and interface
+/// lookups are skipped, since they need a coverage profile / interface
+/// name this command has no way to ask for) against every path in
+/// `paths`, combining each analysis's results across all of them (see
+/// [`complexity::analyze_function_complexity_across`]), writes each
+/// one's combined report under `output_dir` in the
+/// `<analysis>/report.json` layout, and writes `output_dir/index.json`
+/// listing what was produced.
+pub fn run_all_analyses(paths: &[PathBuf], output_dir: &Path) -> Result<AllAnalysesIndex, RunAllError> {
+    let parsed_projects = paths
+        .iter()
+        .map(|path| go_parser::parse_dir(path).map_err(RunAllError::Parse))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let complexity_report = complexity::analyze_function_complexity_across(
+        &parsed_projects,
+        &ComplexityOptions::default(),
+    );
+
+    let mut func_counts = func_counts::FuncCounts::default();
+    let mut loop_findings = Vec::new();
+    let mut comment_markers = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut feature_summary = features::FeatureSummary::default();
+    let mut import_graph = imports::ImportGraph::default();
+    let mut clone_clusters: Vec<CloneCluster> = Vec::new();
+    let mut coupling_report = CouplingReport::default();
+    let mut empty_branch_findings = Vec::new();
+    let mut excessive_returns_findings = Vec::new();
+    let mut constant_condition_findings = Vec::new();
+    for parsed in &parsed_projects {
+        merge_func_counts(&mut func_counts, func_counts::count_funcs(parsed));
+        merge_feature_summary(&mut feature_summary, features::analyze_feature_usage(parsed));
+        let project_loop_findings = lints::empty_or_trivial_loop_conditions(parsed);
+        diagnostics.extend(crate::diagnostics::collect_diagnostics(
+            parsed,
+            &complexity_report,
+            &project_loop_findings,
+        ));
+        loop_findings.extend(project_loop_findings);
+        comment_markers.extend(lints::find_comment_markers(parsed, lints::DEFAULT_COMMENT_MARKERS));
+        merge_import_graph(&mut import_graph, imports::analyze(parsed, &ImportAnalysisOptions::default()));
+        clone_clusters.extend(clones::analyze_clones(parsed).clusters);
+        merge_coupling_report(&mut coupling_report, call_graph::analyze_coupling(parsed, &CouplingOptions::default()));
+        empty_branch_findings.extend(lints::find_empty_branches(parsed, &EmptyBranchOptions::default()));
+        excessive_returns_findings.extend(lints::find_excessive_returns(parsed, lints::DEFAULT_MAX_RETURNS));
+        constant_condition_findings.extend(lints::find_constant_conditions(parsed));
+    }
+    let import_cycles = imports::find_cycles(&import_graph);
+
+    fs::create_dir_all(output_dir).map_err(|err| RunAllError::Io(output_dir.to_path_buf(), err))?;
+
+    let function_length_report = complexity_report.function_length_report(None);
+
+    let reports = vec![
+        write_report(output_dir, "complexity", &complexity_report)?,
+        write_report(output_dir, "func_counts", &func_counts)?,
+        write_report(output_dir, "lints", &loop_findings)?,
+        write_report(output_dir, "comment_markers", &comment_markers)?,
+        write_report(output_dir, "diagnostics", &diagnostics)?,
+        write_report(output_dir, "features", &feature_summary)?,
+        write_report(output_dir, "function_length", &function_length_report)?,
+        write_report(output_dir, "imports", &import_graph)?,
+        write_report(output_dir, "import_cycles", &import_cycles)?,
+        write_report(output_dir, "clones", &clone_clusters)?,
+        write_report(output_dir, "coupling", &coupling_report)?,
+        write_report(output_dir, "empty_branches", &empty_branch_findings)?,
+        write_report(output_dir, "excessive_returns", &excessive_returns_findings)?,
+        write_report(output_dir, "constant_conditions", &constant_condition_findings)?,
+    ];
+
+    let index = AllAnalysesIndex { reports };
+    let index_path = output_dir.join("index.json");
+    let json = render_report("index", &index)?;
+    fs::write(&index_path, json).map_err(|err| RunAllError::Io(index_path, err))?;
+
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_dir(label: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!("skanujkod-all-{label}-{}-{n}", std::process::id()))
+    }
+
+    #[test]
+    fn render_report_produces_the_exact_json_write_report_writes_to_disk() {
+        let counts = func_counts::FuncCounts { total: 1, exported: 1, ..func_counts::FuncCounts::default() };
+
+        let rendered = render_report("func_counts", &counts).unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed["total"], 1);
+        assert_eq!(parsed["exported"], 1);
+        assert!(rendered.contains("\"total\": 1"), "should be pretty-printed, not compact JSON");
+    }
+
+    #[test]
+    fn the_index_lists_each_expected_report_and_every_file_exists() {
+        let project_dir = temp_dir("project");
+        fs::create_dir_all(&project_dir).unwrap();
+        File::create(project_dir.join("a.go"))
+            .unwrap()
+            .write_all(b"package main\n\nfunc f() int {\n\treturn 1\n}\n")
+            .unwrap();
+
+        let output_dir = temp_dir("output");
+
+        let index = run_all_analyses(std::slice::from_ref(&project_dir), &output_dir).unwrap();
+
+        let analyses: Vec<&str> = index.reports.iter().map(|r| r.analysis.as_str()).collect();
+        assert_eq!(
+            analyses,
+            vec![
+                "complexity", "func_counts", "lints", "comment_markers", "diagnostics", "features",
+                "function_length", "imports", "import_cycles", "clones", "coupling", "empty_branches",
+                "excessive_returns", "constant_conditions",
+            ]
+        );
+
+        for report in &index.reports {
+            assert_eq!(report.format, "json");
+            let full_path = output_dir.join(&report.path);
+            assert!(full_path.is_file(), "{} should exist", full_path.display());
+        }
+
+        let index_on_disk = fs::read_to_string(output_dir.join("index.json")).unwrap();
+        let reparsed: AllAnalysesIndex = serde_json::from_str(&index_on_disk).unwrap();
+        assert_eq!(reparsed, index);
+
+        // The whole point of standardizing on <analysis>/report.json is
+        // that nothing lands loose at the output root any more — just
+        // one subdirectory per analysis plus the index.
+        let mut root_entries: Vec<String> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        root_entries.sort();
+        assert_eq!(
+            root_entries,
+            vec![
+                "clones", "comment_markers", "complexity", "constant_conditions", "coupling",
+                "diagnostics", "empty_branches", "excessive_returns", "features", "func_counts",
+                "function_length", "import_cycles", "imports", "index.json", "lints",
+            ]
+        );
+
+        fs::remove_dir_all(&project_dir).ok();
+        fs::remove_dir_all(&output_dir).ok();
+    }
+
+    #[test]
+    fn two_project_paths_are_combined_into_one_complexity_report() {
+        let project_a = temp_dir("project-a");
+        let project_b = temp_dir("project-b");
+        fs::create_dir_all(&project_a).unwrap();
+        fs::create_dir_all(&project_b).unwrap();
+        File::create(project_a.join("a.go"))
+            .unwrap()
+            .write_all(b"package main\n\nfunc fromA() int {\n\treturn 1\n}\n")
+            .unwrap();
+        File::create(project_b.join("b.go"))
+            .unwrap()
+            .write_all(b"package main\n\nfunc fromB() int {\n\treturn 2\n}\n")
+            .unwrap();
+
+        let output_dir = temp_dir("output-multi");
+        run_all_analyses(&[project_a.clone(), project_b.clone()], &output_dir).unwrap();
+
+        let complexity_json = fs::read_to_string(output_dir.join("complexity/report.json")).unwrap();
+        let report: serde_json::Value = serde_json::from_str(&complexity_json).unwrap();
+        let names: Vec<&str> =
+            report["functions"].as_array().unwrap().iter().map(|fc| fc["function"]["name"].as_str().unwrap()).collect();
+        assert!(names.contains(&"fromA"));
+        assert!(names.contains(&"fromB"));
+
+        fs::remove_dir_all(&project_a).ok();
+        fs::remove_dir_all(&project_b).ok();
+        fs::remove_dir_all(&output_dir).ok();
+    }
+}
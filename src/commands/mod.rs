@@ -0,0 +1,5 @@
+//! Multi-analysis commands that run more than one plugin function and
+//! present the combined result as a unit, rather than the single-plugin
+//! pipeline `main.rs` wires up for everyday use.
+
+pub mod all;
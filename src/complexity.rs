@@ -0,0 +1,1593 @@
+//! Cyclomatic complexity and source-size metrics, function by function.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use goscript_parser::ast::{self, Decl, Expr, Node};
+use goscript_parser::token::Token;
+
+use crate::ast_search;
+use crate::cfg_plugin;
+use crate::go_parser::{self, AstObjects, ParseDirResult, Span, line_of};
+use crate::model::{self, FunctionId};
+use crate::sloc::{self, LineCounts};
+
+/// A coarse bucket for a function's cyclomatic complexity, for reports
+/// that want a label rather than a raw number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub enum ComplexityLevel {
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+}
+
+impl fmt::Display for ComplexityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ComplexityLevel::Low => "Low",
+            ComplexityLevel::Moderate => "Moderate",
+            ComplexityLevel::High => "High",
+            ComplexityLevel::VeryHigh => "VeryHigh",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Upper bounds (inclusive) of each level below `VeryHigh`. The defaults
+/// (1-5 Low, 6-10 Moderate, 11-20 High, 21+ VeryHigh) match the informal
+/// ranges most cyclomatic-complexity tooling (gocyclo included) settles
+/// on, but different teams draw these lines differently, so they're
+/// configurable rather than baked in.
+#[derive(Debug, Clone)]
+pub struct ComplexityLevelBoundaries {
+    pub low_max: usize,
+    pub moderate_max: usize,
+    pub high_max: usize,
+}
+
+impl Default for ComplexityLevelBoundaries {
+    fn default() -> Self {
+        Self {
+            low_max: 5,
+            moderate_max: 10,
+            high_max: 20,
+        }
+    }
+}
+
+fn classify(complexity: usize, boundaries: &ComplexityLevelBoundaries) -> ComplexityLevel {
+    if complexity <= boundaries.low_max {
+        ComplexityLevel::Low
+    } else if complexity <= boundaries.moderate_max {
+        ComplexityLevel::Moderate
+    } else if complexity <= boundaries.high_max {
+        ComplexityLevel::High
+    } else {
+        ComplexityLevel::VeryHigh
+    }
+}
+
+/// How `switch`/`select`/type-switch arms count toward a decision point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SwitchCounting {
+    /// Each non-default `case`/`comm` arm is its own decision point, so a
+    /// switch with N arms adds N to the count. This is what `gocyclo`
+    /// does (it counts `ast.CaseClause`/`ast.CommClause` directly).
+    #[default]
+    PerCase,
+    /// The `switch`/`select`/type-switch itself is one decision point,
+    /// regardless of how many arms it has. This is closer to SonarGo's
+    /// cyclomatic complexity, which treats a switch as a single branch
+    /// so adding cases to an existing switch doesn't inflate complexity
+    /// as fast as adding new `if`s would.
+    PerSwitch,
+}
+
+/// Parses a `switch_counting` user param value, case-insensitively.
+/// `None` on anything else, the same convention [`parse_level`] uses.
+pub fn parse_switch_counting(s: &str) -> Option<SwitchCounting> {
+    match s.to_ascii_lowercase().as_str() {
+        "per_case" | "per-case" => Some(SwitchCounting::PerCase),
+        "per_switch" | "per-switch" => Some(SwitchCounting::PerSwitch),
+        _ => None,
+    }
+}
+
+/// User-facing knobs for [`analyze_function_complexity`].
+#[derive(Debug, Clone, Default)]
+pub struct ComplexityOptions {
+    pub boundaries: ComplexityLevelBoundaries,
+    /// When set, any function whose cyclomatic complexity exceeds this
+    /// is listed in the report's `violations`.
+    pub max_allowed_complexity: Option<usize>,
+    pub switch_counting: SwitchCounting,
+    /// When set, any function at or above this level is listed in the
+    /// report's `level_violations` — a policy like "no function may be
+    /// VeryHigh" that doesn't need a team to pick a specific numeric
+    /// threshold the way `max_allowed_complexity` does.
+    pub fail_on_level: Option<ComplexityLevel>,
+    /// When set, restricts the report to the single function named by
+    /// this [`matches_function_filter`] pattern — for iterating on one
+    /// hotspot without re-analyzing the whole project.
+    pub function_filter: Option<String>,
+    /// When set, any function with more than this many parameters is
+    /// listed in the report's `too_many_params`.
+    pub max_params: Option<usize>,
+    /// When set, restricts the report to exported API surface only: the
+    /// function/method's own name must be exported, and a method's
+    /// receiver type name must be too — an unexported helper's
+    /// complexity matters less to a library's users than what they can
+    /// actually call. See [`is_exported_api_surface`].
+    pub exported_only: bool,
+    /// When set, [`analyze_function_complexity`] records how long each
+    /// function's own analysis took in `ComplexityReport::timings` — for
+    /// tracking down which functions make a large project slow to
+    /// analyze. Off by default: like [`crate::kernel::Pipeline::run_pipeline`]'s
+    /// `profile` flag, `Instant::now()` isn't free on every platform and
+    /// most runs don't need the breakdown.
+    pub profile: bool,
+}
+
+/// Complexity and size figures for a single function.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FunctionComplexity {
+    pub function: FunctionId,
+    /// McCabe cyclomatic complexity, derived from a walk of the AST's
+    /// decision points rather than the CFG (see
+    /// [`cfg_plugin::ControlFlowGraph::cyclomatic_complexity`] for the
+    /// graph-based E-N+2 count).
+    pub cyclomatic_complexity: usize,
+    pub level: ComplexityLevel,
+    pub lines: LineCounts,
+    /// The deepest a control structure (`if`/`for`/`switch`/...) nests
+    /// inside another, starting from 1 — a flat function with no control
+    /// structures at all is still depth 1, not 0.
+    pub nesting_depth_max: usize,
+    /// 1-based line the `func` keyword starts on, for reports (like
+    /// [`render_gocyclo`]) that point a reader at the declaration.
+    pub line: usize,
+    /// Every statement in the function body, counted recursively (a
+    /// statement inside an `if`'s body counts same as one at the top
+    /// level) via [`ast_search::walk_stmts`] — excluding the parser's
+    /// own implicit trailing `Stmt::Empty` placeholders, which aren't
+    /// really statements a developer wrote.
+    pub statement_count: usize,
+    /// Parameters the function declares, with a grouped name list like
+    /// `a, b int` expanded to its individual names (`a`, `b`) rather
+    /// than counted as the one [`ast::Field`] it parses to.
+    pub parameter_count: usize,
+    /// Named and unnamed return values the function declares, expanded
+    /// the same way `parameter_count` is.
+    pub result_count: usize,
+    /// The number of blocks on the function's longest acyclic path from
+    /// entry to exit, via [`cfg_plugin::longest_acyclic_path_len`] — a
+    /// proxy for worst-case straight-line length that complements
+    /// cyclomatic complexity, which counts decision points rather than
+    /// length. `0` if the function's exit isn't reachable at all (e.g.
+    /// an infinite loop with no break).
+    pub longest_path_blocks: usize,
+    /// Whether a `//skan:ignore-complexity` or `//nolint:cyclop` comment
+    /// immediately precedes this function (see
+    /// [`SUPPRESS_COMPLEXITY_MARKERS`]). A suppressed function still gets
+    /// its real `cyclomatic_complexity`/`level` reported — suppression
+    /// only keeps it out of [`ComplexityReport::violations`] and
+    /// [`ComplexityReport::level_violations`], not out of the report.
+    pub suppressed: bool,
+}
+
+/// Comment annotations that suppress a function from the complexity
+/// threshold checks ([`ComplexityOptions::max_allowed_complexity`] and
+/// [`ComplexityOptions::fail_on_level`]) without hiding its number —
+/// for a team that's looked at a function and decided its complexity is
+/// acceptable, without having to either raise the threshold for
+/// everyone or leave the build red.
+pub const SUPPRESS_COMPLEXITY_MARKERS: &[&str] = &["skan:ignore-complexity", "nolint:cyclop"];
+
+/// Whether a comment ending on the line right before `func_line` (the
+/// 1-based line a function's own `func` keyword starts on) contains one
+/// of [`SUPPRESS_COMPLEXITY_MARKERS`]. Comments are found by tokenizing
+/// the raw source, the same way `lints::find_comment_markers` does,
+/// rather than by walking the AST — the parser doesn't attach comments
+/// to the declarations they annotate.
+fn is_complexity_suppressed(source: &str, func_line: usize) -> bool {
+    for (pos, tok, text) in go_parser::tokenize(source) {
+        let Token::COMMENT(_) = tok else { continue };
+        let start_line = line_of(source, 0, pos);
+        let end_line = start_line + text.matches('\n').count();
+        if end_line + 1 == func_line
+            && SUPPRESS_COMPLEXITY_MARKERS.iter().any(|marker| text.contains(marker))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// The result of [`analyze_function_complexity`]: every function's
+/// figures, plus the subset that exceeded `max_allowed_complexity`.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ComplexityReport {
+    pub functions: Vec<FunctionComplexity>,
+    pub violations: Vec<FunctionId>,
+    /// Functions at or above `ComplexityOptions::fail_on_level`, if that
+    /// policy was set.
+    pub level_violations: Vec<FunctionId>,
+    /// Functions with more parameters than `ComplexityOptions::max_params`,
+    /// if that policy was set.
+    pub too_many_params: Vec<FunctionId>,
+    /// Conditions that quietly narrowed this report to fewer functions
+    /// than a caller might expect (currently just an
+    /// `options.function_filter` that matched nothing) — so an empty
+    /// report reads as "the filter is wrong" rather than "this project
+    /// genuinely has no functions."
+    pub warnings: Vec<String>,
+    /// Each function's own analysis wall-clock time, when
+    /// `ComplexityOptions::profile` was set.
+    #[serde(skip)]
+    pub timings: Option<BTreeMap<FunctionId, Duration>>,
+}
+
+/// Median and 90th-percentile cyclomatic complexity across a report's
+/// functions, for a one-line "how spread out is this" summary next to
+/// the full distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComplexityPercentiles {
+    pub median: f64,
+    pub p90: usize,
+}
+
+/// Parses a [`ComplexityLevel`] from its `Display` name, case-
+/// insensitively, for reading `fail_on_level` off the command line.
+pub fn parse_level(s: &str) -> Option<ComplexityLevel> {
+    match s.to_ascii_lowercase().as_str() {
+        "low" => Some(ComplexityLevel::Low),
+        "moderate" => Some(ComplexityLevel::Moderate),
+        "high" => Some(ComplexityLevel::High),
+        "veryhigh" => Some(ComplexityLevel::VeryHigh),
+        _ => None,
+    }
+}
+
+/// The complexity (as an index into a sorted list) at percentile `p`
+/// (0.0-1.0), using the "nearest rank" method: good enough for a report
+/// summary without pulling in a stats crate for linear interpolation.
+fn percentile(sorted: &[usize], p: f64) -> usize {
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[rank]
+}
+
+impl ComplexityReport {
+    /// Whether this report should fail a build: any one of the three
+    /// violation lists is enough — `violations` and `level_violations`
+    /// are two ways to express the same "this function is too complex"
+    /// policy, and `too_many_params` flags an overlong parameter list.
+    pub fn fails_policy(&self) -> bool {
+        !self.violations.is_empty()
+            || !self.level_violations.is_empty()
+            || !self.too_many_params.is_empty()
+    }
+
+    /// How many functions fall into each [`ComplexityLevel`], keyed by
+    /// the level's `Display` name so it renders directly in a report
+    /// without the caller needing to know the enum.
+    pub fn complexity_distribution(&self) -> BTreeMap<String, usize> {
+        let mut distribution = BTreeMap::new();
+        for fc in &self.functions {
+            *distribution.entry(fc.level.to_string()).or_insert(0) += 1;
+        }
+        distribution
+    }
+
+    /// Median and p90 cyclomatic complexity across every function in
+    /// this report, or `None` if it has none.
+    pub fn percentiles(&self) -> Option<ComplexityPercentiles> {
+        if self.functions.is_empty() {
+            return None;
+        }
+        let mut complexities: Vec<usize> =
+            self.functions.iter().map(|fc| fc.cyclomatic_complexity).collect();
+        complexities.sort_unstable();
+
+        Some(ComplexityPercentiles {
+            median: percentile(&complexities, 0.5) as f64,
+            p90: percentile(&complexities, 0.9),
+        })
+    }
+
+    /// Average and maximum physical-line length across every function in
+    /// this report (from [`FunctionComplexity::lines`], already sliced
+    /// from each function's own [`ast::FuncDecl`] span), plus every
+    /// function longer than `max_lines` when a limit is given. `None` if
+    /// this report has no functions to summarize.
+    pub fn function_length_report(&self, max_lines: Option<usize>) -> Option<FunctionLengthReport> {
+        if self.functions.is_empty() {
+            return None;
+        }
+        let lengths: Vec<usize> = self.functions.iter().map(|fc| fc.lines.physical).collect();
+        let total: usize = lengths.iter().sum();
+        let longest = *lengths.iter().max().unwrap();
+
+        let violations = match max_lines {
+            Some(limit) => self
+                .functions
+                .iter()
+                .filter(|fc| fc.lines.physical > limit)
+                .map(|fc| fc.function.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Some(FunctionLengthReport {
+            average_lines: total as f64 / lengths.len() as f64,
+            max_lines: longest,
+            violations,
+        })
+    }
+}
+
+/// Average/max physical-line length across a [`ComplexityReport`]'s
+/// functions, plus the ones over a configurable limit — the same
+/// "central tendency plus violations" shape [`ComplexityPercentiles`]/
+/// [`ComplexityReport::violations`] already use, just for line count
+/// rather than cyclomatic complexity.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FunctionLengthReport {
+    pub average_lines: f64,
+    pub max_lines: usize,
+    pub violations: Vec<FunctionId>,
+}
+
+/// Per-file rollup of a [`ComplexityReport`]'s functions, covering
+/// every file `parsed` knows about — including a types/vars/consts-only
+/// file with no functions at all, which gets `average_complexity: None`
+/// rather than a `0.0` that would misleadingly read as "every function
+/// here is trivial" when there's nothing here to average in the first
+/// place.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct FileComplexitySummary {
+    pub functions: usize,
+    pub average_complexity: Option<f64>,
+}
+
+/// Groups `report`'s functions by file, padding in every file `parsed`
+/// has even when it contributed zero functions, so a caller can tell
+/// "no functions to analyze" apart from "functions here, all simple".
+pub fn complexity_by_file(
+    parsed: &ParseDirResult,
+    report: &ComplexityReport,
+) -> BTreeMap<String, FileComplexitySummary> {
+    let mut by_file: BTreeMap<String, FileComplexitySummary> = BTreeMap::new();
+
+    for pkg in parsed.packages.values() {
+        for file_name in pkg.files.keys() {
+            by_file.entry(file_name.clone()).or_default();
+        }
+    }
+
+    for fc in &report.functions {
+        by_file.entry(fc.function.file.clone()).or_default().functions += 1;
+    }
+
+    for (file_name, summary) in by_file.iter_mut() {
+        if summary.functions == 0 {
+            summary.average_complexity = None;
+            continue;
+        }
+        let total: usize = report
+            .functions
+            .iter()
+            .filter(|fc| &fc.function.file == file_name)
+            .map(|fc| fc.cyclomatic_complexity)
+            .sum();
+        summary.average_complexity = Some(total as f64 / summary.functions as f64);
+    }
+
+    by_file
+}
+
+/// Renders [`ComplexityReport::complexity_distribution`] as a one-bar-
+/// per-level ASCII histogram, levels always listed `Low` through
+/// `VeryHigh` even when a level has no functions, so the shape of the
+/// distribution is visible at a glance.
+pub fn render_ascii_histogram(report: &ComplexityReport) -> String {
+    let distribution = report.complexity_distribution();
+    let levels = [
+        ComplexityLevel::Low,
+        ComplexityLevel::Moderate,
+        ComplexityLevel::High,
+        ComplexityLevel::VeryHigh,
+    ];
+
+    let mut out = String::new();
+    for level in levels {
+        let count = distribution.get(&level.to_string()).copied().unwrap_or(0);
+        out.push_str(&format!(
+            "{:<10} | {} {count}\n",
+            level.to_string(),
+            "#".repeat(count)
+        ));
+    }
+    out
+}
+
+/// Light/dark presentation for [`render_html_histogram`], for readers
+/// with contrast needs the other theme doesn't meet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HtmlTheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl HtmlTheme {
+    fn css_class(self) -> &'static str {
+        match self {
+            HtmlTheme::Light => "theme-light",
+            HtmlTheme::Dark => "theme-dark",
+        }
+    }
+}
+
+/// [`render_html_histogram`]'s presentation knobs: a light/dark
+/// [`HtmlTheme`], plus optional per-level color overrides for a team
+/// whose palette (or contrast requirements) the theme defaults don't
+/// cover. A level missing from `level_colors` falls back to that
+/// theme's own default for it.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlThemeOptions {
+    pub theme: HtmlTheme,
+    pub level_colors: BTreeMap<ComplexityLevel, String>,
+}
+
+/// The four [`ComplexityLevel`]s in the fixed order every HTML/ASCII
+/// rendering of a distribution lists them in: `Low` through `VeryHigh`,
+/// so a level with zero functions still gets a bar/legend entry.
+const LEVELS: [ComplexityLevel; 4] = [
+    ComplexityLevel::Low,
+    ComplexityLevel::Moderate,
+    ComplexityLevel::High,
+    ComplexityLevel::VeryHigh,
+];
+
+/// `theme`'s built-in color for `level`, used when `options.level_colors`
+/// doesn't override it. Chosen to hold reasonable contrast against each
+/// theme's own background rather than sharing one palette between both.
+fn default_level_color(level: ComplexityLevel, theme: HtmlTheme) -> &'static str {
+    match (theme, level) {
+        (HtmlTheme::Light, ComplexityLevel::Low) => "#2e7d32",
+        (HtmlTheme::Light, ComplexityLevel::Moderate) => "#f9a825",
+        (HtmlTheme::Light, ComplexityLevel::High) => "#ef6c00",
+        (HtmlTheme::Light, ComplexityLevel::VeryHigh) => "#c62828",
+        (HtmlTheme::Dark, ComplexityLevel::Low) => "#66bb6a",
+        (HtmlTheme::Dark, ComplexityLevel::Moderate) => "#ffca28",
+        (HtmlTheme::Dark, ComplexityLevel::High) => "#ffa726",
+        (HtmlTheme::Dark, ComplexityLevel::VeryHigh) => "#ef5350",
+    }
+}
+
+fn level_color(level: ComplexityLevel, options: &HtmlThemeOptions) -> String {
+    options
+        .level_colors
+        .get(&level)
+        .cloned()
+        .unwrap_or_else(|| default_level_color(level, options.theme).to_string())
+}
+
+/// Renders a legend mapping each [`ComplexityLevel`] to the color
+/// [`render_html_histogram`] draws it with under `options` — so a
+/// color-coded report stays legible to a reader who can't distinguish
+/// the colors themselves, not just one who can.
+pub fn render_html_legend(options: &HtmlThemeOptions) -> String {
+    let mut out = format!("<ul class=\"complexity-legend {}\">\n", options.theme.css_class());
+    for level in LEVELS {
+        let color = level_color(level, options);
+        out.push_str(&format!(
+            "  <li><span class=\"swatch\" style=\"background-color: {color}\"></span>{level}</li>\n"
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+/// Renders [`ComplexityReport::complexity_distribution`] as a minimal
+/// HTML bar chart under `options`'s theme: one `<div>` per level, its
+/// width proportional to that level's share of the tallest bar and its
+/// color from `options`, followed by a [`render_html_legend`] so the
+/// color coding is always explained rather than assumed obvious. Pure
+/// string templating — no JS, no build step — so it drops straight into
+/// a static report page.
+pub fn render_html_histogram(report: &ComplexityReport, options: &HtmlThemeOptions) -> String {
+    let distribution = report.complexity_distribution();
+    let counts: Vec<usize> = LEVELS
+        .iter()
+        .map(|level| distribution.get(&level.to_string()).copied().unwrap_or(0))
+        .collect();
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+
+    let mut out = format!("<div class=\"complexity-histogram {}\">\n", options.theme.css_class());
+    for (level, count) in LEVELS.iter().zip(&counts) {
+        let width_pct = (*count as f64 / max as f64) * 100.0;
+        let color = level_color(*level, options);
+        out.push_str(&format!(
+            "  <div class=\"bar\"><span class=\"label\">{level}</span><span class=\"fill\" style=\"width: {width_pct:.1}%; background-color: {color}\"></span><span class=\"count\">{count}</span></div>\n"
+        ));
+    }
+    out.push_str("</div>\n");
+    out.push_str(&render_html_legend(options));
+    out
+}
+
+/// Renders a report in `gocyclo`'s own text format — one line per
+/// function, `<complexity> <package> <function> <file>:<line>:<column>`,
+/// sorted by descending complexity so the worst offenders are first, the
+/// same order `gocyclo`'s CLI prints in. There's no column tracking in
+/// this codebase's AST positions, so `column` is always `1`.
+pub fn render_gocyclo(report: &ComplexityReport) -> String {
+    render_gocyclo_with_options(report, &ReportPresentationOptions::default())
+}
+
+/// How [`sort_and_limit_functions`] orders a [`ComplexityReport`]'s
+/// function list before it's rendered or truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComplexitySortKey {
+    /// Highest cyclomatic complexity first — the default, since a report
+    /// over a large project is read to find what needs attention, not to
+    /// browse alphabetically.
+    #[default]
+    Complexity,
+    Name,
+}
+
+/// Controls how a large [`ComplexityReport`] gets presented instead of
+/// dumped as one unsorted, unbounded table — see
+/// [`sort_and_limit_functions`]/[`render_gocyclo_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReportPresentationOptions {
+    pub sort_by: ComplexitySortKey,
+    /// When set, keeps only the first `top_n` entries after sorting —
+    /// "the 20 worst offenders" rather than every function in the
+    /// project.
+    pub top_n: Option<usize>,
+}
+
+/// Sorts (and, if `options.top_n` is set, truncates) `functions` per
+/// `options`. Used by [`render_gocyclo_with_options`], and equally
+/// useful to a caller assembling its own JSON or HTML table instead.
+pub fn sort_and_limit_functions(
+    functions: &[FunctionComplexity],
+    options: &ReportPresentationOptions,
+) -> Vec<FunctionComplexity> {
+    let mut sorted = functions.to_vec();
+    match options.sort_by {
+        ComplexitySortKey::Complexity => {
+            sorted.sort_by_key(|fc| std::cmp::Reverse(fc.cyclomatic_complexity))
+        }
+        ComplexitySortKey::Name => sorted.sort_by(|a, b| a.function.name.cmp(&b.function.name)),
+    }
+    if let Some(top_n) = options.top_n {
+        sorted.truncate(top_n);
+    }
+    sorted
+}
+
+/// Like [`render_gocyclo`], but sorted and truncated per `options`
+/// instead of always emitting every function sorted by complexity.
+pub fn render_gocyclo_with_options(report: &ComplexityReport, options: &ReportPresentationOptions) -> String {
+    let mut out = String::new();
+    for fc in sort_and_limit_functions(&report.functions, options) {
+        out.push_str(&format!(
+            "{} {} {} {}:{}:1\n",
+            fc.cyclomatic_complexity, fc.function.package, fc.function.name, fc.function.file, fc.line
+        ));
+    }
+    out
+}
+
+/// Counts every `&&`/`||` operator reachable from `stmts` — not just ones
+/// sitting directly in an `if`/`for` condition, but anywhere in the
+/// function body (an assignment's right-hand side, a `return`, a bare
+/// expression statement, a closure passed to `defer`, ...) — via
+/// [`ast_search::count_exprs_by`], matching real `gocyclo`, which counts
+/// every `Token::LAND`/`Token::LOR` it finds regardless of where it sits.
+fn count_boolean_operators(stmts: &[ast::Stmt], objects: &AstObjects) -> usize {
+    ast_search::count_exprs_by(stmts, objects, |e| {
+        matches!(e, Expr::Binary(b) if matches!(b.op, Token::LAND | Token::LOR))
+    })
+}
+
+/// Walks a statement (and, recursively, any statements it contains),
+/// incrementing `decisions` once per point of branching control flow.
+/// Each `if` and loop adds one decision point, matching the informal
+/// McCabe rule of thumb of "one more path through the function per
+/// branch"; how `switch`/`select`/type-switch arms count is governed by
+/// `switch_counting`. `&&`/`||` are counted separately, once per function,
+/// by [`count_boolean_operators`] — they aren't tied to any one statement.
+///
+/// This walk stays AST-based rather than delegating to
+/// [`cfg_plugin::ControlFlowGraph::cyclomatic_complexity`] because it
+/// tracks something that graph doesn't: `switch_counting` lets a caller
+/// choose whether a switch counts as one decision or one per arm, whereas
+/// the graph always has one edge per `case`.
+fn analyze_statement_for_decision_point(
+    stmt: &ast::Stmt,
+    objects: &AstObjects,
+    switch_counting: SwitchCounting,
+    decisions: &mut usize,
+) {
+    match stmt {
+        ast::Stmt::Block(b) => {
+            for s in &b.list {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::If(i) => {
+            *decisions += 1;
+            if let Some(init) = &i.init {
+                analyze_statement_for_decision_point(init, objects, switch_counting, decisions);
+            }
+            for s in &i.body.list {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+            if let Some(els) = &i.els {
+                analyze_statement_for_decision_point(els, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::For(f) => {
+            *decisions += 1;
+            for s in &f.body.list {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::Range(r) => {
+            *decisions += 1;
+            for s in &r.body.list {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::Switch(sw) => {
+            if switch_counting == SwitchCounting::PerSwitch {
+                *decisions += 1;
+            }
+            for s in &sw.body.list {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::TypeSwitch(sw) => {
+            if switch_counting == SwitchCounting::PerSwitch {
+                *decisions += 1;
+            }
+            for s in &sw.body.list {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::Select(sel) => {
+            if switch_counting == SwitchCounting::PerSwitch {
+                *decisions += 1;
+            }
+            for s in &sel.body.list {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::Case(case) => {
+            // A `default:` clause (no `list`) isn't a decision point: it's
+            // what happens when nothing else matches.
+            if switch_counting == SwitchCounting::PerCase && case.list.is_some() {
+                *decisions += 1;
+            }
+            for s in &case.body {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::Comm(comm) => {
+            if switch_counting == SwitchCounting::PerCase && comm.comm.is_some() {
+                *decisions += 1;
+            }
+            for s in &comm.body {
+                analyze_statement_for_decision_point(s, objects, switch_counting, decisions);
+            }
+        }
+        ast::Stmt::Labeled(key) => {
+            let labeled = &objects.l_stmts[*key];
+            analyze_statement_for_decision_point(&labeled.stmt, objects, switch_counting, decisions);
+        }
+        _ => {}
+    }
+}
+
+/// The deepest level a control structure reaches across `stmts`, given
+/// that `stmts` itself sits at nesting level `level` (0 for a function's
+/// own top-level block). A `case`/`comm` clause's body is walked at its
+/// enclosing switch/select's level rather than one level deeper, since a
+/// clause is a branch of that construct, not additional nesting inside
+/// it — the same reasoning an `if`'s `else` branch gets.
+fn block_nesting_depth(stmts: &[ast::Stmt], objects: &AstObjects, level: usize) -> usize {
+    stmts
+        .iter()
+        .map(|s| stmt_nesting_depth(s, objects, level))
+        .max()
+        .unwrap_or(level)
+}
+
+fn stmt_nesting_depth(stmt: &ast::Stmt, objects: &AstObjects, level: usize) -> usize {
+    match stmt {
+        ast::Stmt::If(i) => {
+            let mut max_depth = block_nesting_depth(&i.body.list, objects, level + 1);
+            if let Some(els) = &i.els {
+                max_depth = max_depth.max(stmt_nesting_depth(els, objects, level));
+            }
+            max_depth
+        }
+        ast::Stmt::For(f) => block_nesting_depth(&f.body.list, objects, level + 1),
+        ast::Stmt::Range(r) => block_nesting_depth(&r.body.list, objects, level + 1),
+        ast::Stmt::Switch(sw) => block_nesting_depth(&sw.body.list, objects, level + 1),
+        ast::Stmt::TypeSwitch(sw) => block_nesting_depth(&sw.body.list, objects, level + 1),
+        ast::Stmt::Select(sel) => block_nesting_depth(&sel.body.list, objects, level + 1),
+        ast::Stmt::Case(case) => block_nesting_depth(&case.body, objects, level),
+        ast::Stmt::Comm(comm) => block_nesting_depth(&comm.body, objects, level),
+        ast::Stmt::Block(b) => block_nesting_depth(&b.list, objects, level + 1),
+        ast::Stmt::Labeled(key) => {
+            stmt_nesting_depth(&objects.l_stmts[*key].stmt, objects, level)
+        }
+        _ => level,
+    }
+}
+
+/// [`block_nesting_depth`] of a function's whole body, floored at 1 so a
+/// flat function with no control structures is still "depth 1" rather
+/// than 0.
+fn nesting_depth(body: &ast::BlockStmt, objects: &AstObjects) -> usize {
+    block_nesting_depth(&body.list, objects, 0).max(1)
+}
+
+/// The number of individual names `fields` declares, expanding a
+/// grouped declaration like `a, b int` (one [`ast::Field`] with two
+/// names) into 2 rather than 1. An unnamed field (as in an interface
+/// method's parameter list, or a bare type in a result list) still
+/// counts as one.
+pub(crate) fn field_list_len(fields: &ast::FieldList, objects: &AstObjects) -> usize {
+    fields
+        .list
+        .iter()
+        .map(|key| objects.fields[*key].names.len().max(1))
+        .sum()
+}
+
+/// Whether `fdecl` is part of a package's exported API surface: its own
+/// name must be exported (per [`ast::is_exported`]), and if it's a
+/// method, its receiver's type name must be exported too — a method on
+/// an unexported type isn't something a caller outside the package can
+/// name, however capitalized the method itself is.
+fn is_exported_api_surface(fdecl: &ast::FuncDecl, objects: &AstObjects) -> bool {
+    if !ast::is_exported(&objects.idents[fdecl.name].name) {
+        return false;
+    }
+    let Some(recv_field_key) = fdecl.recv.as_ref().and_then(|recv| recv.list.first()) else {
+        return true;
+    };
+    match &objects.fields[*recv_field_key].typ {
+        ast::Expr::Ident(key) => ast::is_exported(&objects.idents[*key].name),
+        ast::Expr::Star(star) => match &star.expr {
+            ast::Expr::Ident(key) => ast::is_exported(&objects.idents[*key].name),
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+fn func_span(fdecl: &ast::FuncDecl, objects: &AstObjects) -> Span {
+    let start = fdecl.pos(objects);
+    let end = match &fdecl.body {
+        Some(body) => body.end(),
+        None => fdecl.typ.end(objects),
+    };
+    Span::new(start, end)
+}
+
+fn source_slice(source: &str, base: usize, span: Span) -> String {
+    let start = span.start.saturating_sub(base);
+    let end = span.end.saturating_sub(base);
+    source
+        .chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect()
+}
+
+/// Computes complexity and line-count figures for every function in every
+/// package of `parsed`, classifying each under `options.boundaries` and
+/// collecting any that exceed `options.max_allowed_complexity` into
+/// `ComplexityReport::violations`.
+pub fn analyze_function_complexity(
+    parsed: &ParseDirResult,
+    options: &ComplexityOptions,
+) -> ComplexityReport {
+    let mut report = ComplexityReport::default();
+    if options.profile {
+        report.timings = Some(BTreeMap::new());
+    }
+    for (pkg_dir, pkg) in &parsed.packages {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function = FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+                if options
+                    .function_filter
+                    .as_deref()
+                    .is_some_and(|filter| !model::matches_function_filter(&function, filter))
+                {
+                    continue;
+                }
+                if options.exported_only && !is_exported_api_surface(fdecl, &parsed.objects) {
+                    continue;
+                }
+
+                let started = options.profile.then(Instant::now);
+                let mut decisions = 0;
+                for stmt in &body.list {
+                    analyze_statement_for_decision_point(
+                        stmt,
+                        &parsed.objects,
+                        options.switch_counting,
+                        &mut decisions,
+                    );
+                }
+                decisions += count_boolean_operators(&body.list, &parsed.objects);
+
+                let span = func_span(fdecl, &parsed.objects);
+                let src = source_slice(&pf.source, pf.base, span);
+                let cyclomatic_complexity = decisions + 1;
+                let level = classify(cyclomatic_complexity, &options.boundaries);
+                let line = line_of(&pf.source, pf.base, span.start);
+                let suppressed = is_complexity_suppressed(&pf.source, line);
+
+                if !suppressed
+                    && options
+                        .max_allowed_complexity
+                        .is_some_and(|max| cyclomatic_complexity > max)
+                {
+                    report.violations.push(function.clone());
+                }
+                if !suppressed && options.fail_on_level.is_some_and(|min| level >= min) {
+                    report.level_violations.push(function.clone());
+                }
+
+                let ftype = &parsed.objects.ftypes[fdecl.typ];
+                let parameter_count = field_list_len(&ftype.params, &parsed.objects);
+                let result_count = ftype
+                    .results
+                    .as_ref()
+                    .map_or(0, |fields| field_list_len(fields, &parsed.objects));
+
+                if options.max_params.is_some_and(|max| parameter_count > max) {
+                    report.too_many_params.push(function.clone());
+                }
+
+                // `Stmt::Empty` doesn't count: the parser inserts one as a
+                // semicolon placeholder before most closing `}`s, and
+                // counting those would make statement counts depend on
+                // parser implementation detail rather than what's actually
+                // in the function.
+                let mut statement_count = 0;
+                ast_search::walk_stmts(&body.list, &parsed.objects, &mut |stmt| {
+                    if !matches!(stmt, ast::Stmt::Empty(_)) {
+                        statement_count += 1;
+                    }
+                });
+
+                let cfg = cfg_plugin::build_cfg(body, &parsed.objects);
+                let longest_path_blocks = cfg_plugin::longest_acyclic_path_len(&cfg);
+
+                if let (Some(started), Some(timings)) = (started, report.timings.as_mut()) {
+                    timings.insert(function.clone(), started.elapsed());
+                }
+
+                report.functions.push(FunctionComplexity {
+                    function,
+                    cyclomatic_complexity,
+                    level,
+                    lines: sloc::count_lines(&src),
+                    nesting_depth_max: nesting_depth(body, &parsed.objects),
+                    line,
+                    statement_count,
+                    parameter_count,
+                    result_count,
+                    longest_path_blocks,
+                    suppressed,
+                });
+            }
+        }
+        let _ = pkg_dir;
+    }
+    if let Some(filter) = &options.function_filter
+        && report.functions.is_empty()
+    {
+        report.warnings.push(format!("function filter `{filter}` matched no functions"));
+    }
+    report
+}
+
+/// Runs [`analyze_function_complexity`] over several already-parsed
+/// projects and concatenates the results into one report — for a
+/// multi-repo or multi-module checkout analyzed as a single unit rather
+/// than one report per module. Doesn't try to detect a function declared
+/// in more than one project under the same [`FunctionId`]; two modules
+/// that happen to share a package/file/function name both show up as
+/// distinct entries, same as they'd be distinct declarations in reality.
+pub fn analyze_function_complexity_across(
+    parsed_projects: &[ParseDirResult],
+    options: &ComplexityOptions,
+) -> ComplexityReport {
+    let mut combined = ComplexityReport::default();
+    for parsed in parsed_projects {
+        let report = analyze_function_complexity(parsed, options);
+        combined.functions.extend(report.functions);
+        combined.violations.extend(report.violations);
+        combined.level_violations.extend(report.level_violations);
+        combined.too_many_params.extend(report.too_many_params);
+        combined.warnings.extend(report.warnings);
+    }
+    combined
+}
+
+/// Runs [`analyze_function_complexity`] one file at a time via
+/// [`go_parser::parse_dir_streaming`], calling `on_function` for each
+/// file's [`FunctionComplexity`] results as soon as that file is done —
+/// rather than [`analyze_function_complexity`]'s own all-at-once pass
+/// over a fully parsed [`ParseDirResult`], which needs the whole project
+/// in memory before it can report anything. Each file is wrapped as its
+/// own single-file, single-package [`ParseDirResult`], so a filter or
+/// policy option that only looks within one function's own file (not
+/// cross-file) behaves the same as it would under the eager path.
+pub fn analyze_function_complexity_streaming(
+    root: &std::path::Path,
+    options: &ComplexityOptions,
+    mut on_function: impl FnMut(&FunctionComplexity),
+) -> Result<(), go_parser::ParseDirError> {
+    go_parser::parse_dir_streaming(root, |streamed| {
+        let file_name = streamed.file.path.to_string_lossy().into_owned();
+        let mut files = BTreeMap::new();
+        files.insert(file_name, streamed.file);
+        let mut packages = BTreeMap::new();
+        packages.insert(
+            streamed.package.clone(),
+            go_parser::Package { name: streamed.package, files },
+        );
+        let parsed = ParseDirResult {
+            objects: streamed.objects,
+            file_set: go_parser::FileSet::new(),
+            packages,
+            warnings: Vec::new(),
+        };
+
+        let report = analyze_function_complexity(&parsed, options);
+        for fc in &report.functions {
+            on_function(fc);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn parse_one(src: &str) -> ParseDirResult {
+        let dir = tempfile_dir();
+        let path = dir.join("a.go");
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    fn parse_files(files: &[(&str, &str)]) -> ParseDirResult {
+        let dir = tempfile_dir();
+        for (name, src) in files {
+            std::fs::File::create(dir.join(name))
+                .unwrap()
+                .write_all(src.as_bytes())
+                .unwrap();
+        }
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    fn tempfile_dir() -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-complexity-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn counts_lines_and_complexity_per_function() {
+        let src = "package main\n\nfunc f(x int) int {\n\t// doubles x\n\tif x > 0 {\n\t\treturn x * 2\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        assert_eq!(report.functions.len(), 1);
+        let fc = &report.functions[0];
+        assert_eq!(fc.function.name, "f");
+        assert_eq!(fc.cyclomatic_complexity, 2);
+        assert_eq!(fc.level, ComplexityLevel::Low);
+        assert_eq!(fc.lines.comment, 1);
+        assert!(fc.lines.physical >= 5);
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn custom_boundaries_reclassify_the_same_function() {
+        let src = "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn x * 2\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let options = ComplexityOptions {
+            boundaries: ComplexityLevelBoundaries {
+                low_max: 1,
+                moderate_max: 1,
+                high_max: 1,
+            },
+            max_allowed_complexity: None,
+            switch_counting: SwitchCounting::default(),
+            fail_on_level: None,
+            function_filter: None,
+            max_params: None,
+            exported_only: false,
+            profile: false,
+        };
+        let report = analyze_function_complexity(&parsed, &options);
+        assert_eq!(report.functions[0].level, ComplexityLevel::VeryHigh);
+    }
+
+    #[test]
+    fn functions_over_the_allowed_complexity_are_reported_as_violations() {
+        let src = "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn x * 2\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let options = ComplexityOptions {
+            boundaries: ComplexityLevelBoundaries::default(),
+            max_allowed_complexity: Some(1),
+            switch_counting: SwitchCounting::default(),
+            fail_on_level: None,
+            function_filter: None,
+            max_params: None,
+            exported_only: false,
+            profile: false,
+        };
+        let report = analyze_function_complexity(&parsed, &options);
+        assert_eq!(report.violations, vec![report.functions[0].function.clone()]);
+    }
+
+    #[test]
+    fn profiling_records_a_timing_entry_per_function() {
+        let src = "package main\n\nfunc f() {}\n\nfunc g() {}\n";
+        let parsed = parse_one(src);
+
+        let report = analyze_function_complexity(
+            &parsed,
+            &ComplexityOptions {
+                profile: true,
+                ..Default::default()
+            },
+        );
+        let timings = report.timings.expect("profiling was requested");
+        assert_eq!(timings.len(), report.functions.len());
+        for fc in &report.functions {
+            assert!(timings.contains_key(&fc.function));
+        }
+    }
+
+    #[test]
+    fn switch_counting_mode_changes_complexity_of_a_four_case_switch() {
+        let src = "package main\n\nfunc f(x int) int {\n\tswitch x {\n\tcase 1:\n\t\treturn 1\n\tcase 2:\n\t\treturn 2\n\tcase 3:\n\t\treturn 3\n\tcase 4:\n\t\treturn 4\n\tdefault:\n\t\treturn 0\n\t}\n}\n";
+        let parsed = parse_one(src);
+
+        let per_case = analyze_function_complexity(
+            &parsed,
+            &ComplexityOptions {
+                switch_counting: SwitchCounting::PerCase,
+                ..Default::default()
+            },
+        );
+        assert_eq!(per_case.functions[0].cyclomatic_complexity, 5);
+
+        let per_switch = analyze_function_complexity(
+            &parsed,
+            &ComplexityOptions {
+                switch_counting: SwitchCounting::PerSwitch,
+                ..Default::default()
+            },
+        );
+        assert_eq!(per_switch.functions[0].cyclomatic_complexity, 2);
+    }
+
+    #[test]
+    fn adding_a_boolean_and_raises_complexity_by_one() {
+        let without_and = "package main\n\nfunc f(a, b bool) bool {\n\tif a {\n\t\treturn true\n\t}\n\treturn b\n}\n";
+        let with_and = "package main\n\nfunc f(a, b bool) bool {\n\tif a && b {\n\t\treturn true\n\t}\n\treturn b\n}\n";
+
+        let parsed_without = parse_one(without_and);
+        let parsed_with = parse_one(with_and);
+
+        let report_without =
+            analyze_function_complexity(&parsed_without, &ComplexityOptions::default());
+        let report_with = analyze_function_complexity(&parsed_with, &ComplexityOptions::default());
+
+        assert_eq!(
+            report_with.functions[0].cyclomatic_complexity,
+            report_without.functions[0].cyclomatic_complexity + 1
+        );
+    }
+
+    #[test]
+    fn a_boolean_or_outside_any_if_or_for_condition_still_raises_complexity() {
+        let without_or = "package main\n\nfunc f(a, b bool) bool {\n\tresult := a\n\treturn result\n}\n";
+        let with_or = "package main\n\nfunc f(a, b bool) bool {\n\tresult := a || b\n\treturn result\n}\n";
+
+        let report_without =
+            analyze_function_complexity(&parse_one(without_or), &ComplexityOptions::default());
+        let report_with =
+            analyze_function_complexity(&parse_one(with_or), &ComplexityOptions::default());
+
+        assert_eq!(
+            report_with.functions[0].cyclomatic_complexity,
+            report_without.functions[0].cyclomatic_complexity + 1
+        );
+    }
+
+    #[test]
+    fn distribution_and_p90_match_a_known_set_of_functions() {
+        // Ten functions with cyclomatic complexity 1 through 10: five Low
+        // (1-5), five Moderate (6-10), under the default boundaries.
+        let mut src = String::from("package main\n\n");
+        for n in 1..=10 {
+            let ifs: String = (1..n).map(|i| format!("\tif x > {i} {{\n\t\tx++\n\t}}\n")).collect();
+            src.push_str(&format!("func f{n}(x int) int {{\n{ifs}\treturn x\n}}\n\n"));
+        }
+
+        let parsed = parse_one(&src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        assert_eq!(report.functions.len(), 10);
+
+        let distribution = report.complexity_distribution();
+        assert_eq!(distribution.get("Low").copied(), Some(5));
+        assert_eq!(distribution.get("Moderate").copied(), Some(5));
+        assert_eq!(distribution.get("High"), None);
+        assert_eq!(distribution.get("VeryHigh"), None);
+
+        let percentiles = report.percentiles().unwrap();
+        assert_eq!(percentiles.median, 6.0);
+        assert_eq!(percentiles.p90, 9);
+
+        let histogram = render_ascii_histogram(&report);
+        assert!(histogram.contains("Low        | ##### 5"));
+        assert!(histogram.contains("VeryHigh   |  0"));
+    }
+
+    #[test]
+    fn analyze_function_complexity_across_combines_functions_from_every_project() {
+        let a = parse_one("package main\nfunc fromA() {}\n");
+        let b = parse_one("package main\nfunc fromB() {}\n");
+
+        let combined = analyze_function_complexity_across(&[a, b], &ComplexityOptions::default());
+        let names: Vec<&str> = combined.functions.iter().map(|fc| fc.function.name.as_str()).collect();
+
+        assert_eq!(combined.functions.len(), 2);
+        assert!(names.contains(&"fromA"));
+        assert!(names.contains(&"fromB"));
+    }
+
+    #[test]
+    fn function_length_report_averages_correctly_and_flags_the_long_function_only_under_a_small_limit() {
+        let short = "func short() {\n\tx := 1\n\t_ = x\n}\n";
+        let long_body: String = (0..20).map(|i| format!("\ty{i} := {i}\n\t_ = y{i}\n")).collect();
+        let long = format!("func long() {{\n{long_body}}}\n");
+        let src = format!("package main\n\n{short}\n{long}\n");
+
+        let parsed = parse_one(&src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        assert_eq!(report.functions.len(), 2);
+
+        let short_lines = report.functions.iter().find(|fc| fc.function.name == "short").unwrap().lines.physical;
+        let long_lines = report.functions.iter().find(|fc| fc.function.name == "long").unwrap().lines.physical;
+        assert!(long_lines > short_lines);
+
+        let unbounded = report.function_length_report(None).unwrap();
+        assert_eq!(unbounded.max_lines, long_lines);
+        assert_eq!(unbounded.average_lines, (short_lines + long_lines) as f64 / 2.0);
+        assert!(unbounded.violations.is_empty());
+
+        let small_limit = report.function_length_report(Some(short_lines)).unwrap();
+        assert_eq!(small_limit.violations.len(), 1);
+        assert_eq!(small_limit.violations[0].name, "long");
+
+        let large_limit = report.function_length_report(Some(long_lines)).unwrap();
+        assert!(large_limit.violations.is_empty());
+    }
+
+    #[test]
+    fn fail_on_level_flags_a_very_high_function_even_without_a_numeric_threshold() {
+        let mut ifs = String::new();
+        for i in 1..25 {
+            ifs.push_str(&format!("\tif x > {i} {{\n\t\tx++\n\t}}\n"));
+        }
+        let src = format!("package main\n\nfunc f(x int) int {{\n{ifs}\treturn x\n}}\n");
+        let parsed = parse_one(&src);
+
+        let report = analyze_function_complexity(
+            &parsed,
+            &ComplexityOptions {
+                fail_on_level: Some(ComplexityLevel::High),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(report.functions[0].level, ComplexityLevel::VeryHigh);
+        assert_eq!(report.level_violations, vec![report.functions[0].function.clone()]);
+        assert!(report.fails_policy());
+    }
+
+    #[test]
+    fn function_filter_restricts_the_report_to_the_named_function() {
+        let src = "package main\n\nfunc Foo() int {\n\treturn 1\n}\n\nfunc Bar() int {\n\tif true {\n\t\treturn 2\n\t}\n\treturn 3\n}\n";
+        let parsed = parse_one(src);
+
+        let report = analyze_function_complexity(
+            &parsed,
+            &ComplexityOptions {
+                function_filter: Some("main.Bar".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(report.functions.len(), 1);
+        assert_eq!(report.functions[0].function.name, "Bar");
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn exported_only_restricts_the_report_to_exported_functions_and_methods_on_exported_types() {
+        let src = "package main\n\ntype Public struct{}\ntype private struct{}\n\nfunc Exported() int {\n\treturn 1\n}\n\nfunc unexported() int {\n\treturn 2\n}\n\nfunc (p Public) Method() int {\n\treturn 3\n}\n\nfunc (p private) Method() int {\n\treturn 4\n}\n";
+        let parsed = parse_one(src);
+
+        let report = analyze_function_complexity(
+            &parsed,
+            &ComplexityOptions { exported_only: true, ..Default::default() },
+        );
+
+        let mut names: Vec<&str> = report.functions.iter().map(|fc| fc.function.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Exported", "Method"]);
+        assert_eq!(report.functions.len(), 2);
+    }
+
+    #[test]
+    fn a_function_filter_matching_nothing_is_reported_as_a_warning_not_a_silent_empty_report() {
+        let src = "package main\n\nfunc Foo() int {\n\treturn 1\n}\n";
+        let parsed = parse_one(src);
+
+        let report = analyze_function_complexity(
+            &parsed,
+            &ComplexityOptions {
+                function_filter: Some("main.NoSuchFunction".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert!(report.functions.is_empty());
+        assert_eq!(report.warnings, vec!["function filter `main.NoSuchFunction` matched no functions"]);
+    }
+
+    #[test]
+    fn gocyclo_report_is_sorted_by_descending_complexity_and_matches_its_line_format() {
+        let src = "package main\n\nfunc Simple() int {\n\treturn 1\n}\n\nfunc Complex(x int) int {\n\tif x > 0 {\n\t\tif x > 1 {\n\t\t\treturn 2\n\t\t}\n\t\treturn 1\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        let rendered = render_gocyclo(&report);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let line_re = regex_lite_match;
+        assert!(line_re(lines[0]));
+        assert!(line_re(lines[1]));
+
+        assert!(lines[0].starts_with("3 main Complex a.go:"));
+        assert!(lines[1].starts_with("1 main Simple a.go:"));
+    }
+
+    #[test]
+    fn top_n_returns_exactly_n_functions_sorted_descending_by_complexity() {
+        let src = "package main\n\nfunc A() int {\n\treturn 1\n}\n\nfunc B(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n\nfunc C(x int) int {\n\tif x > 0 {\n\t\tif x > 1 {\n\t\t\treturn 2\n\t\t}\n\t\treturn 1\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        let top_two = sort_and_limit_functions(
+            &report.functions,
+            &ReportPresentationOptions { sort_by: ComplexitySortKey::Complexity, top_n: Some(2) },
+        );
+
+        assert_eq!(top_two.len(), 2);
+        assert_eq!(top_two[0].function.name, "C");
+        assert_eq!(top_two[1].function.name, "B");
+        assert!(top_two[0].cyclomatic_complexity >= top_two[1].cyclomatic_complexity);
+    }
+
+    /// A hand-rolled stand-in for a `gocyclo` line regex
+    /// (`^\d+ \S+ \S+ \S+:\d+:\d+$`) so this test doesn't need to pull in a
+    /// regex crate just to check the shape of one line.
+    fn regex_lite_match(line: &str) -> bool {
+        let Some((head, location)) = line.rsplit_once(' ') else {
+            return false;
+        };
+        let mut parts = head.split(' ');
+        let (Some(complexity), Some(package), Some(function), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return false;
+        };
+        if complexity.parse::<usize>().is_err() || package.is_empty() || function.is_empty() {
+            return false;
+        }
+        let mut location_parts = location.rsplitn(3, ':');
+        let (Some(column), Some(line_no), Some(file)) = (
+            location_parts.next(),
+            location_parts.next(),
+            location_parts.next(),
+        ) else {
+            return false;
+        };
+        column.parse::<usize>().is_ok() && line_no.parse::<usize>().is_ok() && !file.is_empty()
+    }
+
+    #[test]
+    fn nesting_depth_is_one_for_a_flat_function_and_three_for_if_for_if() {
+        let flat_src = "package main\n\nfunc Flat() int {\n\tx := 1\n\treturn x\n}\n";
+        let flat = parse_one(flat_src);
+        let flat_report = analyze_function_complexity(&flat, &ComplexityOptions::default());
+        assert_eq!(flat_report.functions[0].nesting_depth_max, 1);
+
+        let nested_src = "package main\n\nfunc Nested() {\n\tif true {\n\t\tfor {\n\t\t\tif true {\n\t\t\t}\n\t\t}\n\t}\n}\n";
+        let nested = parse_one(nested_src);
+        let nested_report = analyze_function_complexity(&nested, &ComplexityOptions::default());
+        assert_eq!(nested_report.functions[0].nesting_depth_max, 3);
+    }
+
+    #[test]
+    fn a_case_body_does_not_add_its_own_nesting_level_but_its_switch_does() {
+        let src = "package main\n\nfunc F(x int) {\n\tswitch x {\n\tcase 1:\n\t\tif true {\n\t\t}\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        // switch (depth 1) -> case (no extra level) -> if (depth 2).
+        assert_eq!(report.functions[0].nesting_depth_max, 2);
+    }
+
+    #[test]
+    fn a_types_only_file_gets_no_average_instead_of_a_misleading_zero() {
+        let parsed = parse_files(&[
+            ("a.go", "package main\n\nfunc f() int {\n\tif true {\n\t\treturn 1\n\t}\n\treturn 0\n}\n"),
+            ("types.go", "package main\n\ntype Point struct {\n\tX, Y int\n}\n"),
+        ]);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        let by_file = complexity_by_file(&parsed, &report);
+
+        assert_eq!(by_file["types.go"].functions, 0);
+        assert_eq!(by_file["types.go"].average_complexity, None);
+
+        assert_eq!(by_file["a.go"].functions, 1);
+        assert_eq!(by_file["a.go"].average_complexity, Some(2.0));
+    }
+
+    #[test]
+    fn grouped_parameter_names_are_each_counted_and_results_and_statements_too() {
+        let src = "package main\n\nfunc f(a, b int, c string) (int, error) {\n\tx := a + b\n\treturn x, nil\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        let fc = &report.functions[0];
+
+        assert_eq!(fc.parameter_count, 3);
+        assert_eq!(fc.result_count, 2);
+        assert_eq!(fc.statement_count, 2);
+    }
+
+    #[test]
+    fn a_function_with_too_many_params_is_flagged() {
+        let src = "package main\n\nfunc f(a, b, c, d, e, f int) {}\n";
+        let parsed = parse_one(src);
+        let options = ComplexityOptions {
+            max_params: Some(5),
+            ..Default::default()
+        };
+        let report = analyze_function_complexity(&parsed, &options);
+        assert_eq!(report.too_many_params, vec![report.functions[0].function.clone()]);
+        assert!(report.fails_policy(), "too_many_params alone should fail the policy");
+    }
+
+    #[test]
+    fn longest_path_blocks_is_reported_per_function() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        assert!(report.functions[0].longest_path_blocks > 0);
+    }
+
+    #[test]
+    fn a_snippet_parsed_from_a_string_with_no_file_on_disk_still_gets_a_complexity_report() {
+        let src = "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n";
+        let parsed = crate::go_parser::parse_source("stdin.go", src).unwrap();
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        assert_eq!(report.functions.len(), 1);
+        assert_eq!(report.functions[0].function.name, "f");
+        assert_eq!(report.functions[0].cyclomatic_complexity, 2);
+    }
+
+    #[test]
+    fn html_histogram_always_includes_a_legend() {
+        let src = "package main\n\nfunc f() int {\n\treturn 1\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        let rendered = render_html_histogram(&report, &HtmlThemeOptions::default());
+        assert!(rendered.contains("complexity-legend"));
+        for level in LEVELS {
+            assert!(rendered.contains(&level.to_string()));
+        }
+    }
+
+    #[test]
+    fn dark_theme_css_class_appears_when_selected() {
+        let src = "package main\n\nfunc f() int {\n\treturn 1\n}\n";
+        let parsed = parse_one(src);
+        let report = analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        let options = HtmlThemeOptions {
+            theme: HtmlTheme::Dark,
+            ..Default::default()
+        };
+        let rendered = render_html_histogram(&report, &options);
+        assert!(rendered.contains("theme-dark"));
+        assert!(!rendered.contains("theme-light"));
+    }
+
+    #[test]
+    fn a_level_color_override_replaces_the_themes_default() {
+        let options = HtmlThemeOptions {
+            level_colors: BTreeMap::from([(ComplexityLevel::Low, "#00ff00".to_string())]),
+            ..Default::default()
+        };
+        let legend = render_html_legend(&options);
+        assert!(legend.contains("#00ff00"));
+        assert!(!legend.contains(default_level_color(ComplexityLevel::Low, HtmlTheme::Light)));
+    }
+
+    fn complex_function_src(name: &str) -> String {
+        format!(
+            "func {name}(x int) int {{\n\tif x > 10 {{\n\t\treturn 1\n\t}}\n\tif x > 9 {{\n\t\treturn 2\n\t}}\n\tif x > 8 {{\n\t\treturn 3\n\t}}\n\tif x > 7 {{\n\t\treturn 4\n\t}}\n\tif x > 6 {{\n\t\treturn 5\n\t}}\n\tif x > 5 {{\n\t\treturn 6\n\t}}\n\treturn 0\n}}\n"
+        )
+    }
+
+    #[test]
+    fn an_annotated_function_is_excluded_from_violations_but_still_reported() {
+        let src = format!(
+            "package main\n\n//skan:ignore-complexity\n{}",
+            complex_function_src("Annotated")
+        );
+        let parsed = parse_one(&src);
+        let options = ComplexityOptions {
+            max_allowed_complexity: Some(1),
+            fail_on_level: Some(ComplexityLevel::Moderate),
+            ..Default::default()
+        };
+
+        let report = analyze_function_complexity(&parsed, &options);
+        assert!(report.violations.is_empty());
+        assert!(report.level_violations.is_empty());
+        assert!(!report.fails_policy());
+
+        let fc = &report.functions[0];
+        assert!(fc.suppressed);
+        assert!(fc.cyclomatic_complexity > 1, "the real complexity is still reported");
+    }
+
+    #[test]
+    fn an_unannotated_function_of_the_same_shape_still_fails_the_threshold() {
+        let src = format!("package main\n\n{}", complex_function_src("Unannotated"));
+        let parsed = parse_one(&src);
+        let options = ComplexityOptions {
+            max_allowed_complexity: Some(1),
+            ..Default::default()
+        };
+
+        let report = analyze_function_complexity(&parsed, &options);
+        assert_eq!(report.violations, vec![report.functions[0].function.clone()]);
+        assert!(!report.functions[0].suppressed);
+    }
+
+    #[test]
+    fn the_nolint_cyclop_spelling_also_suppresses() {
+        let src = format!(
+            "package main\n\n//nolint:cyclop\n{}",
+            complex_function_src("NolintStyle")
+        );
+        let parsed = parse_one(&src);
+        let options = ComplexityOptions {
+            max_allowed_complexity: Some(1),
+            ..Default::default()
+        };
+
+        let report = analyze_function_complexity(&parsed, &options);
+        assert!(report.violations.is_empty());
+        assert!(report.functions[0].suppressed);
+    }
+
+    #[test]
+    fn a_suppression_comment_not_immediately_above_the_function_does_not_count() {
+        let src = format!(
+            "package main\n\n//skan:ignore-complexity\n\nfunc f() {{}}\n\n{}",
+            complex_function_src("NotAdjacent")
+        );
+        let parsed = parse_one(&src);
+        let options = ComplexityOptions {
+            max_allowed_complexity: Some(1),
+            ..Default::default()
+        };
+
+        let report = analyze_function_complexity(&parsed, &options);
+        let not_adjacent =
+            report.functions.iter().find(|fc| fc.function.name == "NotAdjacent").unwrap();
+        assert!(!not_adjacent.suppressed);
+        assert!(report.violations.contains(&not_adjacent.function));
+    }
+
+    #[test]
+    fn streaming_emits_one_valid_json_line_per_function_matching_the_eager_count() {
+        let dir = tempfile_dir();
+        std::fs::write(dir.join("a.go"), "package main\n\nfunc a() {}\n\nfunc b(x int) int {\n\tif x > 0 {\n\t\treturn x\n\t}\n\treturn 0\n}\n").unwrap();
+        std::fs::write(dir.join("b.go"), "package main\n\nfunc c() {}\n").unwrap();
+
+        let eager = crate::go_parser::parse_dir(&dir).unwrap();
+        let expected = analyze_function_complexity(&eager, &ComplexityOptions::default()).functions.len();
+
+        let mut lines = Vec::new();
+        analyze_function_complexity_streaming(&dir, &ComplexityOptions::default(), |fc| {
+            lines.push(serde_json::to_string(fc).unwrap());
+        })
+        .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(lines.len(), expected);
+        assert_eq!(lines.len(), 3);
+        for line in &lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(value.is_object());
+        }
+    }
+
+    #[test]
+    fn streaming_skips_an_unparseable_file_instead_of_aborting_the_whole_run() {
+        let dir = tempfile_dir();
+        std::fs::write(dir.join("a.go"), "package main\n\nfunc a() {}\n").unwrap();
+        std::fs::write(dir.join("b.go"), "not valid go source {{{").unwrap();
+
+        let mut functions = Vec::new();
+        let result = analyze_function_complexity_streaming(&dir, &ComplexityOptions::default(), |fc| {
+            functions.push(fc.function.name.clone());
+        });
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(result.is_ok());
+        assert_eq!(functions, vec!["a".to_string()]);
+    }
+}
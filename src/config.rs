@@ -0,0 +1,157 @@
+//! Support for a `skan.toml` config file, so a project's usual set of
+//! flags — exclude patterns, complexity thresholds, output formats,
+//! per-analysis options — doesn't have to be respecified as `--param`
+//! flags on every run.
+//!
+//! A config file is just another source of [`UserParams`]: it pre-fills
+//! the same string-keyed map `--param key=value` would build by hand.
+//! [`resolve_params`] loads it (if one applies) and then merges the
+//! CLI's own `--param` flags on top via [`UserParams::extend`], whose
+//! "last one wins" semantics mean an explicit flag always overrides
+//! whatever the file set.
+
+use std::fmt;
+use std::path::Path;
+
+use crate::kernel::UserParams;
+
+/// The config file name looked for at the project root when `--config`
+/// isn't given, alongside `main.rs`'s own `PARSE_CACHE_FILE_NAME`
+/// convention for `--reuse-parse`.
+pub const DEFAULT_CONFIG_FILE_NAME: &str = "skan.toml";
+
+/// The shape of a `skan.toml`: a single `[params]` table of string
+/// values, matching the shape `--param key=value` already produces —
+/// there's no separate "exclude patterns" or "thresholds" schema,
+/// because every one of those is already just a named [`UserParams`]
+/// entry a plugin function declares (`exclude_generated`,
+/// `max_allowed_complexity`, `fail_on_level`, ...).
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    params: std::collections::BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Turns this config into a [`UserParams`] with one entry per
+    /// `[params]` key.
+    pub fn into_params(self) -> UserParams {
+        let mut params = UserParams::new();
+        for (key, value) in self.params {
+            params.set(key, value);
+        }
+        params
+    }
+}
+
+/// Errors from reading or parsing a `skan.toml`.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "couldn't read config file: {err}"),
+            ConfigError::Parse(err) => write!(f, "couldn't parse config file: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Reads and parses the `skan.toml` at `path`.
+pub fn load_config(path: &Path) -> Result<Config, ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+    toml::from_str(&text).map_err(ConfigError::Parse)
+}
+
+/// Builds the [`UserParams`] for a run: `skan.toml` values (if any apply)
+/// with `raw_params` (`--param key=value` strings) merged on top.
+///
+/// The config file to load, if any, is chosen like this: an explicit
+/// `config_path` (`--config`) is always used, and it's an error if it
+/// can't be read; otherwise, if `project_root` is given and it has a
+/// `skan.toml`, that's used; otherwise no config file applies and
+/// `raw_params` are the only source.
+pub fn resolve_params(
+    config_path: Option<&Path>,
+    project_root: Option<&Path>,
+    raw_params: &[String],
+) -> Result<UserParams, String> {
+    let mut params = match config_path {
+        Some(path) => load_config(path).map_err(|err| err.to_string())?.into_params(),
+        None => match project_root.map(|root| root.join(DEFAULT_CONFIG_FILE_NAME)) {
+            Some(default_path) if default_path.is_file() => {
+                load_config(&default_path).map_err(|err| err.to_string())?.into_params()
+            }
+            _ => UserParams::new(),
+        },
+    };
+
+    let extra = crate::kernel::parse_key_value_params(raw_params)?;
+    params.extend(extra);
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tempdir() -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("skanujkod-config-test-{}-{n}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn a_cli_param_overrides_the_same_key_set_in_the_config_file() {
+        let dir = tempdir();
+        std::fs::write(
+            dir.join(DEFAULT_CONFIG_FILE_NAME),
+            "[params]\nmax_allowed_complexity = \"5\"\n",
+        )
+        .unwrap();
+
+        let params = resolve_params(None, Some(&dir), &["max_allowed_complexity=10".to_string()]).unwrap();
+        assert_eq!(params.get::<String>("max_allowed_complexity").map(String::as_str), Some("10"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn a_config_only_value_is_kept_when_no_cli_flag_overrides_it() {
+        let dir = tempdir();
+        std::fs::write(dir.join(DEFAULT_CONFIG_FILE_NAME), "[params]\nfail_on_level = \"high\"\n").unwrap();
+
+        let params = resolve_params(None, Some(&dir), &[]).unwrap();
+        assert_eq!(params.get::<String>("fail_on_level").map(String::as_str), Some("high"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn no_config_file_at_the_project_root_is_not_an_error() {
+        let dir = tempdir();
+
+        let params = resolve_params(None, Some(&dir), &["package=main".to_string()]).unwrap();
+        assert_eq!(params.get::<String>("package").map(String::as_str), Some("main"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn an_explicit_config_path_that_does_not_exist_is_an_error() {
+        let dir = tempdir();
+        let missing = dir.join("nope.toml");
+
+        assert!(resolve_params(Some(&missing), None, &[]).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
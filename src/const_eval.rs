@@ -0,0 +1,135 @@
+//! Constant-folding a small subset of Go expressions — integer literals,
+//! boolean literals, and the arithmetic/comparison/logical operators over
+//! them — so a lint can tell a genuinely variable branch condition
+//! (`if x > 0`) from one that's provably always true or always false
+//! (`if 1 == 1`, `if false`) without running the program.
+//!
+//! This deliberately doesn't attempt full Go constant evaluation (no
+//! `const` identifiers, floats, strings, or overflow-checked arithmetic
+//! per the spec) — just enough of it that a condition someone wrote as a
+//! literal or a trivial literal expression gets recognized as dead code.
+
+use goscript_parser::ast::Expr;
+use goscript_parser::objects::Objects as AstObjects;
+use goscript_parser::token::Token;
+
+/// The result of folding a constant expression: either a boolean (from a
+/// `true`/`false` literal or a comparison/logical operator) or an integer
+/// (from an integer literal or arithmetic on one), so a comparison like
+/// `2 > 1` can fold its integer operands down to the boolean the whole
+/// expression evaluates to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstValue {
+    Bool(bool),
+    Int(i64),
+}
+
+/// Folds `expr` down to a [`ConstValue`] if it's built entirely out of
+/// integer/boolean literals and the operators this module understands,
+/// or `None` if it references anything else (a variable, a function
+/// call, a float or string literal, ...) — in which case its value isn't
+/// knowable without running the program.
+pub fn eval_const(expr: &Expr, objects: &AstObjects) -> Option<ConstValue> {
+    match expr {
+        Expr::Ident(key) => match objects.idents[*key].name.as_str() {
+            "true" => Some(ConstValue::Bool(true)),
+            "false" => Some(ConstValue::Bool(false)),
+            _ => None,
+        },
+        Expr::BasicLit(lit) => match &lit.token {
+            Token::INT(data) => data.as_str().parse::<i64>().ok().map(ConstValue::Int),
+            _ => None,
+        },
+        Expr::Paren(paren) => eval_const(&paren.expr, objects),
+        Expr::Unary(unary) => {
+            let operand = eval_const(&unary.expr, objects)?;
+            match (&unary.op, operand) {
+                (Token::NOT, ConstValue::Bool(b)) => Some(ConstValue::Bool(!b)),
+                (Token::SUB, ConstValue::Int(n)) => Some(ConstValue::Int(-n)),
+                _ => None,
+            }
+        }
+        Expr::Binary(binary) => {
+            let a = eval_const(&binary.expr_a, objects)?;
+            let b = eval_const(&binary.expr_b, objects)?;
+            eval_binary(&binary.op, a, b)
+        }
+        _ => None,
+    }
+}
+
+fn eval_binary(op: &Token, a: ConstValue, b: ConstValue) -> Option<ConstValue> {
+    use ConstValue::{Bool, Int};
+    match (op, a, b) {
+        (Token::LAND, Bool(a), Bool(b)) => Some(Bool(a && b)),
+        (Token::LOR, Bool(a), Bool(b)) => Some(Bool(a || b)),
+        (Token::EQL, a, b) => Some(Bool(a == b)),
+        (Token::NEQ, a, b) => Some(Bool(a != b)),
+        (Token::LSS, Int(a), Int(b)) => Some(Bool(a < b)),
+        (Token::LEQ, Int(a), Int(b)) => Some(Bool(a <= b)),
+        (Token::GTR, Int(a), Int(b)) => Some(Bool(a > b)),
+        (Token::GEQ, Int(a), Int(b)) => Some(Bool(a >= b)),
+        (Token::ADD, Int(a), Int(b)) => a.checked_add(b).map(Int),
+        (Token::SUB, Int(a), Int(b)) => a.checked_sub(b).map(Int),
+        (Token::MUL, Int(a), Int(b)) => a.checked_mul(b).map(Int),
+        (Token::QUO, Int(a), Int(b)) if b != 0 => a.checked_div(b).map(Int),
+        (Token::REM, Int(a), Int(b)) if b != 0 => a.checked_rem(b).map(Int),
+        _ => None,
+    }
+}
+
+/// [`eval_const`], but only for conditions — expects (and requires) the
+/// result to be a boolean, since a branch condition that folded to an
+/// integer would be a type error the parser's own checker should have
+/// already caught, not something a lint should report on.
+pub fn eval_const_bool(expr: &Expr, objects: &AstObjects) -> Option<bool> {
+    match eval_const(expr, objects)? {
+        ConstValue::Bool(b) => Some(b),
+        ConstValue::Int(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goscript_parser::ast::{Decl, Stmt};
+
+    fn if_cond_folds_to(src: &str, expected: Option<bool>) {
+        let full = format!("package main\nfunc f() {{\n\t{src}\n}}\n");
+        let parsed = crate::go_parser::parse_source("a.go", &full).unwrap();
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+        let Decl::Func(key) = &pf.ast.decls[0] else { unreachable!() };
+        let fdecl = &parsed.objects.fdecls[*key];
+        let body = fdecl.body.as_ref().unwrap();
+        let if_stmt = body
+            .list
+            .iter()
+            .find_map(|stmt| match stmt {
+                Stmt::If(if_stmt) => Some(if_stmt),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(eval_const_bool(&if_stmt.cond, &parsed.objects), expected);
+    }
+
+    #[test]
+    fn a_literal_true_condition_folds_to_true() {
+        if_cond_folds_to("if true {\n\t}", Some(true));
+    }
+
+    #[test]
+    fn a_literal_false_condition_folds_to_false() {
+        if_cond_folds_to("if false {\n\t}", Some(false));
+    }
+
+    #[test]
+    fn a_constant_comparison_folds_through_its_integer_operands() {
+        if_cond_folds_to("if 2 > 1 {\n\t}", Some(true));
+    }
+
+    #[test]
+    fn a_condition_over_a_variable_does_not_fold() {
+        if_cond_folds_to("x := 1\n\tif x > 0 {\n\t}", None);
+    }
+}
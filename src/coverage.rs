@@ -0,0 +1,859 @@
+//! Branch coverage: maps a coverage profile (the format `go test
+//! -coverprofile` writes) onto `if` statements' CFG edges, so a report
+//! can say not just "this function ran" but "the else side of this `if`
+//! never did".
+//!
+//! Relies on `cfg_plugin::Builder::build_if`'s edge order: a condition
+//! block's first successor is always the `then` branch, and its second
+//! is the `else` branch when there's an explicit `else` (without one, it's
+//! the block reached after the `if`).
+
+pub mod iface;
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use goscript_parser::ast::{self, Node, Stmt};
+
+use crate::cfg_plugin::{self, ControlFlowGraph};
+use crate::go_parser::{AstObjects, ParseDirResult, Pos, line_of};
+use crate::model::{self, FunctionId};
+
+/// A single `count` entry parsed out of a coverage profile: `file` plus
+/// the 1-based line range it covers, and how many times it ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CoverageBlock {
+    file: String,
+    start_line: usize,
+    end_line: usize,
+    count: u64,
+}
+
+/// A parsed coverage profile, queryable by file and line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageProfile {
+    blocks: Vec<CoverageBlock>,
+    /// One entry per line that didn't parse as a coverage block, so a
+    /// caller can tell a profile that's mostly garbage apart from one
+    /// that's genuinely all-zero — both would otherwise render the same
+    /// report with no indication anything was actually skipped.
+    warnings: Vec<String>,
+}
+
+impl CoverageProfile {
+    /// Parses the `go test -coverprofile` text format: an optional
+    /// `mode: ...` header line, then one line per block of
+    /// `file:startLine.startCol,endLine.endCol numStmt count`. A line
+    /// that doesn't match is skipped rather than failing the whole
+    /// parse — a profile is diagnostic input, not something this tool
+    /// controls the format of — but it's recorded in
+    /// [`CoverageProfile::warnings`] rather than silently dropped.
+    pub fn parse(text: &str) -> CoverageProfile {
+        let mut blocks = Vec::new();
+        let mut warnings = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.starts_with("mode:") || line.trim().is_empty() {
+                continue;
+            }
+            match parse_coverage_line(line) {
+                Some(block) => blocks.push(block),
+                None => warnings.push(format!("line {}: not a coverage block: `{line}`", i + 1)),
+            }
+        }
+        CoverageProfile { blocks, warnings }
+    }
+
+    /// Whether any profiled block covering `line` ran at least once.
+    /// `file_name` is matched by suffix, since a profile's
+    /// module-qualified path (`example.com/proj/pkg/file.go`) still
+    /// needs to match a project-relative name (`pkg/file.go`).
+    fn line_is_covered(&self, file_name: &str, line: usize) -> bool {
+        self.blocks.iter().any(|b| {
+            b.file.ends_with(file_name) && line >= b.start_line && line <= b.end_line && b.count > 0
+        })
+    }
+}
+
+fn parse_coverage_line(line: &str) -> Option<CoverageBlock> {
+    let (file, rest) = line.split_once(':')?;
+    let mut fields = rest.split_whitespace();
+    let range = fields.next()?;
+    let _num_stmt = fields.next()?;
+    let count = fields.next()?.parse().ok()?;
+
+    let (start, end) = range.split_once(',')?;
+    let (start_line, _) = start.split_once('.')?;
+    let (end_line, _) = end.split_once('.')?;
+
+    Some(CoverageBlock {
+        file: file.to_string(),
+        start_line: start_line.parse().ok()?,
+        end_line: end_line.parse().ok()?,
+        count,
+    })
+}
+
+/// Whether an `if`'s branch (true or false side) ran, going by the line
+/// of the first statement in the block it leads to. A branch with no
+/// statements of its own (an empty body) has nothing to check a line
+/// on, so it's reported as taken — there's no statement coverage could
+/// have missed.
+fn branch_is_taken(cfg: &ControlFlowGraph, block_id: usize, objects: &AstObjects, source: &str, base: Pos, file_name: &str, profile: &CoverageProfile) -> bool {
+    match cfg.blocks[block_id].statements.first() {
+        Some(stmt) => {
+            let line = line_of(source, base, stmt.stmt.pos(objects));
+            profile.line_is_covered(file_name, line)
+        }
+        None => true,
+    }
+}
+
+fn stmt_pos(stmt: &Stmt) -> Pos {
+    match stmt {
+        Stmt::If(i) => i.if_pos,
+        Stmt::For(f) => f.for_pos,
+        Stmt::Return(r) => r.ret,
+        Stmt::Switch(sw) => sw.switch,
+        Stmt::TypeSwitch(sw) => sw.switch,
+        _ => 0,
+    }
+}
+
+/// Whether an `if`'s true and false sides each ran at least once,
+/// according to a coverage profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchCoverage {
+    pub function: FunctionId,
+    pub line: usize,
+    pub true_taken: bool,
+    pub false_taken: bool,
+}
+
+impl BranchCoverage {
+    /// Neither side ran.
+    pub fn is_uncovered(&self) -> bool {
+        !self.true_taken && !self.false_taken
+    }
+
+    /// Exactly one side ran — the condition was evaluated, but only one
+    /// outcome was ever observed. This is what a developer acting on a
+    /// coverage report actually wants flagged: a test exists, but it
+    /// never exercises the other side.
+    pub fn is_partial(&self) -> bool {
+        self.true_taken != self.false_taken
+    }
+}
+
+/// Whether a single `case` arm (or `default`) of a `switch`/`type switch`
+/// ran, going by the same first-statement heuristic [`branch_is_taken`]
+/// uses for an `if`'s branches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitchCaseCoverage {
+    pub function: FunctionId,
+    /// Line of the `switch`/`type switch` keyword this case belongs to,
+    /// so cases from the same statement can be grouped in a report.
+    pub switch_line: usize,
+    /// Line of the `case`/`default` keyword itself.
+    pub case_line: usize,
+    /// Whether this is the `default` case rather than an explicit
+    /// `case <expr>`.
+    pub is_default: bool,
+    pub taken: bool,
+}
+
+/// Every `if`/`else` branch's coverage across a project, split out by
+/// whether it's fully uncovered or only partially so, plus every `switch`
+/// case's coverage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectBranchCoverage {
+    pub branches: Vec<BranchCoverage>,
+    pub uncovered_branches: Vec<BranchCoverage>,
+    pub partially_covered_branches: Vec<BranchCoverage>,
+    pub switch_cases: Vec<SwitchCaseCoverage>,
+    /// The subset of `switch_cases` that never ran, `default` included —
+    /// the switch equivalent of `uncovered_branches`.
+    pub uncovered_switch_cases: Vec<SwitchCaseCoverage>,
+    /// Copied from the profile's own [`CoverageProfile::parse`] warnings,
+    /// so a report generated from a badly-formed profile says so instead
+    /// of just looking like everything's uncovered.
+    pub warnings: Vec<String>,
+}
+
+/// Reports per-branch coverage for every `if` with an explicit `else`
+/// in `parsed`, according to `profile`. `if`s with no `else` aren't
+/// reported: the block reached after skipping the body is shared with
+/// whatever follows the `if`, so a profile can't tell "the condition
+/// was false" apart from "the rest of the function just kept running"
+/// at that granularity. When `function_filter` is set, restricts the
+/// report to the single function it names (see
+/// [`model::matches_function_filter`]).
+pub fn branch_coverage(
+    parsed: &ParseDirResult,
+    profile: &CoverageProfile,
+    function_filter: Option<&str>,
+) -> ProjectBranchCoverage {
+    let mut branches = Vec::new();
+    let mut switch_cases = Vec::new();
+    let mut matched_any_function = false;
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function = FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+                if function_filter.is_some_and(|filter| !model::matches_function_filter(&function, filter)) {
+                    continue;
+                }
+                matched_any_function = true;
+
+                let cfg = cfg_plugin::build_cfg(body, &parsed.objects);
+
+                for block in &cfg.blocks {
+                    let Some(last) = block.statements.last() else { continue };
+                    let Stmt::If(if_stmt) = &last.stmt else { continue };
+                    if if_stmt.els.is_none() {
+                        continue;
+                    }
+                    let (Some(&then_start), Some(&else_start)) =
+                        (block.successors.first(), block.successors.get(1))
+                    else {
+                        continue;
+                    };
+
+                    branches.push(BranchCoverage {
+                        function: function.clone(),
+                        line: line_of(&pf.source, pf.base, if_stmt.if_pos),
+                        true_taken: branch_is_taken(&cfg, then_start, &parsed.objects, &pf.source, pf.base, file_name, profile),
+                        false_taken: branch_is_taken(&cfg, else_start, &parsed.objects, &pf.source, pf.base, file_name, profile),
+                    });
+                }
+
+                // A switch's `cur` block links to one successor per case
+                // clause, in source order (`cfg_plugin::Builder::build_switch`),
+                // plus one more for `after` when there's no `default` — so
+                // zipping the block's cases with its successors pairs each
+                // case with the block execution enters through when taken,
+                // and stops before that trailing `after` edge on its own.
+                for block in &cfg.blocks {
+                    let Some(last) = block.statements.last() else { continue };
+                    let clauses: &[Stmt] = match &last.stmt {
+                        Stmt::Switch(sw) => &sw.body.list,
+                        Stmt::TypeSwitch(sw) => &sw.body.list,
+                        _ => continue,
+                    };
+                    let switch_line = line_of(&pf.source, pf.base, stmt_pos(&last.stmt));
+                    let cases = clauses.iter().filter_map(|c| match c {
+                        Stmt::Case(case) => Some(case),
+                        _ => None,
+                    });
+                    for (case, &case_block) in cases.zip(&block.successors) {
+                        switch_cases.push(SwitchCaseCoverage {
+                            function: function.clone(),
+                            switch_line,
+                            case_line: line_of(&pf.source, pf.base, case.case),
+                            is_default: case.list.is_none(),
+                            taken: branch_is_taken(&cfg, case_block, &parsed.objects, &pf.source, pf.base, file_name, profile),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let uncovered_branches = branches.iter().filter(|b| b.is_uncovered()).cloned().collect();
+    let partially_covered_branches = branches.iter().filter(|b| b.is_partial()).cloned().collect();
+    let uncovered_switch_cases = switch_cases.iter().filter(|c| !c.taken).cloned().collect();
+
+    let mut warnings = profile.warnings.clone();
+    if let Some(filter) = function_filter
+        && !matched_any_function
+    {
+        warnings.push(format!("function filter `{filter}` matched no functions"));
+    }
+
+    ProjectBranchCoverage {
+        branches,
+        uncovered_branches,
+        partially_covered_branches,
+        switch_cases,
+        uncovered_switch_cases,
+        warnings,
+    }
+}
+
+/// One function's line-level coverage: every statement's line, split by
+/// whether a coverage profile saw it run at least once. Unlike
+/// [`BranchCoverage`], which only looks at `if`/`else` edges, this covers
+/// every statement in the function — the "exactly which lines ran" view
+/// a developer reaching for a coverage report actually wants.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FunctionStatementCoverage {
+    pub function: FunctionId,
+    /// 1-based line numbers, in ascending order, of every statement that
+    /// ran at least once.
+    pub covered_lines: Vec<usize>,
+    /// 1-based line numbers, in ascending order, of every statement that
+    /// never ran.
+    pub uncovered_lines: Vec<usize>,
+}
+
+impl FunctionStatementCoverage {
+    /// Fraction of this function's statements that ran, from `0.0`
+    /// (nothing covered) to `1.0` (everything covered) — `1.0` for a
+    /// function with no statements at all, since there's nothing left
+    /// uncovered to report.
+    pub fn coverage_fraction(&self) -> f64 {
+        let total = self.covered_lines.len() + self.uncovered_lines.len();
+        if total == 0 {
+            1.0
+        } else {
+            self.covered_lines.len() as f64 / total as f64
+        }
+    }
+}
+
+/// Every function's [`FunctionStatementCoverage`] across a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ProjectStatementCoverage {
+    pub functions: Vec<FunctionStatementCoverage>,
+    /// Copied from the profile's own [`CoverageProfile::parse`] warnings —
+    /// see [`ProjectBranchCoverage::warnings`].
+    pub warnings: Vec<String>,
+}
+
+/// How [`sort_and_limit_statement_coverage`] orders a
+/// [`ProjectStatementCoverage`]'s function list before it's rendered or
+/// truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverageSortKey {
+    /// Lowest coverage fraction first — the default, since a coverage
+    /// report over a large project is read to find what needs more
+    /// tests, not to browse alphabetically.
+    #[default]
+    LeastCovered,
+    Name,
+}
+
+/// Controls how a large [`ProjectStatementCoverage`] gets presented
+/// instead of dumped as one unsorted, unbounded table — mirrors
+/// [`crate::complexity::ReportPresentationOptions`] for the coverage
+/// side of a report.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoveragePresentationOptions {
+    pub sort_by: CoverageSortKey,
+    /// When set, keeps only the first `top_n` entries after sorting —
+    /// "the 20 least-covered functions" rather than every function in
+    /// the project.
+    pub top_n: Option<usize>,
+}
+
+/// Sorts (and, if `options.top_n` is set, truncates) `functions` per
+/// `options`. Ties broken by function name so the result is stable
+/// regardless of the map iteration order [`statement_coverage`] built
+/// `functions` from.
+pub fn sort_and_limit_statement_coverage(
+    functions: &[FunctionStatementCoverage],
+    options: &CoveragePresentationOptions,
+) -> Vec<FunctionStatementCoverage> {
+    let mut sorted = functions.to_vec();
+    match options.sort_by {
+        CoverageSortKey::LeastCovered => sorted.sort_by(|a, b| {
+            a.coverage_fraction()
+                .partial_cmp(&b.coverage_fraction())
+                .unwrap()
+                .then_with(|| a.function.name.cmp(&b.function.name))
+        }),
+        CoverageSortKey::Name => sorted.sort_by(|a, b| a.function.name.cmp(&b.function.name)),
+    }
+    if let Some(top_n) = options.top_n {
+        sorted.truncate(top_n);
+    }
+    sorted
+}
+
+/// Reports, for every function in `parsed`, which of its statements'
+/// lines `profile` saw run at least once. Walks every CFG block rather
+/// than just the `if`/`else` edges [`branch_coverage`] looks at, so a
+/// statement with no branch of its own (a plain assignment, a bare call)
+/// still shows up as covered or not. When `function_filter` is set,
+/// restricts the report to the single function it names (see
+/// [`model::matches_function_filter`]).
+pub fn statement_coverage(
+    parsed: &ParseDirResult,
+    profile: &CoverageProfile,
+    function_filter: Option<&str>,
+) -> ProjectStatementCoverage {
+    let mut functions = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function = FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+                if function_filter.is_some_and(|filter| !model::matches_function_filter(&function, filter)) {
+                    continue;
+                }
+
+                let cfg = cfg_plugin::build_cfg(body, &parsed.objects);
+
+                let mut covered_lines = Vec::new();
+                let mut uncovered_lines = Vec::new();
+                for block in &cfg.blocks {
+                    for stmt in &block.statements {
+                        let line = line_of(&pf.source, pf.base, stmt.stmt.pos(&parsed.objects));
+                        if profile.line_is_covered(file_name, line) {
+                            covered_lines.push(line);
+                        } else {
+                            uncovered_lines.push(line);
+                        }
+                    }
+                }
+                covered_lines.sort_unstable();
+                covered_lines.dedup();
+                uncovered_lines.sort_unstable();
+                uncovered_lines.dedup();
+
+                functions.push(FunctionStatementCoverage { function, covered_lines, uncovered_lines });
+            }
+        }
+    }
+
+    let mut warnings = profile.warnings.clone();
+    if let Some(filter) = function_filter
+        && functions.is_empty()
+    {
+        warnings.push(format!("function filter `{filter}` matched no functions"));
+    }
+
+    ProjectStatementCoverage { functions, warnings }
+}
+
+/// Renders `source` with every line prefixed by what `coverage` says
+/// about it: `+` for a covered statement's line, `-` for an uncovered
+/// one, and a blank prefix for a line with no statement of its own (a
+/// blank line, a comment, a brace). This is the annotated-source view
+/// [`statement_coverage`]'s line lists exist to support.
+pub fn render_annotated_source(source: &str, coverage: &FunctionStatementCoverage) -> String {
+    let mut out = String::new();
+    for (i, line) in source.lines().enumerate() {
+        let lineno = i + 1;
+        let prefix = if coverage.uncovered_lines.contains(&lineno) {
+            '-'
+        } else if coverage.covered_lines.contains(&lineno) {
+            '+'
+        } else {
+            ' '
+        };
+        out.push(prefix);
+        out.push(' ');
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Escapes `text` for use inside HTML character data (`<`, `>`, `&`) —
+/// Go source is otherwise as HTML-unsafe as any other text, and this is
+/// the only place [`render_annotated_source_html`] emits raw source into
+/// markup.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Every covered and uncovered line across every function
+/// [`FunctionStatementCoverage`] in `functions` reports for `file_name` —
+/// merged because a source file's HTML page annotates lines, not
+/// functions, and a file has more than one function to draw lines from.
+fn covered_and_uncovered_lines_for_file(
+    functions: &[FunctionStatementCoverage],
+    file_name: &str,
+) -> (BTreeSet<usize>, BTreeSet<usize>) {
+    let mut covered = BTreeSet::new();
+    let mut uncovered = BTreeSet::new();
+    for f in functions.iter().filter(|f| f.function.file == file_name) {
+        covered.extend(&f.covered_lines);
+        uncovered.extend(&f.uncovered_lines);
+    }
+    (covered, uncovered)
+}
+
+/// Renders `source` (`file_name`'s own text) as a standalone HTML page,
+/// one `<pre>`-wrapped `<span>` per line, with `covered`/`uncovered` CSS
+/// classes so a stylesheet (or, here, the inline `<style>` block) can
+/// color them the way `go tool cover -html` does — green for a line that
+/// ran, red for one that never did, unstyled for a line with no
+/// statement of its own. Lines from `functions` not belonging to
+/// `file_name` are ignored, so a caller can pass a whole project's
+/// [`ProjectStatementCoverage::functions`] without filtering it first.
+pub fn render_annotated_source_html(
+    source: &str,
+    file_name: &str,
+    functions: &[FunctionStatementCoverage],
+) -> String {
+    let (covered, uncovered) = covered_and_uncovered_lines_for_file(functions, file_name);
+
+    let mut body = String::new();
+    for (i, line) in source.lines().enumerate() {
+        let lineno = i + 1;
+        let class = if uncovered.contains(&lineno) {
+            " class=\"uncovered\""
+        } else if covered.contains(&lineno) {
+            " class=\"covered\""
+        } else {
+            ""
+        };
+        body.push_str(&format!(
+            "<span{class}>{:>5} {}</span>\n",
+            lineno,
+            escape_html(line)
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: monospace; }}\n\
+         pre {{ white-space: pre; }}\n\
+         .covered {{ background-color: #e6ffed; }}\n\
+         .uncovered {{ background-color: #ffeef0; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<pre>\n{body}</pre>\n</body>\n</html>\n",
+        title = escape_html(file_name),
+    )
+}
+
+/// One file's coverage page, as listed in [`write_html_coverage_report`]'s
+/// index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageHtmlPage {
+    pub package: String,
+    pub file: String,
+    pub path: PathBuf,
+}
+
+/// The name [`write_html_coverage_report`] gives a file's page —
+/// namespaced by package as well as file name, since two packages can
+/// each have their own same-named file (`helpers.go` in both `foo` and
+/// `bar`), which would otherwise collide under one output directory.
+fn html_page_file_name(package: &str, file: &str) -> String {
+    let sanitize = |s: &str| s.replace(['/', '\\'], "_");
+    format!("{}__{}.html", sanitize(package), sanitize(file))
+}
+
+/// Renders one annotated HTML page per source file covered by
+/// `coverage` (via [`render_annotated_source_html`]), plus the
+/// `index.html` linking to all of them — the pure computation
+/// [`write_html_coverage_report`] writes to disk, split out so the
+/// rendered content can be asserted on directly without a temp
+/// directory. `parsed` supplies each file's original source text — the
+/// same project `statement_coverage` computed `coverage` from.
+pub fn render_html_coverage_pages(
+    parsed: &ParseDirResult,
+    coverage: &ProjectStatementCoverage,
+) -> (Vec<(CoverageHtmlPage, String)>, String) {
+    let mut pages = Vec::new();
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            let html = render_annotated_source_html(&pf.source, file_name, &coverage.functions);
+            let page_file_name = html_page_file_name(&pkg.name, file_name);
+            let page = CoverageHtmlPage {
+                package: pkg.name.clone(),
+                file: file_name.clone(),
+                path: PathBuf::from(page_file_name),
+            };
+            pages.push((page, html));
+        }
+    }
+    pages.sort_by(|a, b| (&a.0.package, &a.0.file).cmp(&(&b.0.package, &b.0.file)));
+
+    let mut index = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Coverage</title>\n</head>\n<body>\n<h1>Coverage</h1>\n<ul>\n",
+    );
+    for (page, _) in &pages {
+        index.push_str(&format!(
+            "<li><a href=\"{href}\">{pkg}/{file}</a></li>\n",
+            href = page.path.display(),
+            pkg = escape_html(&page.package),
+            file = escape_html(&page.file),
+        ));
+    }
+    index.push_str("</ul>\n</body>\n</html>\n");
+
+    (pages, index)
+}
+
+/// Writes one annotated HTML page per source file covered by `coverage`
+/// into `output_dir`, plus an `index.html` linking to all of them (see
+/// [`render_html_coverage_pages`]), and returns the list of pages
+/// written.
+pub fn write_html_coverage_report(
+    parsed: &ParseDirResult,
+    coverage: &ProjectStatementCoverage,
+    output_dir: &Path,
+) -> std::io::Result<Vec<CoverageHtmlPage>> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let (rendered_pages, index) = render_html_coverage_pages(parsed, coverage);
+
+    let mut pages = Vec::with_capacity(rendered_pages.len());
+    for (page, html) in rendered_pages {
+        std::fs::write(output_dir.join(&page.path), html)?;
+        pages.push(page);
+    }
+    std::fs::write(output_dir.join("index.html"), index)?;
+
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    fn parse_one(src: &str) -> ParseDirResult {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-coverage-test-{}-{n}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::File::create(dir.join("a.go"))
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    #[test]
+    fn an_else_side_never_exercised_is_reported_as_partial() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+
+        // Line 4 (`return 1`, the `then` side) ran; line 6 (`return 2`,
+        // the `else` side) never did.
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 1\na.go:6.1,6.10 1 0\n");
+
+        let report = branch_coverage(&parsed, &profile, None);
+        assert_eq!(report.branches.len(), 1);
+        let branch = &report.branches[0];
+        assert!(branch.true_taken);
+        assert!(!branch.false_taken);
+        assert!(branch.is_partial());
+        assert!(!branch.is_uncovered());
+
+        assert_eq!(report.partially_covered_branches.len(), 1);
+        assert!(report.uncovered_branches.is_empty());
+    }
+
+    #[test]
+    fn a_branch_exercised_on_both_sides_is_not_reported_as_partial() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 1\na.go:6.1,6.10 1 1\n");
+
+        let report = branch_coverage(&parsed, &profile, None);
+        assert!(report.partially_covered_branches.is_empty());
+        assert!(report.uncovered_branches.is_empty());
+    }
+
+    #[test]
+    fn a_functions_uncovered_lines_match_the_statements_behind_an_unexecuted_branch() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:3.1,4.10 1 1\na.go:6.1,6.10 1 0\n");
+
+        let branches = branch_coverage(&parsed, &profile, None);
+        assert_eq!(branches.partially_covered_branches.len(), 1);
+        assert!(branches.branches[0].true_taken);
+        assert!(!branches.branches[0].false_taken);
+
+        let statements = statement_coverage(&parsed, &profile, None);
+        assert_eq!(statements.functions.len(), 1);
+        let f = &statements.functions[0];
+        assert_eq!(f.covered_lines, vec![3, 4]);
+        assert_eq!(f.uncovered_lines, vec![6]);
+
+        let rendered = render_annotated_source(src, f);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[3], "+ \t\treturn 1");
+        assert_eq!(lines[5], "- \t\treturn 2");
+    }
+
+    #[test]
+    fn a_malformed_profile_line_surfaces_as_a_warning_instead_of_being_silently_dropped() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 1\nnot a coverage line at all\n");
+
+        assert_eq!(profile.warnings, vec!["line 3: not a coverage block: `not a coverage line at all`"]);
+
+        let branches = branch_coverage(&parsed, &profile, None);
+        assert_eq!(branches.warnings, profile.warnings);
+
+        let statements = statement_coverage(&parsed, &profile, None);
+        assert_eq!(statements.warnings, profile.warnings);
+    }
+
+    #[test]
+    fn a_switch_with_only_one_case_run_reports_the_other_two_uncovered() {
+        let src = "package main\nfunc f(x int) int {\n\tswitch x {\n\tcase 1:\n\t\treturn 1\n\tcase 2:\n\t\treturn 2\n\tdefault:\n\t\treturn 0\n\t}\n\treturn -1\n}\n";
+        let parsed = parse_one(src);
+
+        // Only the `case 1` branch (line 5) ran.
+        let profile = CoverageProfile::parse("mode: set\na.go:5.1,5.10 1 1\na.go:7.1,7.10 1 0\na.go:9.1,9.10 1 0\n");
+
+        let report = branch_coverage(&parsed, &profile, None);
+        assert_eq!(report.switch_cases.len(), 3);
+        assert_eq!(report.uncovered_switch_cases.len(), 2);
+
+        let by_line: std::collections::BTreeMap<usize, &SwitchCaseCoverage> =
+            report.switch_cases.iter().map(|c| (c.case_line, c)).collect();
+        assert!(by_line[&4].taken);
+        assert!(!by_line[&6].taken);
+        assert!(!by_line[&8].taken);
+        assert!(by_line[&8].is_default);
+    }
+
+    #[test]
+    fn a_function_filter_restricts_branch_coverage_to_the_named_function() {
+        let src = "package main\n\nfunc Foo(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n\nfunc Bar(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::default();
+
+        let report = branch_coverage(&parsed, &profile, Some("main.Bar"));
+
+        assert_eq!(report.branches.len(), 1);
+        assert_eq!(report.branches[0].function.name, "Bar");
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn a_branch_coverage_filter_matching_nothing_is_reported_as_a_warning() {
+        let src = "package main\nfunc Foo(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::default();
+
+        let report = branch_coverage(&parsed, &profile, Some("main.NoSuchFunction"));
+
+        assert!(report.branches.is_empty());
+        assert_eq!(report.warnings, vec!["function filter `main.NoSuchFunction` matched no functions"]);
+    }
+
+    #[test]
+    fn a_function_filter_restricts_statement_coverage_to_the_named_function() {
+        let src = "package main\n\nfunc Foo() int {\n\treturn 1\n}\n\nfunc Bar() int {\n\treturn 2\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::default();
+
+        let report = statement_coverage(&parsed, &profile, Some("main.Bar"));
+
+        assert_eq!(report.functions.len(), 1);
+        assert_eq!(report.functions[0].function.name, "Bar");
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn a_statement_coverage_filter_matching_nothing_is_reported_as_a_warning() {
+        let src = "package main\nfunc Foo() int {\n\treturn 1\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::default();
+
+        let report = statement_coverage(&parsed, &profile, Some("main.NoSuchFunction"));
+
+        assert!(report.functions.is_empty());
+        assert_eq!(report.warnings, vec!["function filter `main.NoSuchFunction` matched no functions"]);
+    }
+
+    #[test]
+    fn an_if_with_no_else_is_not_reported() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 0\n");
+
+        let report = branch_coverage(&parsed, &profile, None);
+        assert!(report.branches.is_empty());
+    }
+
+    #[test]
+    fn top_n_returns_exactly_n_functions_sorted_ascending_by_coverage_fraction() {
+        let src = "package main\n\nfunc FullyCovered() int {\n\treturn 1\n}\n\nfunc PartlyCovered() int {\n\treturn 2\n}\n\nfunc NeverCovered() int {\n\treturn 3\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 1\na.go:8.1,8.10 1 0\n");
+        let statements = statement_coverage(&parsed, &profile, None);
+        assert_eq!(statements.functions.len(), 3);
+
+        let worst_two = sort_and_limit_statement_coverage(
+            &statements.functions,
+            &CoveragePresentationOptions { sort_by: CoverageSortKey::LeastCovered, top_n: Some(2) },
+        );
+
+        assert_eq!(worst_two.len(), 2);
+        assert!(worst_two[0].coverage_fraction() <= worst_two[1].coverage_fraction());
+        assert_eq!(worst_two[0].function.name, "NeverCovered");
+    }
+
+    #[test]
+    fn rendered_html_marks_an_uncovered_line_with_the_uncovered_class() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 1\na.go:6.1,6.10 1 0\n");
+        let statements = statement_coverage(&parsed, &profile, None);
+
+        let html = render_annotated_source_html(src, "a.go", &statements.functions);
+
+        assert!(html.contains("class=\"covered\">    4"));
+        assert!(html.contains("class=\"uncovered\">    6"));
+        assert!(!html.contains("class=\"uncovered\">    4"));
+    }
+
+    #[test]
+    fn render_html_coverage_pages_produces_the_same_content_without_touching_disk() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 1\na.go:6.1,6.10 1 0\n");
+        let statements = statement_coverage(&parsed, &profile, None);
+
+        let (pages, index) = render_html_coverage_pages(&parsed, &statements);
+
+        assert_eq!(pages.len(), 1);
+        let (page, html) = &pages[0];
+        assert_eq!(page.file, "a.go");
+        assert!(html.contains("class=\"uncovered\""));
+        assert!(index.contains(&page.path.display().to_string()));
+    }
+
+    #[test]
+    fn write_html_coverage_report_writes_one_page_per_file_and_an_index() {
+        let src = "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let profile = CoverageProfile::parse("mode: set\na.go:4.1,4.10 1 1\na.go:6.1,6.10 1 0\n");
+        let statements = statement_coverage(&parsed, &profile, None);
+
+        let out_dir = std::env::temp_dir().join(format!(
+            "skanujkod-coverage-html-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&out_dir).unwrap();
+
+        let pages = write_html_coverage_report(&parsed, &statements, &out_dir).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].file, "a.go");
+
+        let page_contents = fs::read_to_string(out_dir.join(&pages[0].path)).unwrap();
+        assert!(page_contents.contains("class=\"uncovered\""));
+
+        let index = fs::read_to_string(out_dir.join("index.html")).unwrap();
+        assert!(index.contains(&pages[0].path.display().to_string()));
+
+        fs::remove_dir_all(&out_dir).ok();
+    }
+}
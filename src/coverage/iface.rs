@@ -0,0 +1,168 @@
+//! Plugin-function wrappers around [`branch_coverage`](super::branch_coverage)
+//! and [`statement_coverage`](super::statement_coverage).
+//!
+//! Both depend on [`parse_project_id`], the shared parsed-project
+//! dependency root, rather than parsing `path` again themselves — a
+//! coverage report is otherwise the same project every other analysis
+//! already parsed, just read against a `go test -coverprofile` file too.
+
+use std::path::PathBuf;
+
+use crate::go_parser::ParseDirResult;
+use crate::go_parser::iface::parse_project_id;
+use crate::kernel::{PluginFunction, QualPfId, UserParamSpec};
+
+use super::{CoverageProfile, branch_coverage, statement_coverage};
+
+/// Reads the optional `coverage_profile_path` user parameter and parses
+/// it, or an empty (nothing-covered) [`CoverageProfile`] when it wasn't
+/// supplied — a coverage plugin function still runs without a profile,
+/// it just reports every branch/statement as uncovered.
+fn coverage_profile_from_params(params: &crate::kernel::UserParams) -> Result<CoverageProfile, String> {
+    match params.get::<PathBuf>("coverage_profile_path") {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|err| format!("couldn't read coverage profile `{}`: {err}", path.display()))?;
+            Ok(CoverageProfile::parse(&text))
+        }
+        None => Ok(CoverageProfile::default()),
+    }
+}
+
+/// `coverage.branch_coverage`: `if`/`else` and `switch` case coverage
+/// across the project. Depends on `project.parse`; honors the optional
+/// `coverage_profile_path` and `function_filter` user parameters.
+pub fn branch_coverage_id() -> QualPfId {
+    QualPfId::new("coverage", "branch_coverage")
+}
+
+pub fn branch_coverage_pf() -> PluginFunction {
+    let dep = parse_project_id();
+    PluginFunction::new(branch_coverage_id(), vec![dep.clone()], move |results, params| {
+        let parsed = results.try_get::<ParseDirResult>(&dep).map_err(|e| e.to_string())?;
+        let profile = coverage_profile_from_params(params)?;
+        let function_filter = params.get::<String>("function_filter").map(String::as_str);
+        Ok(branch_coverage(parsed, &profile, function_filter))
+    })
+    .with_user_params(vec![
+        UserParamSpec::optional("coverage_profile_path"),
+        UserParamSpec::optional("function_filter"),
+    ])
+}
+
+/// `coverage.statement_coverage`: per-line statement coverage across the
+/// project. Depends on `project.parse`; honors the optional
+/// `coverage_profile_path` and `function_filter` user parameters the
+/// same way [`branch_coverage_pf`] does.
+pub fn statement_coverage_id() -> QualPfId {
+    QualPfId::new("coverage", "statement_coverage")
+}
+
+pub fn statement_coverage_pf() -> PluginFunction {
+    let dep = parse_project_id();
+    PluginFunction::new(statement_coverage_id(), vec![dep.clone()], move |results, params| {
+        let parsed = results.try_get::<ParseDirResult>(&dep).map_err(|e| e.to_string())?;
+        let profile = coverage_profile_from_params(params)?;
+        let function_filter = params.get::<String>("function_filter").map(String::as_str);
+        Ok(statement_coverage(parsed, &profile, function_filter))
+    })
+    .with_user_params(vec![
+        UserParamSpec::optional("coverage_profile_path"),
+        UserParamSpec::optional("function_filter"),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::{ProjectBranchCoverage, ProjectStatementCoverage};
+    use crate::go_parser::iface::parse_project_pf;
+    use crate::kernel::{Pipeline, UserParams};
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn tempdir_with(src: &str) -> std::path::PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-coverage-iface-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::File::create(dir.join("a.go")).unwrap().write_all(src.as_bytes()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn without_a_profile_every_branch_is_reported_uncovered() {
+        let dir = tempdir_with(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n",
+        );
+
+        let pipeline = Pipeline::new(vec![parse_project_pf(), branch_coverage_pf()]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+
+        let output = pipeline.run(&params).unwrap();
+        let report = output.results.get::<ProjectBranchCoverage>(&branch_coverage_id()).unwrap();
+
+        assert_eq!(report.branches.len(), 1);
+        assert!(report.branches[0].is_uncovered());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn a_supplied_profile_marks_the_exercised_branch_covered() {
+        let dir = tempdir_with(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n",
+        );
+        let profile_path = dir.join("cover.out");
+        std::fs::write(&profile_path, "mode: set\na.go:4.1,4.10 1 1\na.go:6.1,6.10 1 0\n").unwrap();
+
+        let pipeline = Pipeline::new(vec![parse_project_pf(), branch_coverage_pf(), statement_coverage_pf()]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+        params.set("coverage_profile_path", profile_path);
+
+        let output = pipeline.run(&params).unwrap();
+        let branches = output.results.get::<ProjectBranchCoverage>(&branch_coverage_id()).unwrap();
+        assert!(branches.branches[0].is_partial());
+
+        let statements = output.results.get::<ProjectStatementCoverage>(&statement_coverage_id()).unwrap();
+        assert_eq!(statements.functions[0].covered_lines, vec![4]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn the_project_is_parsed_exactly_once_when_cfg_and_both_coverage_analyses_run_together() {
+        let dir = tempdir_with(
+            "package main\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 2\n\t}\n}\n",
+        );
+
+        let parse_count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted_parse_count = parse_count.clone();
+        let counted_parse = PluginFunction::new(parse_project_id(), vec![], move |_results, params| {
+            counted_parse_count.fetch_add(1, Ordering::Relaxed);
+            let path = params.get::<PathBuf>("path").unwrap();
+            crate::go_parser::parse_dir(path).map_err(|err| err.to_string())
+        });
+
+        let pipeline = Pipeline::new(vec![
+            counted_parse,
+            crate::cfg_plugin::iface::build_cfgs_pf(),
+            branch_coverage_pf(),
+            statement_coverage_pf(),
+        ]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+
+        let output = pipeline.run(&params).unwrap();
+        assert!(output.results.get::<ProjectBranchCoverage>(&branch_coverage_id()).is_some());
+        assert!(output.results.get::<ProjectStatementCoverage>(&statement_coverage_id()).is_some());
+        assert_eq!(parse_count.load(Ordering::Relaxed), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,372 @@
+//! LSP-style diagnostics: a JSON-friendly view of this crate's lints
+//! (high complexity, unreachable code, missing return, infinite loop)
+//! as `{ file, range, severity, code, message }` records, positioned
+//! with 0-based line/character offsets the way the Language Server
+//! Protocol expects rather than this crate's usual 1-based line numbers.
+//!
+//! This is a reporting layer over analyses that already exist
+//! ([`complexity`][crate::complexity], [`cfg_plugin`][crate::cfg_plugin],
+//! [`lints`][crate::lints]) plus one heuristic ([`is_terminating`]) for
+//! "missing return", which none of them cover yet.
+
+use goscript_parser::ast::{Decl, Node, Stmt};
+use goscript_parser::token::Token;
+use serde::Serialize;
+
+use crate::cfg_plugin;
+use crate::complexity::{ComplexityLevel, ComplexityReport};
+use crate::go_parser::{AstObjects, ParseDirResult, Pos};
+use crate::lints::LoopConditionFinding;
+
+/// A 0-based line/character position, as the Language Server Protocol
+/// specifies it — unlike this crate's usual 1-based line numbers
+/// (see e.g. `complexity::FunctionComplexity::line`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LspPosition {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+/// LSP's `DiagnosticSeverity`, serialized as its numeric code (1-4)
+/// rather than the variant name, since that's what an editor's JSON
+/// consumer expects on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error = 1,
+    Warning = 2,
+    Information = 3,
+    Hint = 4,
+}
+
+impl Serialize for DiagnosticSeverity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// One finding, positioned and worded for an editor to show inline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub range: LspRange,
+    pub severity: DiagnosticSeverity,
+    /// A short, stable, machine-matchable label for the kind of finding
+    /// (`"high-complexity"`, `"unreachable-code"`, `"missing-return"`,
+    /// `"infinite-loop"`) — not the human-readable `message`.
+    pub code: String,
+    pub message: String,
+}
+
+/// Converts a `Pos` (an absolute offset into a `FileSet`) into a 0-based
+/// LSP position within `source`, given `base` — the file's own offset
+/// into that `FileSet`.
+fn lsp_position(source: &str, base: Pos, pos: Pos) -> LspPosition {
+    let offset = pos.saturating_sub(base);
+    let mut line = 0;
+    let mut character = 0;
+    for c in source.chars().take(offset) {
+        if c == '\n' {
+            line += 1;
+            character = 0;
+        } else {
+            character += 1;
+        }
+    }
+    LspPosition { line, character }
+}
+
+fn lsp_range(source: &str, base: Pos, start: Pos, end: Pos) -> LspRange {
+    LspRange {
+        start: lsp_position(source, base, start),
+        end: lsp_position(source, base, end),
+    }
+}
+
+/// A zero-width range at `pos` — for findings (like
+/// [`LoopConditionFinding`]) that only carry a 1-based line number
+/// rather than a precise `Pos`, so there's no real end position to
+/// report.
+fn lsp_point(source: &str, base: Pos, pos: Pos) -> LspRange {
+    let point = lsp_position(source, base, pos);
+    LspRange { start: point, end: point }
+}
+
+/// The last statement in `list` that isn't an implicit `Empty` (the
+/// parser inserts one as a semicolon placeholder before a closing `}`),
+/// so a block ending in `return x` isn't mistaken for one that falls
+/// straight off the end.
+fn last_real_stmt(list: &[Stmt]) -> Option<&Stmt> {
+    list.iter().rev().find(|s| !matches!(s, Stmt::Empty(_)))
+}
+
+/// Whether `stmt` is a Go "terminating statement" by this heuristic's
+/// (deliberately partial) approximation of the spec: it covers `return`,
+/// `goto`, condition-less `for`, blocks/`if`/labels/switches that
+/// recursively terminate, but not `select` clauses or a trailing
+/// unconditional `panic(...)` call. Good enough to flag the common
+/// "function declares results but a path clearly falls off the end"
+/// case without claiming to be a full control-flow prover.
+fn is_terminating(stmt: &Stmt, objects: &AstObjects) -> bool {
+    match stmt {
+        Stmt::Return(_) => true,
+        Stmt::Branch(b) => b.token == Token::GOTO,
+        Stmt::For(f) => f.cond.is_none(),
+        Stmt::Block(b) => last_real_stmt(&b.list).is_some_and(|s| is_terminating(s, objects)),
+        Stmt::If(i) => match &i.els {
+            Some(els) => {
+                last_real_stmt(&i.body.list).is_some_and(|s| is_terminating(s, objects))
+                    && is_terminating(els, objects)
+            }
+            None => false,
+        },
+        Stmt::Labeled(key) => is_terminating(&objects.l_stmts[*key].stmt, objects),
+        Stmt::Switch(sw) => switch_is_terminating(&sw.body.list, objects),
+        Stmt::TypeSwitch(sw) => switch_is_terminating(&sw.body.list, objects),
+        _ => false,
+    }
+}
+
+/// A `switch`/type-switch terminates if it has a `default` case and
+/// every case's body terminates.
+fn switch_is_terminating(clauses: &[Stmt], objects: &AstObjects) -> bool {
+    let mut has_default = false;
+    let mut all_terminate = true;
+    for clause in clauses {
+        let Stmt::Case(case) = clause else { continue };
+        if case.list.is_none() {
+            has_default = true;
+        }
+        if !last_real_stmt(&case.body).is_some_and(|s| is_terminating(s, objects)) {
+            all_terminate = false;
+        }
+    }
+    has_default && all_terminate
+}
+
+/// Every diagnostic this crate's lints would raise for `parsed`, keyed
+/// to editor-friendly LSP positions: high-complexity functions (from
+/// `complexity_report`, already computed against whatever
+/// `ComplexityOptions` the caller ran it with), unreachable code (a
+/// fresh CFG validation per function), missing returns (the
+/// [`is_terminating`] heuristic), and infinite loops (from
+/// `loop_findings`, already computed by
+/// [`crate::lints::empty_or_trivial_loop_conditions`]).
+pub fn collect_diagnostics(
+    parsed: &ParseDirResult,
+    complexity_report: &ComplexityReport,
+    loop_findings: &[LoopConditionFinding],
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let high_complexity: std::collections::BTreeSet<_> = complexity_report
+        .functions
+        .iter()
+        .filter(|fc| fc.level >= ComplexityLevel::High)
+        .map(|fc| fc.function.clone())
+        .collect();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function =
+                    crate::model::FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+
+                let decl_start = fdecl.pos(&parsed.objects);
+                let decl_end = fdecl.typ.end(&parsed.objects);
+                let decl_range = lsp_range(&pf.source, pf.base, decl_start, decl_end);
+
+                if high_complexity.contains(&function) {
+                    diagnostics.push(Diagnostic {
+                        file: file_name.clone(),
+                        range: decl_range,
+                        severity: DiagnosticSeverity::Warning,
+                        code: "high-complexity".to_string(),
+                        message: format!("{function} has high cyclomatic complexity"),
+                    });
+                }
+
+                let has_results = parsed.objects.ftypes[fdecl.typ]
+                    .results
+                    .as_ref()
+                    .is_some_and(|fields| !fields.list.is_empty());
+                let terminates =
+                    last_real_stmt(&body.list).is_some_and(|s| is_terminating(s, &parsed.objects));
+                if has_results && !terminates {
+                    diagnostics.push(Diagnostic {
+                        file: file_name.clone(),
+                        range: decl_range,
+                        severity: DiagnosticSeverity::Error,
+                        code: "missing-return".to_string(),
+                        message: format!("{function} may fall off the end without returning a value"),
+                    });
+                }
+
+                let cfg = cfg_plugin::build_cfg_with_options(
+                    body,
+                    &parsed.objects,
+                    cfg_plugin::CfgBuildOptions { keep_unreachable: true, ..Default::default() },
+                );
+                for block_id in cfg.validate().unreachable {
+                    let Some(stmt) = cfg.blocks[block_id].statements.first() else { continue };
+                    let pos = stmt.stmt.pos(&parsed.objects);
+                    diagnostics.push(Diagnostic {
+                        file: file_name.clone(),
+                        range: lsp_point(&pf.source, pf.base, pos),
+                        severity: DiagnosticSeverity::Warning,
+                        code: "unreachable-code".to_string(),
+                        message: "unreachable code".to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    for finding in loop_findings {
+        let Some(pf) = parsed
+            .packages
+            .values()
+            .find_map(|pkg| pkg.files.get(&finding.function.file))
+        else {
+            continue;
+        };
+        let pos = pf.base + line_start_offset(&pf.source, finding.line);
+        diagnostics.push(Diagnostic {
+            file: finding.function.file.clone(),
+            range: lsp_point(&pf.source, pf.base, pos),
+            severity: DiagnosticSeverity::Warning,
+            code: "infinite-loop".to_string(),
+            message: "loop never exits (no condition and no reachable break)".to_string(),
+        });
+    }
+
+    diagnostics
+}
+
+/// The offset of the start of 1-based `line` within `source`.
+fn line_start_offset(source: &str, line: usize) -> usize {
+    if line <= 1 {
+        return 0;
+    }
+    source
+        .char_indices()
+        .filter(|&(_, c)| c == '\n')
+        .nth(line.saturating_sub(2))
+        .map(|(i, _)| i + 1)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::complexity::{self, ComplexityOptions};
+    use std::fs;
+    use std::io::Write;
+
+    fn parse_one(src: &str) -> ParseDirResult {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-diagnostics-test-{}-{n}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::File::create(dir.join("a.go"))
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    #[test]
+    fn a_high_complexity_function_gets_a_warning_at_its_declaration() {
+        let mut src = String::from("package main\n\nfunc f(x int) int {\n");
+        for i in 0..25 {
+            src.push_str(&format!("\tif x == {i} {{\n\t\treturn {i}\n\t}}\n"));
+        }
+        src.push_str("\treturn -1\n}\n");
+        let parsed = parse_one(&src);
+
+        let complexity_report =
+            complexity::analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        assert!(
+            complexity_report.functions[0].level >= ComplexityLevel::High,
+            "fixture should actually be high complexity"
+        );
+
+        let diagnostics = collect_diagnostics(&parsed, &complexity_report, &[]);
+        let diagnostic = diagnostics
+            .iter()
+            .find(|d| d.code == "high-complexity")
+            .expect("a high-complexity diagnostic should be reported");
+
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostic.range.start, LspPosition { line: 2, character: 0 });
+    }
+
+    #[test]
+    fn a_function_missing_a_return_on_some_path_is_flagged() {
+        let src = "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let complexity_report =
+            complexity::analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        let diagnostics = collect_diagnostics(&parsed, &complexity_report, &[]);
+        assert!(diagnostics.iter().any(|d| d.code == "missing-return"));
+    }
+
+    #[test]
+    fn a_function_that_always_returns_is_not_flagged_as_missing_a_return() {
+        let src = "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn 1\n\t} else {\n\t\treturn 0\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let complexity_report =
+            complexity::analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        let diagnostics = collect_diagnostics(&parsed, &complexity_report, &[]);
+        assert!(!diagnostics.iter().any(|d| d.code == "missing-return"));
+    }
+
+    #[test]
+    fn code_after_an_unconditional_return_is_flagged_as_unreachable() {
+        let src = "package main\n\nfunc f() {\n\treturn\n\tx := 1\n\t_ = x\n}\n";
+        let parsed = parse_one(src);
+        let complexity_report =
+            complexity::analyze_function_complexity(&parsed, &ComplexityOptions::default());
+
+        let diagnostics = collect_diagnostics(&parsed, &complexity_report, &[]);
+        let diagnostic = diagnostics
+            .iter()
+            .find(|d| d.code == "unreachable-code")
+            .expect("an unreachable-code diagnostic should be reported");
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+    }
+
+    #[test]
+    fn an_infinite_loop_finding_is_reported_as_a_diagnostic_at_its_line() {
+        let src = "package main\n\nfunc f() {\n\tfor {\n\t\tx := 1\n\t\t_ = x\n\t}\n}\n";
+        let parsed = parse_one(src);
+        let complexity_report =
+            complexity::analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        let loop_findings = crate::lints::empty_or_trivial_loop_conditions(&parsed);
+
+        let diagnostics = collect_diagnostics(&parsed, &complexity_report, &loop_findings);
+        let diagnostic = diagnostics
+            .iter()
+            .find(|d| d.code == "infinite-loop")
+            .expect("an infinite-loop diagnostic should be reported");
+
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostic.range.start, LspPosition { line: 3, character: 0 });
+    }
+}
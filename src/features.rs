@@ -0,0 +1,236 @@
+//! Which Go concurrency/error-handling/generics features a function
+//! actually uses — goroutines, `defer`, `recover()`, channel operations,
+//! `select`, and type parameters — for a caller that wants to flag
+//! concurrency-heavy or generic code rather than just its complexity.
+//!
+//! Five of the six features are ordinary AST shapes [`ast_search`] can
+//! find directly. Type parameters are the exception: the parser never
+//! sees them at all (see [`crate::go_parser`]'s `generics` module), so
+//! that one is detected with a source-text heuristic instead, the same
+//! way [`crate::lints::find_comment_markers`] and
+//! `complexity::is_complexity_suppressed` fall back to re-tokenizing raw
+//! source for information the AST doesn't carry.
+
+use goscript_parser::ast::{Decl, Expr, Stmt};
+use goscript_parser::token::Token;
+
+use crate::ast_search;
+use crate::go_parser::{self, AstObjects, ParseDirResult};
+use crate::model::FunctionId;
+
+/// A single Go language feature [`analyze_feature_usage`] looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub enum Feature {
+    Goroutines,
+    Defer,
+    Recover,
+    Channels,
+    Select,
+    Generics,
+}
+
+/// Every [`Feature`] a single function uses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FunctionFeatureProfile {
+    pub function: FunctionId,
+    pub features: std::collections::BTreeSet<Feature>,
+}
+
+/// The result of [`analyze_feature_usage`]: every function's own feature
+/// profile, plus how many functions use each [`Feature`] project-wide.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct FeatureSummary {
+    pub functions: Vec<FunctionFeatureProfile>,
+    pub counts: std::collections::BTreeMap<Feature, usize>,
+}
+
+/// Whether `expr` is a call to the bare `recover` identifier — `recover()`
+/// is only ever meaningful unqualified, so unlike [`crate::call_graph`]'s
+/// callee resolution this doesn't need to know what else is declared.
+fn is_recover_call(expr: &Expr, objects: &AstObjects) -> bool {
+    let Expr::Call(call) = expr else { return false };
+    matches!(&call.func, Expr::Ident(key) if objects.idents[*key].name == "recover")
+}
+
+/// The features used directly in `body` — not counting whatever a nested
+/// function literal declared elsewhere in the project uses, but *does*
+/// count one written inline (`defer func() { recover() }()`), since
+/// [`ast_search::walk_expr`] descends into a `FuncLit`'s body.
+fn features_in_body(body: &[Stmt], objects: &AstObjects) -> std::collections::BTreeSet<Feature> {
+    let mut features = std::collections::BTreeSet::new();
+
+    if !ast_search::find_stmts_by(body, objects, |s| matches!(s, Stmt::Go(_))).is_empty() {
+        features.insert(Feature::Goroutines);
+    }
+    if !ast_search::find_stmts_by(body, objects, |s| matches!(s, Stmt::Defer(_))).is_empty() {
+        features.insert(Feature::Defer);
+    }
+    if !ast_search::find_stmts_by(body, objects, |s| matches!(s, Stmt::Select(_))).is_empty() {
+        features.insert(Feature::Select);
+    }
+    let has_send = !ast_search::find_stmts_by(body, objects, |s| matches!(s, Stmt::Send(_))).is_empty();
+    let has_receive = ast_search::count_exprs_by(body, objects, |e| {
+        matches!(e, Expr::Unary(u) if u.op == Token::ARROW)
+    }) > 0;
+    if has_send || has_receive {
+        features.insert(Feature::Channels);
+    }
+    if ast_search::count_exprs_by(body, objects, |e| is_recover_call(e, objects)) > 0 {
+        features.insert(Feature::Recover);
+    }
+
+    features
+}
+
+/// Computes every function's [`FunctionFeatureProfile`] across `parsed`,
+/// plus the project-wide per-[`Feature`] counts.
+pub fn analyze_feature_usage(parsed: &ParseDirResult) -> FeatureSummary {
+    let mut summary = FeatureSummary::default();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            let generic_functions = go_parser::functions_with_type_params(&pf.source);
+
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+
+                let mut features = match &fdecl.body {
+                    Some(body) => features_in_body(&body.list, &parsed.objects),
+                    None => std::collections::BTreeSet::new(),
+                };
+                // Methods can't declare their own type parameters in Go, so
+                // `generic_functions` (a bare-name index) can only ever mean
+                // a free function of that name — checking it against a
+                // method would risk a false positive from an unrelated free
+                // function sharing the method's name in the same file.
+                if fdecl.recv.is_none() && generic_functions.contains(&name) {
+                    features.insert(Feature::Generics);
+                }
+
+                for &feature in &features {
+                    *summary.counts.entry(feature).or_insert(0) += 1;
+                }
+
+                summary.functions.push(FunctionFeatureProfile {
+                    function: FunctionId::new(pkg.name.clone(), file_name.clone(), name),
+                    features,
+                });
+            }
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(dir: &Path, name: &str, src: &str) {
+        fs::write(dir.join(name), src).unwrap();
+    }
+
+    fn tempfile_dir(tag: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("skanujkod-features-test-{tag}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn profile_of<'a>(summary: &'a FeatureSummary, name: &str) -> &'a FunctionFeatureProfile {
+        summary.functions.iter().find(|p| p.function.name == name).unwrap()
+    }
+
+    #[test]
+    fn detects_each_feature_in_the_function_that_uses_it_and_nothing_in_a_plain_one() {
+        let dir = tempfile_dir("basic");
+        write(
+            &dir,
+            "a.go",
+            "package main\n\
+             func plain(x int) int {\n\
+             \treturn x + 1\n\
+             }\n\
+             \n\
+             func withGoroutine() {\n\
+             \tgo plain(1)\n\
+             }\n\
+             \n\
+             func withDeferAndRecover() {\n\
+             \tdefer func() {\n\
+             \t\trecover()\n\
+             \t}()\n\
+             }\n\
+             \n\
+             func withChannels(ch chan int) {\n\
+             \tch <- 1\n\
+             \t<-ch\n\
+             }\n\
+             \n\
+             func withSelect(ch chan int) {\n\
+             \tselect {\n\
+             \tcase v := <-ch:\n\
+             \t\t_ = v\n\
+             \tdefault:\n\
+             \t}\n\
+             }\n\
+             \n\
+             func Generic[T any](x T) T {\n\
+             \treturn x\n\
+             }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let summary = analyze_feature_usage(&parsed);
+
+        assert!(profile_of(&summary, "plain").features.is_empty());
+        assert_eq!(profile_of(&summary, "withGoroutine").features, [Feature::Goroutines].into());
+        assert_eq!(
+            profile_of(&summary, "withDeferAndRecover").features,
+            [Feature::Defer, Feature::Recover].into()
+        );
+        assert_eq!(profile_of(&summary, "withChannels").features, [Feature::Channels].into());
+        // A `select` with a channel receive in its own `case` arm counts
+        // as both `Select` and `Channels`.
+        assert_eq!(
+            profile_of(&summary, "withSelect").features,
+            [Feature::Select, Feature::Channels].into()
+        );
+        assert_eq!(profile_of(&summary, "Generic").features, [Feature::Generics].into());
+
+        assert_eq!(summary.counts[&Feature::Goroutines], 1);
+        assert_eq!(summary.counts[&Feature::Defer], 1);
+        assert_eq!(summary.counts[&Feature::Recover], 1);
+        assert_eq!(summary.counts[&Feature::Channels], 2);
+        assert_eq!(summary.counts[&Feature::Select], 1);
+        assert_eq!(summary.counts[&Feature::Generics], 1);
+    }
+
+    #[test]
+    fn a_method_is_not_tagged_generic_just_because_a_free_function_of_the_same_name_is() {
+        let dir = tempfile_dir("generic-name-collision");
+        write(
+            &dir,
+            "a.go",
+            "package main\n\
+             type T struct{}\n\
+             func (t T) Foo() {}\n\
+             func Foo[X any](x X) X { return x }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let summary = analyze_feature_usage(&parsed);
+        let with_generics: Vec<_> =
+            summary.functions.iter().filter(|p| p.features.contains(&Feature::Generics)).collect();
+
+        assert_eq!(with_generics.len(), 1, "only the free function should be tagged generic");
+        assert_eq!(summary.counts[&Feature::Generics], 1);
+    }
+}
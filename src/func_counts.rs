@@ -0,0 +1,132 @@
+//! Counting function and method declarations across a parsed project,
+//! broken down the ways a reviewer usually cares about: per file, per
+//! package, exported vs unexported, and free functions vs methods.
+
+use std::collections::BTreeMap;
+
+use goscript_parser::ast::Decl;
+
+use crate::go_parser::ParseDirResult;
+
+/// Function/method counts for a parsed project, with `total` kept around
+/// for callers that only want the single number a plain count used to
+/// give.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct FuncCounts {
+    pub total: usize,
+    /// Keyed by file name, as stored in `Package::files`.
+    pub by_file: BTreeMap<String, usize>,
+    /// Keyed by package directory, as stored in `ParseDirResult::packages`.
+    pub by_package: BTreeMap<String, usize>,
+    pub exported: usize,
+    pub unexported: usize,
+    /// Declarations with a receiver (`func (t T) Name()`).
+    pub methods: usize,
+    /// Declarations without a receiver (`func Name()`).
+    pub free_functions: usize,
+}
+
+/// Go's own exported-name convention: a name is exported if its first
+/// character is uppercase.
+fn is_exported(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_uppercase())
+}
+
+/// Counts every function and method declaration in `parsed`.
+pub fn count_funcs(parsed: &ParseDirResult) -> FuncCounts {
+    let mut counts = FuncCounts::default();
+
+    for (pkg_dir, pkg) in &parsed.packages {
+        for (file_name, pf) in &pkg.files {
+            let mut in_file = 0;
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let name = &parsed.objects.idents[fdecl.name].name;
+
+                counts.total += 1;
+                in_file += 1;
+
+                if is_exported(name) {
+                    counts.exported += 1;
+                } else {
+                    counts.unexported += 1;
+                }
+
+                if fdecl.recv.is_some() {
+                    counts.methods += 1;
+                } else {
+                    counts.free_functions += 1;
+                }
+            }
+
+            if in_file > 0 {
+                *counts.by_file.entry(file_name.clone()).or_insert(0) += in_file;
+                *counts.by_package.entry(pkg_dir.clone()).or_insert(0) += in_file;
+            }
+        }
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(dir: &Path, name: &str, src: &str) -> std::path::PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, src).unwrap();
+        path
+    }
+
+    #[test]
+    fn counts_per_file_and_the_exported_method_breakdown() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-func-counts-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package main\n\
+             func Exported() {}\n\
+             func unexported() {}\n\
+             func (t T) Method() {}\n",
+        );
+        write(&dir, "b.go", "package main\nfunc another() {}\n");
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let counts = count_funcs(&parsed);
+
+        assert_eq!(counts.total, 4);
+        assert_eq!(counts.by_file["a.go"], 3);
+        assert_eq!(counts.by_file["b.go"], 1);
+        assert_eq!(counts.by_package[""], 4);
+        assert_eq!(counts.exported, 2);
+        assert_eq!(counts.unexported, 2);
+        assert_eq!(counts.methods, 1);
+        assert_eq!(counts.free_functions, 3);
+    }
+
+    #[test]
+    fn by_package_breaks_down_functions_across_separate_packages() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-func-counts-multi-pkg-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("widgets")).unwrap();
+        write(&dir, "root.go", "package main\nfunc Main() {}\n");
+        write(&dir.join("widgets"), "widgets.go", "package widgets\nfunc New() {}\nfunc reset() {}\n");
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let counts = count_funcs(&parsed);
+
+        assert_eq!(counts.total, 3);
+        assert_eq!(counts.by_package[""], 1);
+        assert_eq!(counts.by_package["widgets"], 2);
+    }
+}
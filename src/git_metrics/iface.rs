@@ -0,0 +1,233 @@
+//! Plugin-function wrappers around [`git_metrics`](super), for composing
+//! history analysis into a kernel [`Pipeline`](crate::kernel::Pipeline).
+
+use std::path::PathBuf;
+
+use crate::complexity::ComplexityOptions;
+use crate::go_parser::ParseOptions;
+use crate::kernel::{PluginFunction, QualPfId, UserParamSpec, UserParams};
+
+use super::{
+    CloneOptions, CommitWalkOptions, CommitsByAuthor, FirstLastCommit, GitMetricsError, Metric,
+    MetricResultType, Repo, complexity_trend, read_repo_from, run_metrics, total_commit_percentage,
+};
+
+/// `git_metrics.read_repo`: opens the repository named by the
+/// user-supplied `repo_path` parameter — a local path, or a Git URL to
+/// clone into a temporary directory first (see [`read_repo_from`]). An
+/// optional `shallow_depth` parameter limits a URL clone to that many
+/// recent commits. The dependency root for every other PF in this
+/// module.
+pub fn read_repo_id() -> QualPfId {
+    QualPfId::new("git_metrics", "read_repo")
+}
+
+pub fn read_repo_pf() -> PluginFunction {
+    PluginFunction::new(read_repo_id(), vec![], |_results, params: &UserParams| {
+        let path = params
+            .get::<PathBuf>("repo_path")
+            .ok_or_else(|| "missing user parameter `repo_path`".to_string())?;
+        let source = path
+            .to_str()
+            .ok_or_else(|| "repo_path is not valid UTF-8".to_string())?;
+        let options = CloneOptions {
+            shallow_depth: params.get::<i32>("shallow_depth").copied(),
+        };
+        read_repo_from(source, &options).map_err(|err: GitMetricsError| err.to_string())
+    })
+    .with_user_params(vec![
+        UserParamSpec::required("repo_path"),
+        UserParamSpec::optional("shallow_depth"),
+    ])
+}
+
+/// `git_metrics.first_last_commit`: each author's earliest and most
+/// recent commit, for onboarding/attrition-style reports. Depends on
+/// `git_metrics.read_repo`, and honors an optional `max_commits` user
+/// parameter the same way [`CommitWalkOptions`] does.
+pub fn first_last_commit_id() -> QualPfId {
+    QualPfId::new("git_metrics", "first_last_commit")
+}
+
+pub fn first_last_commit_pf() -> PluginFunction {
+    let dep = read_repo_id();
+    PluginFunction::new(first_last_commit_id(), vec![dep.clone()], move |results, params| {
+        let repo = results
+            .get::<Repo>(&dep)
+            .expect("git_metrics.read_repo already ran");
+        let options = CommitWalkOptions {
+            max_commits: params.get::<usize>("max_commits").copied(),
+        };
+
+        let mut metrics: Vec<Box<dyn Metric>> = vec![Box::new(FirstLastCommit::default())];
+        run_metrics(repo, &options, &mut metrics).map_err(|err| err.to_string())?;
+        Ok(metrics[0].result())
+    })
+    .with_user_params(vec![UserParamSpec::optional("max_commits")])
+}
+
+/// `git_metrics.commits_by_author`: commit counts bucketed by author.
+/// Depends on `git_metrics.read_repo`.
+pub fn commits_by_author_id() -> QualPfId {
+    QualPfId::new("git_metrics", "commits_by_author")
+}
+
+pub fn commits_by_author_pf() -> PluginFunction {
+    let dep = read_repo_id();
+    PluginFunction::new(commits_by_author_id(), vec![dep.clone()], move |results, params| {
+        let repo = results
+            .get::<Repo>(&dep)
+            .expect("git_metrics.read_repo already ran");
+        let options = CommitWalkOptions {
+            max_commits: params.get::<usize>("max_commits").copied(),
+        };
+
+        let mut metrics: Vec<Box<dyn Metric>> = vec![Box::new(CommitsByAuthor::default())];
+        run_metrics(repo, &options, &mut metrics).map_err(|err| err.to_string())?;
+        Ok(metrics[0].result())
+    })
+    .with_user_params(vec![UserParamSpec::optional("max_commits")])
+}
+
+/// `git_metrics.total_commit_percentage`: each author's share of total
+/// commits, as a percentage. Depends on `git_metrics.commits_by_author`
+/// rather than `read_repo` directly, so it doesn't re-walk history the
+/// kernel has already walked for that PF.
+pub fn total_commit_percentage_id() -> QualPfId {
+    QualPfId::new("git_metrics", "total_commit_percentage")
+}
+
+pub fn total_commit_percentage_pf() -> PluginFunction {
+    let dep = commits_by_author_id();
+    PluginFunction::new(total_commit_percentage_id(), vec![dep.clone()], move |results, _params| {
+        let by_author = results
+            .get::<MetricResultType>(&dep)
+            .expect("git_metrics.commits_by_author already ran");
+        let counts = by_author
+            .as_count_map()
+            .ok_or_else(|| "git_metrics.commits_by_author did not return a CountMap".to_string())?;
+        Ok(total_commit_percentage(counts))
+    })
+}
+
+/// `git_metrics.complexity_trend`: average/max cyclomatic complexity at
+/// each commit, for charting whether a project is getting more or less
+/// complex over time. Depends on `git_metrics.read_repo`, and honors an
+/// optional `max_commits` user parameter the same way the other
+/// history-walking PFs in this module do.
+pub fn complexity_trend_id() -> QualPfId {
+    QualPfId::new("git_metrics", "complexity_trend")
+}
+
+pub fn complexity_trend_pf() -> PluginFunction {
+    let dep = read_repo_id();
+    PluginFunction::new(complexity_trend_id(), vec![dep.clone()], move |results, params| {
+        let repo = results
+            .get::<Repo>(&dep)
+            .expect("git_metrics.read_repo already ran");
+        let walk_options = CommitWalkOptions {
+            max_commits: params.get::<usize>("max_commits").copied(),
+        };
+        complexity_trend(repo, &walk_options, &ParseOptions::default(), &ComplexityOptions::default())
+            .map_err(|err: GitMetricsError| err.to_string())
+    })
+    .with_user_params(vec![UserParamSpec::optional("max_commits")])
+}
+
+// `total_commits` still doesn't have a plugin function of its own, so a
+// pipeline can't yet ask for just it without running the other metrics:
+//
+// pub fn total_commits_pf() -> PluginFunction { ... }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::{Pipeline, UserParams};
+    use std::path::Path;
+
+    fn tempfile_dir() -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-git-metrics-iface-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn commit_file(repo: &git2::Repository, root: &Path, author: &str, message: &str) {
+        std::fs::write(root.join("file.txt"), message).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        index.write().unwrap();
+        let tree_id = index.write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let sig = git2::Signature::now(author, "author@example.com").unwrap();
+        let parent = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .and_then(|oid| repo.find_commit(oid).ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn total_commit_percentage_sums_to_100_through_the_pipeline() {
+        let dir = tempfile_dir();
+        let git_repo = git2::Repository::init(&dir).unwrap();
+        commit_file(&git_repo, &dir, "alice", "one");
+        commit_file(&git_repo, &dir, "alice", "two");
+        commit_file(&git_repo, &dir, "bob", "three");
+
+        let pipeline = Pipeline::new(vec![
+            read_repo_pf(),
+            commits_by_author_pf(),
+            total_commit_percentage_pf(),
+        ]);
+        let mut params = UserParams::new();
+        params.set("repo_path", dir.clone());
+
+        let output = pipeline.run(&params).unwrap();
+        let percentages = output
+            .results
+            .get::<std::collections::BTreeMap<String, f64>>(&total_commit_percentage_id())
+            .unwrap();
+
+        assert!((percentages["alice"] - (200.0 / 3.0)).abs() < 1e-9);
+        assert!((percentages["bob"] - (100.0 / 3.0)).abs() < 1e-9);
+        let sum: f64 = percentages.values().sum();
+        assert!((sum - 100.0).abs() < 1e-9);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn complexity_trend_runs_through_the_pipeline_and_yields_one_point_per_commit() {
+        let dir = tempfile_dir();
+        let git_repo = git2::Repository::init(&dir).unwrap();
+        std::fs::write(dir.join("a.go"), "package main\nfunc f() {}\n").unwrap();
+        let mut index = git_repo.index().unwrap();
+        index.add_path(Path::new("a.go")).unwrap();
+        index.write().unwrap();
+        let tree = git_repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("alice", "alice@example.com").unwrap();
+        git_repo.commit(Some("HEAD"), &sig, &sig, "add a.go", &tree, &[]).unwrap();
+
+        let pipeline = Pipeline::new(vec![read_repo_pf(), complexity_trend_pf()]);
+        let mut params = UserParams::new();
+        params.set("repo_path", dir.clone());
+
+        let output = pipeline.run(&params).unwrap();
+        let series = output
+            .results
+            .get::<Vec<super::super::ComplexityTrendPoint>>(&complexity_trend_id())
+            .unwrap();
+        assert_eq!(series.len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,311 @@
+//! Parsing and evaluating Go build constraints (`//go:build` and the
+//! legacy `// +build`), so a directory parse can skip files that don't
+//! apply to the target platform instead of treating every `.go` file
+//! as if it always builds.
+
+/// Which platform a project should be analyzed as if it were being built
+/// for. Defaults to the host this analysis is running on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub goos: String,
+    pub goarch: String,
+    /// Whether a file with syntax errors should fail the whole parse
+    /// ([`ParseDirError::Syntax`][super::dir::ParseDirError::Syntax])
+    /// instead of being silently dropped from the result. Defaults to
+    /// `false`, since a single malformed file in an otherwise-healthy
+    /// project is usually more useful to skip than to block on.
+    pub fail_on_parse_error: bool,
+    /// Whether a file bearing Go's `// Code generated ... DO NOT EDIT.`
+    /// header ([`super::generated::has_generated_header`]) is skipped
+    /// entirely, the same way a file that doesn't match the target
+    /// platform already is. Defaults to `true`, since generated code
+    /// (protobuf bindings, mocks, ...) inflates every metric (SLOC,
+    /// complexity, func counts) without telling you anything about the
+    /// project a human actually wrote.
+    pub exclude_generated: bool,
+    /// Skip a file entirely, recording a warning instead of parsing it
+    /// into the result, once it declares more than this many top-level
+    /// functions. `None` (the default) means unlimited. Guards against a
+    /// generated file with tens of thousands of functions stalling
+    /// downstream analyses (CFG construction in particular) that assume
+    /// a project's files are human-sized.
+    pub max_functions_per_file: Option<usize>,
+    /// Whether a `_test.go` file is parsed at all. Defaults to `false`:
+    /// most analyses (complexity, func counts, lints) are about the
+    /// project's own code, and a test file's setup/assertion-heavy style
+    /// would otherwise skew those numbers. Set this when the analysis
+    /// itself is about the tests — e.g. complexity of test helpers.
+    pub include_test_files: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            goos: std::env::consts::OS.to_string(),
+            goarch: std::env::consts::ARCH.to_string(),
+            fail_on_parse_error: false,
+            exclude_generated: true,
+            max_functions_per_file: None,
+            include_test_files: false,
+        }
+    }
+}
+
+/// `GOOS` values that satisfy the "unix" pseudo build tag.
+const UNIX_GOOS: &[&str] = &[
+    "aix", "android", "darwin", "dragonfly", "freebsd", "hurd", "illumos", "ios", "linux",
+    "netbsd", "openbsd", "solaris",
+];
+
+const KNOWN_GOOS: &[&str] = &[
+    "aix", "android", "darwin", "dragonfly", "freebsd", "hurd", "illumos", "ios", "js", "linux",
+    "netbsd", "openbsd", "plan9", "solaris", "windows",
+];
+
+const KNOWN_GOARCH: &[&str] = &[
+    "386", "amd64", "arm", "arm64", "mips", "mips64", "mips64le", "mipsle", "ppc64", "ppc64le",
+    "riscv64", "s390x", "wasm",
+];
+
+/// Whether `tag` holds for `options`. A tag this module doesn't recognize
+/// (a custom build tag like `integration`, or a `-tags`-style feature
+/// flag) is assumed to hold — this is a static analyzer, not a build, so
+/// erring towards analyzing a file rather than silently dropping it on an
+/// unfamiliar tag is the safer default.
+fn tag_matches(tag: &str, options: &ParseOptions) -> bool {
+    if tag == "unix" {
+        return UNIX_GOOS.contains(&options.goos.as_str());
+    }
+    if KNOWN_GOOS.contains(&tag) {
+        return tag == options.goos;
+    }
+    if KNOWN_GOARCH.contains(&tag) {
+        return tag == options.goarch;
+    }
+    true
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Tag(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, options: &ParseOptions) -> bool {
+        match self {
+            Expr::Tag(tag) => tag_matches(tag, options),
+            Expr::Not(e) => !e.eval(options),
+            Expr::And(a, b) => a.eval(options) && b.eval(options),
+            Expr::Or(a, b) => a.eval(options) || b.eval(options),
+        }
+    }
+}
+
+fn tokenize(expr: &str) -> Vec<String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' || c == ')' || c == '!' {
+            tokens.push(c.to_string());
+            i += 1;
+        } else if c == '&' && chars.get(i + 1) == Some(&'&') {
+            tokens.push("&&".to_string());
+            i += 2;
+        } else if c == '|' && chars.get(i + 1) == Some(&'|') {
+            tokens.push("||".to_string());
+            i += 2;
+        } else {
+            let start = i;
+            while i < chars.len() && !chars[i].is_whitespace() && !"()!&|".contains(chars[i]) {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        }
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn advance(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.pos).map(String::as_str);
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Option<Expr> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some("||") {
+            self.advance();
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Some(left)
+    }
+
+    fn parse_and(&mut self) -> Option<Expr> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some("&&") {
+            self.advance();
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Some(left)
+    }
+
+    fn parse_unary(&mut self) -> Option<Expr> {
+        if self.peek() == Some("!") {
+            self.advance();
+            return Some(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Option<Expr> {
+        match self.advance()? {
+            "(" => {
+                let inner = self.parse_or()?;
+                if self.peek() == Some(")") {
+                    self.advance();
+                }
+                Some(inner)
+            }
+            tag => Some(Expr::Tag(tag.to_string())),
+        }
+    }
+}
+
+fn parse_go_build_expr(expr: &str) -> Option<Expr> {
+    let tokens = tokenize(expr);
+    if tokens.is_empty() {
+        return None;
+    }
+    Parser { tokens: &tokens, pos: 0 }.parse_or()
+}
+
+/// Whether a `// +build` line's terms are satisfied: space-separated
+/// terms are OR'd, comma-separated terms within one are AND'd, and a
+/// leading `!` negates a single term.
+fn plus_build_line_matches(line: &str, options: &ParseOptions) -> bool {
+    line.split_whitespace().any(|term| {
+        term.split(',').all(|t| match t.strip_prefix('!') {
+            Some(negated) => !tag_matches(negated, options),
+            None => tag_matches(t, options),
+        })
+    })
+}
+
+/// Whether `source` should be analyzed under `options`, based on any
+/// `//go:build` or `// +build` constraint comments found before the
+/// first non-comment, non-blank line (i.e. before the `package` clause).
+/// A file with no constraint comments always applies.
+pub fn file_applies(source: &str, options: &ParseOptions) -> bool {
+    let mut go_build: Option<Expr> = None;
+    let mut plus_build_lines: Vec<&str> = Vec::new();
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("//go:build ") {
+            if go_build.is_none() {
+                go_build = parse_go_build_expr(rest);
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("// +build ") {
+            plus_build_lines.push(rest);
+        } else if !trimmed.starts_with("//") {
+            break;
+        }
+    }
+
+    if let Some(expr) = go_build {
+        return expr.eval(options);
+    }
+
+    plus_build_lines
+        .iter()
+        .all(|line| plus_build_line_matches(line, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(goos: &str, goarch: &str) -> ParseOptions {
+        ParseOptions {
+            goos: goos.to_string(),
+            goarch: goarch.to_string(),
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn a_file_with_no_constraints_always_applies() {
+        assert!(file_applies("package main\n", &options("linux", "amd64")));
+    }
+
+    #[test]
+    fn go_build_matches_only_the_named_goos() {
+        let src = "//go:build windows\n\npackage main\n";
+        assert!(file_applies(src, &options("windows", "amd64")));
+        assert!(!file_applies(src, &options("linux", "amd64")));
+    }
+
+    #[test]
+    fn go_build_supports_negation_and_and_or() {
+        let src = "//go:build linux && !arm\n\npackage main\n";
+        assert!(file_applies(src, &options("linux", "amd64")));
+        assert!(!file_applies(src, &options("linux", "arm")));
+        assert!(!file_applies(src, &options("windows", "amd64")));
+
+        let src = "//go:build linux || darwin\n\npackage main\n";
+        assert!(file_applies(src, &options("darwin", "arm64")));
+        assert!(!file_applies(src, &options("windows", "amd64")));
+    }
+
+    #[test]
+    fn legacy_plus_build_lines_and_across_lines_or_within_a_line() {
+        let src = "// +build linux darwin\n// +build amd64\n\npackage main\n";
+        assert!(file_applies(src, &options("linux", "amd64")));
+        assert!(!file_applies(src, &options("linux", "arm64")));
+        assert!(!file_applies(src, &options("windows", "amd64")));
+    }
+
+    #[test]
+    fn unix_tag_matches_any_unix_like_goos() {
+        let src = "//go:build unix\n\npackage main\n";
+        assert!(file_applies(src, &options("linux", "amd64")));
+        assert!(file_applies(src, &options("darwin", "arm64")));
+        assert!(!file_applies(src, &options("windows", "amd64")));
+    }
+
+    #[test]
+    fn an_unrecognized_tag_is_assumed_to_apply() {
+        let src = "//go:build integration\n\npackage main\n";
+        assert!(file_applies(src, &options("linux", "amd64")));
+    }
+
+    #[test]
+    fn go_build_takes_precedence_over_a_conflicting_plus_build_line() {
+        // Per the Go spec, a file with both directives is evaluated by
+        // //go:build alone; the legacy // +build line is only consulted
+        // when //go:build is absent.
+        let src = "//go:build linux\n// +build windows\n\npackage main\n";
+        assert!(file_applies(src, &options("linux", "amd64")));
+        assert!(!file_applies(src, &options("windows", "amd64")));
+    }
+}
@@ -0,0 +1,939 @@
+//! Parsing a whole directory tree of Go source into packages, grouping
+//! files the way `go build` would (one package per directory).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use goscript_parser::errors::ErrorList;
+use goscript_parser::objects::Objects as AstObjects;
+use goscript_parser::position::FileSet;
+use goscript_parser::{ast, parse_file};
+
+use super::build_constraints::{self, ParseOptions};
+use super::generated::has_generated_header;
+use super::generics::strip_type_params;
+use super::normalize::normalize_source;
+use super::token::Pos;
+
+/// A single parsed `.go` file, plus enough bookkeeping to map positions
+/// back to source text.
+pub struct ParsedFile {
+    pub path: PathBuf,
+    pub source: String,
+    /// The `FileSet` base offset this file's positions start at, so a
+    /// global `Pos` can be turned back into a local char offset into
+    /// `source` via `pos - base`.
+    pub base: Pos,
+    pub ast: ast::File,
+}
+
+/// A Go package: every `.go` file that declared the same package name in
+/// the same directory.
+pub struct Package {
+    pub name: String,
+    pub files: BTreeMap<String, ParsedFile>,
+}
+
+/// The result of parsing every `.go` file under a directory.
+///
+/// `objects`/`file_set` are the shared arenas every file's AST was parsed
+/// into, so cross-file analyses (import graphs, CFGs, ...) can resolve
+/// `IdentKey`s and `Pos`s without juggling one arena per file.
+pub struct ParseDirResult {
+    pub objects: AstObjects,
+    pub file_set: FileSet,
+    /// Keyed by the package's directory, relative to the root that was
+    /// parsed (the empty string for the root itself).
+    pub packages: BTreeMap<String, Package>,
+    /// One entry per file skipped because it tripped a guardrail (see
+    /// [`ParseOptions::max_functions_per_file`]), rather than one of the
+    /// checks that were already here before (a build tag that doesn't
+    /// match, a generated header) — those are ordinary, expected
+    /// filtering, not something worth flagging to a caller.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ParseDirError {
+    Io(PathBuf, std::io::Error),
+    /// `root` exists but isn't a directory — distinguished from the
+    /// generic [`ParseDirError::Io`] case so a caller can tell "you gave
+    /// me a file" apart from "that path doesn't exist" or "I don't have
+    /// permission to read it" without inspecting an `io::ErrorKind`.
+    NotADirectory(PathBuf),
+    /// A file had syntax errors and [`ParseOptions::fail_on_parse_error`]
+    /// was set, so the file was rejected instead of being silently
+    /// dropped from the result. Carries the parser's own error messages.
+    Syntax(PathBuf, String),
+}
+
+impl fmt::Display for ParseDirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDirError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            ParseDirError::NotADirectory(path) => write!(f, "{}: not a directory", path.display()),
+            ParseDirError::Syntax(path, errors) => {
+                write!(f, "{}: syntax error(s):\n{errors}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseDirError {}
+
+/// Whether `path` is a Go test file (`..._test.go`), the same suffix
+/// `go build`/`go test` use to tell test-only code apart from a
+/// package's regular sources.
+fn is_test_file(path: &Path) -> bool {
+    path.file_stem().and_then(|s| s.to_str()).is_some_and(|s| s.ends_with("_test"))
+}
+
+pub(crate) fn collect_go_files(root: &Path, out: &mut Vec<PathBuf>) -> Result<(), ParseDirError> {
+    let entries =
+        fs::read_dir(root).map_err(|e| ParseDirError::Io(root.to_path_buf(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| ParseDirError::Io(root.to_path_buf(), e))?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_go_files(&path, out)?;
+        } else if path.extension().is_some_and(|ext| ext == "go") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Parses every `.go` file under `root`, grouping them into packages by
+/// directory. Files that fail to parse are skipped rather than aborting
+/// the whole run, since one malformed file shouldn't block analysis of
+/// the rest of the project.
+///
+/// Equivalent to [`parse_dir_with_options`] with the host platform as the
+/// target, i.e. files constrained to a different OS/architecture are
+/// skipped the same way `go build` would skip them here.
+pub fn parse_dir(root: &Path) -> Result<ParseDirResult, ParseDirError> {
+    parse_dir_with_options(root, &ParseOptions::default())
+}
+
+/// Parses every `.go` file under `root` as if building for `options`,
+/// skipping files whose `//go:build`/`// +build` constraints don't match
+/// it. Otherwise identical to [`parse_dir`].
+pub fn parse_dir_with_options(
+    root: &Path,
+    options: &ParseOptions,
+) -> Result<ParseDirResult, ParseDirError> {
+    if !root.exists() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file or directory");
+        return Err(ParseDirError::Io(root.to_path_buf(), err));
+    }
+    if !root.is_dir() {
+        return Err(ParseDirError::NotADirectory(root.to_path_buf()));
+    }
+
+    let mut go_files = Vec::new();
+    collect_go_files(root, &mut go_files)?;
+    go_files.sort();
+
+    let mut files = Vec::with_capacity(go_files.len());
+    for path in go_files {
+        let source = fs::read_to_string(&path).map_err(|e| ParseDirError::Io(path.clone(), e))?;
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        files.push((rel, source));
+    }
+
+    parse_file_contents(files, options)
+}
+
+/// Parses a set of in-memory `(relative path, source)` pairs into one
+/// [`ParseDirResult`], grouping files into packages by directory exactly
+/// as [`parse_dir_with_options`] does for files read from disk — this is
+/// that function's shared per-file filtering and package-grouping logic,
+/// factored out so a caller with sources from somewhere other than the
+/// filesystem (a Git blob, an in-memory archive, ...) doesn't have to
+/// duplicate it. `path` need not exist on disk; it's used the same way a
+/// real file's relative path is: as the key into its package's `files`
+/// map and to derive that package's directory.
+pub fn parse_file_contents(
+    files: Vec<(PathBuf, String)>,
+    options: &ParseOptions,
+) -> Result<ParseDirResult, ParseDirError> {
+    let mut objects = AstObjects::new();
+    let mut file_set = FileSet::new();
+    let errors = ErrorList::new();
+    let mut packages: BTreeMap<String, Package> = BTreeMap::new();
+    let mut warnings = Vec::new();
+
+    for (rel, source) in files {
+        let source = normalize_source(&source);
+        if !build_constraints::file_applies(&source, options) {
+            continue;
+        }
+        if options.exclude_generated && has_generated_header(&source) {
+            continue;
+        }
+        if !options.include_test_files && is_test_file(&rel) {
+            continue;
+        }
+        let path = rel.clone();
+        let file_name = rel.to_string_lossy().into_owned();
+
+        let base = file_set.base();
+        // Parse a type-parameter-free view of the source: the matching
+        // `Pos`s still line up with `source` since the rewrite only ever
+        // blanks characters out, never shifts them. See `generics`.
+        let parseable = strip_type_params(&source);
+        let errors_before = errors.len();
+        let (_, parsed) =
+            parse_file(&mut objects, &mut file_set, &errors, &file_name, &parseable, false);
+
+        if options.fail_on_parse_error && (errors.len() > errors_before || parsed.is_none()) {
+            let messages = errors
+                .borrow()
+                .iter()
+                .skip(errors_before)
+                .map(|e| e.to_string())
+                .collect::<String>();
+            let messages = if messages.is_empty() {
+                "the parser produced no result".to_string()
+            } else {
+                messages
+            };
+            return Err(ParseDirError::Syntax(path, messages));
+        }
+
+        let Some(ast_file) = parsed else {
+            continue;
+        };
+
+        let func_count = ast_file.decls.iter().filter(|d| matches!(d, ast::Decl::Func(_))).count();
+        if let Some(max) = options.max_functions_per_file
+            && func_count > max
+        {
+            warnings.push(format!(
+                "{file_name}: skipped, {func_count} functions exceeds the configured limit of {max}"
+            ));
+            continue;
+        }
+
+        let pkg_name = objects.idents[ast_file.name].name.clone();
+        let dir_key = rel
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let pkg = packages.entry(dir_key).or_insert_with(|| Package {
+            name: pkg_name,
+            files: BTreeMap::new(),
+        });
+        pkg.files.insert(
+            file_name,
+            ParsedFile {
+                path,
+                source,
+                base,
+                ast: ast_file,
+            },
+        );
+    }
+
+    Ok(ParseDirResult {
+        objects,
+        file_set,
+        packages,
+        warnings,
+    })
+}
+
+/// Re-parses a subset of files from a previous [`parse_dir`] run, reusing
+/// the rest without touching disk again. Meant for editor integrations,
+/// where re-parsing (and re-building CFGs for) an entire project on every
+/// keystroke is too slow.
+///
+/// `objects`/`file_set` are shared, append-only arenas: there's no way to
+/// evict one file's nodes from a `slotmap`-backed arena without
+/// invalidating every `Pos`/`IdentKey` anyone still holds for the rest of
+/// the project, so a changed file is parsed into *new* arena slots rather
+/// than having its old ones reused in place. The file's old slots become
+/// unreachable garbage inside `objects`/`file_set` until the project is
+/// parsed from scratch again with `parse_dir` — acceptable for an
+/// edit-save-edit loop, but a process that calls this repeatedly without
+/// ever calling `parse_dir` again will see those arenas grow unbounded.
+///
+/// Only files that already exist on disk are handled; a `changed` path
+/// for a file that has been deleted is silently skipped, same as
+/// `parse_dir`'s handling of files that fail to parse. A changed file
+/// whose build constraints (see [`build_constraints::file_applies`]) no
+/// longer match the default target is removed from its package instead
+/// of being (re)inserted, matching `parse_dir`'s own exclusion of such
+/// files.
+pub fn reparse_files(
+    root: &Path,
+    mut previous: ParseDirResult,
+    changed: &[PathBuf],
+) -> Result<ParseDirResult, ParseDirError> {
+    for path in changed {
+        if !path.exists() {
+            continue;
+        }
+        let source = fs::read_to_string(path).map_err(|e| ParseDirError::Io(path.clone(), e))?;
+        let source = normalize_source(&source);
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        let file_name = rel.to_string_lossy().into_owned();
+        let dir_key = rel
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        if !build_constraints::file_applies(&source, &ParseOptions::default()) {
+            if let Some(pkg) = previous.packages.get_mut(&dir_key) {
+                pkg.files.remove(&file_name);
+            }
+            continue;
+        }
+
+        let errors = ErrorList::new();
+        let base = previous.file_set.base();
+        let parseable = strip_type_params(&source);
+        let (_, parsed) = parse_file(
+            &mut previous.objects,
+            &mut previous.file_set,
+            &errors,
+            &file_name,
+            &parseable,
+            false,
+        );
+        let Some(ast_file) = parsed else { continue };
+        let pkg_name = previous.objects.idents[ast_file.name].name.clone();
+
+        let pkg = previous
+            .packages
+            .entry(dir_key)
+            .or_insert_with(|| Package {
+                name: pkg_name,
+                files: BTreeMap::new(),
+            });
+        pkg.files.insert(
+            file_name,
+            ParsedFile {
+                path: path.clone(),
+                source,
+                base,
+                ast: ast_file,
+            },
+        );
+    }
+
+    Ok(previous)
+}
+
+/// Parses a single in-memory source string as a one-file package, never
+/// touching the filesystem — for stdin input and similar "analyze this
+/// snippet" use cases where there's no project directory to walk.
+/// `file_name` is a synthetic name (it doesn't need to exist on disk)
+/// used the same way a real file's relative path would be: as the key
+/// into the resulting package's `files` map and in any location this
+/// result's positions get reported at.
+///
+/// Like [`parse_dir`]'s own default, the parser's error recovery is
+/// trusted: a file with syntax errors it could still recover an AST from
+/// is kept, errors and all. Only a file the parser couldn't produce any
+/// AST for at all is reported as [`ParseDirError::Syntax`].
+pub fn parse_source(file_name: &str, source: &str) -> Result<ParseDirResult, ParseDirError> {
+    let mut objects = AstObjects::new();
+    let mut file_set = FileSet::new();
+    let errors = ErrorList::new();
+
+    let source = normalize_source(source);
+    let base = file_set.base();
+    let parseable = strip_type_params(&source);
+    let (_, parsed) =
+        parse_file(&mut objects, &mut file_set, &errors, file_name, &parseable, false);
+
+    let Some(ast_file) = parsed else {
+        let messages = errors.borrow().iter().map(|e| e.to_string()).collect::<String>();
+        let messages = if messages.is_empty() {
+            "the parser produced no result".to_string()
+        } else {
+            messages
+        };
+        return Err(ParseDirError::Syntax(PathBuf::from(file_name), messages));
+    };
+
+    let pkg_name = objects.idents[ast_file.name].name.clone();
+    let mut packages = BTreeMap::new();
+    packages.insert(
+        String::new(),
+        Package {
+            name: pkg_name,
+            files: BTreeMap::from([(
+                file_name.to_string(),
+                ParsedFile {
+                    path: PathBuf::from(file_name),
+                    source,
+                    base,
+                    ast: ast_file,
+                },
+            )]),
+        },
+    );
+
+    Ok(ParseDirResult { objects, file_set, packages, warnings: Vec::new() })
+}
+
+/// A single file handed to [`parse_dir_streaming`]'s callback: the same
+/// shape as [`ParsedFile`], paired with its own private `AstObjects` —
+/// not shared with any other file — so it (and the memory behind it) can
+/// be dropped the moment the callback returns.
+pub struct StreamedFile {
+    pub package: String,
+    pub file: ParsedFile,
+    pub objects: AstObjects,
+}
+
+/// Parses every `.go` file under `root` one at a time, handing each to
+/// `on_file` and dropping it (along with the small per-file `AstObjects`/
+/// `FileSet` it was parsed into) before moving to the next, so memory
+/// stays bounded by the largest single file rather than the whole
+/// project.
+///
+/// This trades away `parse_dir`'s shared arenas — what lets `imports`
+/// and friends resolve identifiers across files — for the ability to
+/// stream arbitrarily large trees. Callers that need cross-file analysis
+/// should use `parse_dir`; callers doing purely per-file work (CFG,
+/// complexity, SLOC) can use this to avoid holding the whole project in
+/// memory at once.
+///
+/// Applies [`ParseOptions::default`]'s file-level exclusions (build
+/// constraints, generated files, `_test.go` files) the same way
+/// `parse_dir` does, so a streamed result matches the eager one for the
+/// same tree.
+pub fn parse_dir_streaming(
+    root: &Path,
+    mut on_file: impl FnMut(StreamedFile),
+) -> Result<(), ParseDirError> {
+    let options = ParseOptions::default();
+    let mut go_files = Vec::new();
+    collect_go_files(root, &mut go_files)?;
+    go_files.sort();
+
+    for path in go_files {
+        let source = fs::read_to_string(&path).map_err(|e| ParseDirError::Io(path.clone(), e))?;
+        let source = normalize_source(&source);
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        if !build_constraints::file_applies(&source, &options) {
+            continue;
+        }
+        if options.exclude_generated && has_generated_header(&source) {
+            continue;
+        }
+        if !options.include_test_files && is_test_file(rel) {
+            continue;
+        }
+        let file_name = rel.to_string_lossy().into_owned();
+
+        let mut objects = AstObjects::new();
+        let mut file_set = FileSet::new();
+        let errors = ErrorList::new();
+        let base = file_set.base();
+        let parseable = strip_type_params(&source);
+        let (_, parsed) =
+            parse_file(&mut objects, &mut file_set, &errors, &file_name, &parseable, false);
+        let Some(ast_file) = parsed else {
+            continue;
+        };
+        let package = objects.idents[ast_file.name].name.clone();
+
+        on_file(StreamedFile {
+            package,
+            file: ParsedFile {
+                path,
+                source,
+                base,
+                ast: ast_file,
+            },
+            objects,
+        });
+    }
+
+    Ok(())
+}
+
+/// Narrows an already-parsed project down to a single package, identified
+/// by `selector` matched first against a package's directory (its import
+/// path relative to the parsed root, the empty string for the root
+/// itself) and, failing that, against its declared package name.
+///
+/// Matching the directory first means an exact import path is never
+/// ambiguous; matching the name is a convenience for the common case
+/// where only one package in the project is called that. Returns an
+/// error naming every directory that shares the name if there's more
+/// than one, so the caller knows to pass the import path instead.
+pub fn select_package<'a>(
+    parsed: &'a ParseDirResult,
+    selector: &str,
+) -> Result<&'a str, String> {
+    if let Some((dir, _)) = parsed.packages.get_key_value(selector) {
+        return Ok(dir.as_str());
+    }
+    let matches: Vec<&str> = parsed
+        .packages
+        .iter()
+        .filter(|(_, pkg)| pkg.name == selector)
+        .map(|(dir, _)| dir.as_str())
+        .collect();
+    match matches.as_slice() {
+        [dir] => Ok(dir),
+        [] => Err(format!(
+            "no package matches `{selector}` by import path or package name"
+        )),
+        _ => Err(format!(
+            "`{selector}` matches more than one package's name ({}); pass the import path instead",
+            matches.join(", ")
+        )),
+    }
+}
+
+/// Drops every package from `parsed` except the one `selector` resolves
+/// to (see [`select_package`]), leaving `objects`/`file_set` untouched so
+/// positions in the surviving package's files still resolve correctly.
+pub fn filter_to_package(
+    mut parsed: ParseDirResult,
+    selector: &str,
+) -> Result<ParseDirResult, String> {
+    let dir = select_package(&parsed, selector)?.to_string();
+    parsed.packages.retain(|pkg_dir, _| *pkg_dir == dir);
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go_parser::line_of;
+    use goscript_parser::ast::Node;
+
+    fn write(dir: &Path, name: &str, src: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, src).unwrap();
+        path
+    }
+
+    #[test]
+    fn reparse_only_touches_the_changed_file() {
+        let dir = std::env::temp_dir().join(format!("skanujkod-reparse-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(&dir, "a.go", "package main\nfunc a() {\n\tx := 1\n}\n");
+        write(&dir, "b.go", "package main\nfunc b() {\n\ty := 2\n}\n");
+
+        let parsed = parse_dir(&dir).unwrap();
+        let a_cfgs_before = crate::cfg_plugin::build_cfgs_for_file(
+            &parsed.packages[""].files["a.go"].ast,
+            &parsed.objects,
+        );
+        let b_cfgs_before = crate::cfg_plugin::build_cfgs_for_file(
+            &parsed.packages[""].files["b.go"].ast,
+            &parsed.objects,
+        );
+
+        let changed_path = write(&dir, "a.go", "package main\nfunc a() {\n\tx := 99\n}\n");
+        let reparsed = reparse_files(&dir, parsed, &[changed_path]).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let a_cfgs_after = crate::cfg_plugin::build_cfgs_for_file(
+            &reparsed.packages[""].files["a.go"].ast,
+            &reparsed.objects,
+        );
+        let b_cfgs_after = crate::cfg_plugin::build_cfgs_for_file(
+            &reparsed.packages[""].files["b.go"].ast,
+            &reparsed.objects,
+        );
+
+        let a_text_before = &a_cfgs_before["a"].blocks[a_cfgs_before["a"].entry].statements[0].text;
+        let a_text_after = &a_cfgs_after["a"].blocks[a_cfgs_after["a"].entry].statements[0].text;
+        assert_ne!(a_text_before, a_text_after);
+        assert_eq!(a_text_after, "x := 99");
+
+        let b_text_before = &b_cfgs_before["b"].blocks[b_cfgs_before["b"].entry].statements[0].text;
+        let b_text_after = &b_cfgs_after["b"].blocks[b_cfgs_after["b"].entry].statements[0].text;
+        assert_eq!(b_text_before, b_text_after);
+    }
+
+    #[test]
+    fn reparsing_a_file_that_no_longer_satisfies_its_build_constraint_drops_it() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-reparse-build-constraint-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(&dir, "a.go", "package main\nfunc a() {\n\tx := 1\n}\n");
+
+        let parsed = parse_dir(&dir).unwrap();
+        assert!(parsed.packages[""].files.contains_key("a.go"));
+
+        let changed_path =
+            write(&dir, "a.go", "//go:build windows\n\npackage main\nfunc a() {\n\tx := 1\n}\n");
+        let reparsed = reparse_files(&dir, parsed, &[changed_path]).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(!reparsed.packages[""].files.contains_key("a.go"));
+    }
+
+    #[test]
+    fn a_changed_path_for_a_file_deleted_since_the_previous_parse_is_silently_skipped() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-reparse-deleted-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let a_path = write(&dir, "a.go", "package main\nfunc a() {\n\tx := 1\n}\n");
+
+        let parsed = parse_dir(&dir).unwrap();
+        assert!(parsed.packages[""].files.contains_key("a.go"));
+
+        fs::remove_file(&a_path).unwrap();
+        let reparsed = reparse_files(&dir, parsed, &[a_path]).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(reparsed.packages[""].files.contains_key("a.go"));
+    }
+
+    #[test]
+    fn only_the_file_matching_the_target_platform_is_parsed() {
+        let dir = std::env::temp_dir().join(format!("skanujkod-build-tags-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "windows_only.go",
+            "//go:build windows\n\npackage main\nfunc OnWindows() {}\n",
+        );
+        write(
+            &dir,
+            "linux_only.go",
+            "//go:build linux\n\npackage main\nfunc OnLinux() {}\n",
+        );
+
+        let options = ParseOptions {
+            goos: "linux".to_string(),
+            goarch: "amd64".to_string(),
+            ..ParseOptions::default()
+        };
+        let parsed = parse_dir_with_options(&dir, &options).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let files = &parsed.packages[""].files;
+        assert!(files.contains_key("linux_only.go"));
+        assert!(!files.contains_key("windows_only.go"));
+    }
+
+    #[test]
+    fn a_generated_file_is_excluded_by_default_but_included_when_the_option_is_disabled() {
+        let dir = std::env::temp_dir().join(format!("skanujkod-generated-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "generated.go",
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\n\npackage main\nfunc Generated() {}\n",
+        );
+        write(&dir, "handwritten.go", "package main\nfunc Handwritten() {}\n");
+
+        let default_parsed = parse_dir(&dir).unwrap();
+        let files = &default_parsed.packages[""].files;
+        assert!(!files.contains_key("generated.go"));
+        assert!(files.contains_key("handwritten.go"));
+
+        let options = ParseOptions {
+            exclude_generated: false,
+            ..ParseOptions::default()
+        };
+        let included = parse_dir_with_options(&dir, &options).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let files = &included.packages[""].files;
+        assert!(files.contains_key("generated.go"));
+        assert!(files.contains_key("handwritten.go"));
+    }
+
+    #[test]
+    fn a_file_over_the_function_count_limit_is_skipped_with_a_warning() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-max-functions-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut huge = String::from("package main\n\n");
+        for i in 0..50 {
+            huge.push_str(&format!("func f{i}() {{}}\n"));
+        }
+        write(&dir, "huge.go", &huge);
+        write(&dir, "small.go", "package main\nfunc Small() {}\n");
+
+        let options = ParseOptions { max_functions_per_file: Some(10), ..ParseOptions::default() };
+        let parsed = parse_dir_with_options(&dir, &options).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let files = &parsed.packages[""].files;
+        assert!(!files.contains_key("huge.go"));
+        assert!(files.contains_key("small.go"));
+        assert_eq!(parsed.warnings.len(), 1);
+        assert!(parsed.warnings[0].contains("huge.go"));
+    }
+
+    #[test]
+    fn a_test_file_is_excluded_by_default_but_included_when_the_option_is_set() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-include-test-files-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(&dir, "a.go", "package main\nfunc Handwritten() {}\n");
+        write(&dir, "a_test.go", "package main\nfunc TestHandwritten() {}\n");
+
+        let default_parsed = parse_dir(&dir).unwrap();
+        let files = &default_parsed.packages[""].files;
+        assert!(files.contains_key("a.go"));
+        assert!(!files.contains_key("a_test.go"));
+
+        let options = ParseOptions { include_test_files: true, ..ParseOptions::default() };
+        let with_tests = parse_dir_with_options(&dir, &options).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let files = &with_tests.packages[""].files;
+        assert!(files.contains_key("a.go"));
+        assert!(files.contains_key("a_test.go"));
+        assert_eq!(func_names(&files["a_test.go"].ast, &with_tests.objects), vec!["TestHandwritten"]);
+    }
+
+    #[test]
+    fn a_nonexistent_path_is_reported_as_an_io_error() {
+        let dir = std::env::temp_dir().join(format!("skanujkod-missing-path-test-{}", std::process::id()));
+        // Deliberately not created.
+
+        let result = parse_dir(&dir);
+        assert!(matches!(result, Err(ParseDirError::Io(_, _))));
+    }
+
+    #[test]
+    fn a_file_given_instead_of_a_directory_is_reported_distinctly() {
+        let dir = std::env::temp_dir().join(format!("skanujkod-not-a-dir-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = write(&dir, "a.go", "package main\n");
+
+        let result = parse_dir(&file);
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(matches!(result, Err(ParseDirError::NotADirectory(path)) if path == file));
+    }
+
+    #[test]
+    fn fail_on_parse_error_rejects_a_file_with_a_syntax_error() {
+        let dir = std::env::temp_dir().join(format!("skanujkod-parse-error-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(&dir, "broken.go", "package main\nfunc f( {\n");
+
+        // The lenient default tolerates the syntax error (the parser
+        // recovers and the file is kept, errors and all).
+        assert!(parse_dir(&dir).is_ok());
+
+        let options = ParseOptions {
+            fail_on_parse_error: true,
+            ..ParseOptions::default()
+        };
+        let strict = parse_dir_with_options(&dir, &options);
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(matches!(strict, Err(ParseDirError::Syntax(_, _))));
+    }
+
+    #[test]
+    fn generic_function_parses_and_builds_a_cfg() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-generics-func-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package main\nfunc Max[T int | float64](a, b T) T {\n\tif a > b {\n\t\treturn a\n\t}\n\treturn b\n}\n",
+        );
+
+        let parsed = parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let pf = &parsed.packages[""].files["a.go"];
+        let cfgs = crate::cfg_plugin::build_cfgs_for_file(&pf.ast, &parsed.objects);
+        assert!(cfgs.contains_key("Max"));
+    }
+
+    #[test]
+    fn generic_type_declaration_parses() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-generics-type-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package main\ntype Pair[K, V any] struct {\n\tKey K\n\tVal V\n}\nfunc F() {\n\tx := 1\n}\n",
+        );
+
+        let parsed = parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let pf = &parsed.packages[""].files["a.go"];
+        let cfgs = crate::cfg_plugin::build_cfgs_for_file(&pf.ast, &parsed.objects);
+        assert!(cfgs.contains_key("F"));
+    }
+
+    fn func_names(ast: &ast::File, objects: &AstObjects) -> Vec<String> {
+        ast.decls
+            .iter()
+            .filter_map(|d| match d {
+                ast::Decl::Func(key) => Some(objects.idents[objects.fdecls[*key].name].name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn streaming_invokes_callback_once_per_file_and_matches_eager_results() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-streaming-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(&dir, "a.go", "package main\nfunc a() {\n\tx := 1\n}\n");
+        write(&dir, "b.go", "package main\nfunc b() {\n\ty := 2\n}\n");
+
+        let eager = parse_dir(&dir).unwrap();
+        let mut eager_by_file: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for pkg in eager.packages.values() {
+            for (name, pf) in &pkg.files {
+                eager_by_file.insert(name.clone(), func_names(&pf.ast, &eager.objects));
+            }
+        }
+
+        let mut streamed_by_file: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut call_count = 0;
+        super::parse_dir_streaming(&dir, |streamed| {
+            call_count += 1;
+            let name = streamed.file.path.strip_prefix(&dir).unwrap().to_string_lossy().into_owned();
+            streamed_by_file.insert(name, func_names(&streamed.file.ast, &streamed.objects));
+        })
+        .unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(call_count, 2);
+        assert_eq!(streamed_by_file, eager_by_file);
+    }
+
+    #[test]
+    fn streaming_applies_the_same_file_level_exclusions_as_parse_dir() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-streaming-exclusions-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(&dir, "a.go", "package main\nfunc a() {\n\tx := 1\n}\n");
+        write(&dir, "a_test.go", "package main\nfunc TestA() {\n\ty := 2\n}\n");
+
+        let eager = parse_dir(&dir).unwrap();
+        let mut eager_files: Vec<String> = eager
+            .packages
+            .values()
+            .flat_map(|pkg| pkg.files.keys().cloned())
+            .collect();
+        eager_files.sort();
+
+        let mut streamed_files: Vec<String> = Vec::new();
+        super::parse_dir_streaming(&dir, |streamed| {
+            streamed_files.push(streamed.file.path.strip_prefix(&dir).unwrap().to_string_lossy().into_owned());
+        })
+        .unwrap();
+        streamed_files.sort();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(streamed_files, eager_files);
+        assert_eq!(streamed_files, vec!["a.go".to_string()]);
+    }
+
+    #[test]
+    fn parse_source_builds_a_one_file_package_without_touching_disk() {
+        let parsed =
+            parse_source("stdin.go", "package main\nfunc f() {\n\tx := 1\n\t_ = x\n}\n").unwrap();
+
+        let pkg = parsed.packages.values().next().unwrap();
+        assert_eq!(pkg.name, "main");
+        let pf = pkg.files.get("stdin.go").unwrap();
+        assert_eq!(func_names(&pf.ast, &parsed.objects), vec!["f"]);
+    }
+
+    #[test]
+    fn parse_source_reports_a_syntax_error_when_no_ast_could_be_recovered_at_all() {
+        let result = parse_source("stdin.go", "this is not go code at all {{{");
+        assert!(matches!(result, Err(ParseDirError::Syntax(_, _))));
+    }
+
+    #[test]
+    fn parse_source_is_lenient_when_the_parser_can_still_recover_an_ast() {
+        // Same recoverable-error leniency parse_dir_with_options gives a
+        // project by default (see fail_on_parse_error_rejects_a_file_with_a_syntax_error
+        // above) — a snippet with a syntax error the parser can still
+        // recover from is kept rather than rejected, since parse_source
+        // has no fail_on_parse_error knob of its own to opt into strictness.
+        let parsed = parse_source("stdin.go", "package main\nfunc f( {\n\treturn\n}\n").unwrap();
+        let pkg = parsed.packages.values().next().unwrap();
+        assert!(pkg.files.contains_key("stdin.go"));
+    }
+
+    #[test]
+    fn select_package_resolves_by_import_path_or_unambiguous_name() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-select-package-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("widgets")).unwrap();
+        fs::create_dir_all(dir.join("gadgets")).unwrap();
+        write(&dir, "root.go", "package main\nfunc Main() {}\n");
+        write(&dir.join("widgets"), "widgets.go", "package widgets\nfunc New() {}\n");
+        write(&dir.join("gadgets"), "gadgets.go", "package gadgets\nfunc New() {}\n");
+
+        let parsed = parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(select_package(&parsed, "widgets").unwrap(), "widgets");
+        assert_eq!(select_package(&parsed, "gadgets").unwrap(), "gadgets");
+        assert_eq!(select_package(&parsed, "").unwrap(), "");
+        assert!(select_package(&parsed, "nope").is_err());
+    }
+
+    #[test]
+    fn a_leading_bom_and_crlf_line_endings_dont_shift_reported_line_numbers() {
+        let plain = "package main\nfunc f() {\n\tx := 1\n\t_ = x\n}\n";
+        let with_bom_and_crlf =
+            "\u{feff}package main\r\nfunc f() {\r\n\tx := 1\r\n\t_ = x\r\n}\r\n";
+
+        let plain_parsed = parse_source("a.go", plain).unwrap();
+        let messy_parsed = parse_source("a.go", with_bom_and_crlf).unwrap();
+
+        let plain_pf = &plain_parsed.packages[""].files["a.go"];
+        let messy_pf = &messy_parsed.packages[""].files["a.go"];
+
+        let plain_cfgs = crate::cfg_plugin::build_cfgs_for_file(&plain_pf.ast, &plain_parsed.objects);
+        let messy_cfgs = crate::cfg_plugin::build_cfgs_for_file(&messy_pf.ast, &messy_parsed.objects);
+
+        let plain_stmt = &plain_cfgs["f"].blocks[plain_cfgs["f"].entry].statements[0].stmt;
+        let messy_stmt = &messy_cfgs["f"].blocks[messy_cfgs["f"].entry].statements[0].stmt;
+
+        let plain_line =
+            line_of(&plain_pf.source, plain_pf.base, plain_stmt.pos(&plain_parsed.objects));
+        let messy_line =
+            line_of(&messy_pf.source, messy_pf.base, messy_stmt.pos(&messy_parsed.objects));
+
+        assert_eq!(plain_line, messy_line);
+        assert!(!messy_pf.source.contains('\u{feff}'));
+        assert!(!messy_pf.source.contains('\r'));
+    }
+
+    #[test]
+    fn filter_to_package_keeps_only_the_selected_packages_functions() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-filter-to-package-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("widgets")).unwrap();
+        write(&dir, "root.go", "package main\nfunc Main() {}\n");
+        write(&dir.join("widgets"), "widgets.go", "package widgets\nfunc New() {}\n");
+
+        let parsed = parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let filtered = filter_to_package(parsed, "widgets").unwrap();
+        assert_eq!(filtered.packages.len(), 1);
+        let pkg = filtered.packages.get("widgets").unwrap();
+        let file_name = pkg.files.keys().next().unwrap();
+        assert_eq!(func_names(&pkg.files[file_name].ast, &filtered.objects), vec!["New"]);
+    }
+}
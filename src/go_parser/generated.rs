@@ -0,0 +1,59 @@
+//! Detecting Go's "generated file" convention, so a directory parse can
+//! optionally skip generated code the same way it already skips files
+//! that don't match the target platform (see `build_constraints`).
+//!
+//! Go tooling marks a generated file with a comment line of the exact
+//! shape `// Code generated <tool> DO NOT EDIT.` near the top of the
+//! file — `gofmt`, `golint`, and friends all key off that same line to
+//! recognize generated code rather than guessing from the file name.
+
+/// Whether `source`'s leading comment block (the same run of `//` lines
+/// [`super::build_constraints::file_applies`] scans for build tags)
+/// contains a line of the shape `// Code generated <anything> DO NOT
+/// EDIT.`.
+pub fn has_generated_header(source: &str) -> bool {
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if !trimmed.starts_with("//") {
+            break;
+        }
+        if let Some(rest) = trimmed.strip_prefix("// Code generated ")
+            && rest.ends_with(" DO NOT EDIT.")
+        {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_the_standard_generated_header() {
+        let src = "// Code generated by protoc-gen-go. DO NOT EDIT.\n\npackage pb\n";
+        assert!(has_generated_header(src));
+    }
+
+    #[test]
+    fn ignores_a_header_that_doesnt_end_with_do_not_edit() {
+        let src = "// Code generated by hand, please review.\n\npackage main\n";
+        assert!(!has_generated_header(src));
+    }
+
+    #[test]
+    fn ignores_a_plain_file_with_no_generated_header() {
+        let src = "// Package main does a thing.\npackage main\n";
+        assert!(!has_generated_header(src));
+    }
+
+    #[test]
+    fn only_looks_at_the_leading_comment_block() {
+        let src = "package main\n\n// Code generated by hand. DO NOT EDIT.\nfunc f() {}\n";
+        assert!(!has_generated_header(src));
+    }
+}
@@ -0,0 +1,244 @@
+//! Works around the ported parser's lack of support for Go's type
+//! parameter lists (`func F[T any](x T) T`, `type Pair[K, V any] struct`).
+//!
+//! `goscript_parser` is a port of Go 1.12's `go/parser`, which predates
+//! generics entirely: it has no concept of a type parameter list on
+//! `FuncType`/`TypeSpec`, and feeding it one produces a run of parser
+//! errors as it tries (and fails) to make sense of `[T any]` as an index
+//! expression. Rather than fork the vendored parser to add a feature it
+//! was never designed for, this strips type parameter lists out of the
+//! source before handing it to the parser, so a generic declaration's
+//! *body* still parses cleanly — at the cost of losing the type
+//! parameters themselves, which isn't something this framework's
+//! analyses (CFG, complexity, ...) look at today.
+//!
+//! This is a best-effort, token-level heuristic rather than a real
+//! grammar change, and it has to disambiguate type parameter lists from
+//! array types (`type Matrix [4][4]int` is legal Go with no generics
+//! involved). The rule of thumb used here: a bracketed list right after a
+//! `func`/`type` name counts as type parameters only if it contains two
+//! or more identifiers (a name plus its constraint, e.g. `T any`) — an
+//! array length is a single constant expression, which has at most one.
+//! That misses qualified consts like `[pkg.N]byte`, a known limitation.
+
+use std::collections::BTreeSet;
+
+use super::scanner::tokenize;
+use super::token::Token;
+
+/// Replaces each top-level type parameter list after a `func`/`type`
+/// declaration's name with spaces, preserving every other byte's offset
+/// so downstream `Pos`-to-source-text mapping stays correct.
+pub fn strip_type_params(src: &str) -> String {
+    let mut chars: Vec<char> = src.chars().collect();
+    let tokens: Vec<(usize, Token)> = tokenize(src).map(|(pos, tok, _)| (pos, tok)).collect();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if matches!(tokens[i].1, Token::FUNC | Token::TYPE) {
+            let mut j = i + 1;
+            // Skip an optional method receiver (`func (p Pair[K, V]) Swap()`),
+            // stripping the receiver type's own type argument list along the
+            // way — a method on a generic type instantiates it there, not
+            // after the method name, which never has type parameters of its
+            // own.
+            if matches!(tokens[i].1, Token::FUNC)
+                && matches!(tokens.get(j), Some((_, Token::LPAREN)))
+                && let Some(rparen_idx) = matching_rparen_index(&tokens, j)
+            {
+                if let Some(lbrack_idx) = (j + 1..rparen_idx).find(|&k| matches!(tokens[k].1, Token::LBRACK))
+                    && let Some(rbrack_idx) = matching_rbrack_index(&tokens, lbrack_idx)
+                    && looks_like_type_params(&tokens[lbrack_idx + 1..rbrack_idx])
+                {
+                    let start = tokens[lbrack_idx].0;
+                    let end = tokens[rbrack_idx].0 + 1;
+                    blank_out(&mut chars, start, end);
+                }
+                j = rparen_idx + 1;
+            }
+            while j < tokens.len() && !matches!(tokens[j].1, Token::IDENT(_)) {
+                j += 1;
+            }
+            let name_idx = j;
+            if name_idx < tokens.len()
+                && matches!(tokens.get(name_idx + 1), Some((_, Token::LBRACK)))
+                && let Some(rbrack_idx) = matching_rbrack_index(&tokens, name_idx + 1)
+                && looks_like_type_params(&tokens[name_idx + 2..rbrack_idx])
+            {
+                let start = tokens[name_idx + 1].0;
+                let end = tokens[rbrack_idx].0 + 1;
+                blank_out(&mut chars, start, end);
+            }
+        }
+        i += 1;
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Given the token index of a `(`, finds the token index of its matching
+/// `)`, tracking nested parens.
+fn matching_rparen_index(tokens: &[(usize, Token)], lparen_idx: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, (_, tok)) in tokens.iter().enumerate().skip(lparen_idx) {
+        match tok {
+            Token::LPAREN => depth += 1,
+            Token::RPAREN => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The name of every top-level function in `src` whose declaration has a
+/// type parameter list, by the same heuristic [`strip_type_params`] uses
+/// to blank one out. Exists because that blanking is exactly what makes
+/// generics invisible everywhere else in this crate: the parser (and so
+/// every AST-based analysis) never sees a function's type parameters at
+/// all, so [`crate::features`] has nowhere else to look for them but the
+/// original, unstripped source text.
+pub fn functions_with_type_params(src: &str) -> BTreeSet<String> {
+    let tokens: Vec<(usize, Token, String)> = tokenize(src).collect();
+    let mut names = BTreeSet::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if matches!(tokens[i].1, Token::FUNC) {
+            let mut j = i + 1;
+            while j < tokens.len() && !matches!(tokens[j].1, Token::IDENT(_)) {
+                j += 1;
+            }
+            let name_idx = j;
+            if name_idx < tokens.len()
+                && matches!(tokens.get(name_idx + 1), Some((_, Token::LBRACK, _)))
+                && let Some(rbrack_idx) = matching_rbrack_index_with_text(&tokens, name_idx + 1)
+                && looks_like_type_params_with_text(&tokens[name_idx + 2..rbrack_idx])
+            {
+                names.insert(tokens[name_idx].2.clone());
+            }
+        }
+        i += 1;
+    }
+
+    names
+}
+
+/// [`matching_rbrack_index`], over the `(pos, token, text)` triples
+/// [`functions_with_type_params`] needs the function name's text from.
+fn matching_rbrack_index_with_text(tokens: &[(usize, Token, String)], lbrack_idx: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, (_, tok, _)) in tokens.iter().enumerate().skip(lbrack_idx) {
+        match tok {
+            Token::LBRACK => depth += 1,
+            Token::RBRACK => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// [`looks_like_type_params`], over the `(pos, token, text)` triples
+/// [`functions_with_type_params`] needs the function name's text from.
+fn looks_like_type_params_with_text(inner: &[(usize, Token, String)]) -> bool {
+    inner
+        .iter()
+        .filter(|(_, tok, _)| matches!(tok, Token::IDENT(_)))
+        .count()
+        >= 2
+}
+
+/// Given the token index of a `[`, finds the token index of its matching
+/// `]`, tracking nested brackets (e.g. `[K, V []int]`).
+fn matching_rbrack_index(tokens: &[(usize, Token)], lbrack_idx: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, (_, tok)) in tokens.iter().enumerate().skip(lbrack_idx) {
+        match tok {
+            Token::LBRACK => depth += 1,
+            Token::RBRACK => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A type parameter list has a name plus a constraint for each parameter
+/// (`T any`, `K, V any`), so it has at least two identifiers; an array
+/// length is a single constant expression, which has at most one.
+fn looks_like_type_params(inner: &[(usize, Token)]) -> bool {
+    inner
+        .iter()
+        .filter(|(_, tok)| matches!(tok, Token::IDENT(_)))
+        .count()
+        >= 2
+}
+
+/// Overwrites `chars[start..end]` with spaces, except for `\n` which is
+/// kept so line numbers in parser error messages don't shift.
+fn blank_out(chars: &mut [char], start: usize, end: usize) {
+    for c in &mut chars[start..end] {
+        if *c != '\n' {
+            *c = ' ';
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blanks_a_generic_function_type_parameter_list() {
+        let src = "package main\nfunc F[T any](x T) T {\n\treturn x\n}\n";
+        let stripped = strip_type_params(src);
+        assert!(!stripped.contains('['));
+        assert!(stripped.contains("func F"));
+        assert!(stripped.contains("(x T) T"));
+        assert_eq!(stripped.lines().count(), src.lines().count());
+    }
+
+    #[test]
+    fn blanks_a_generic_type_parameter_list() {
+        let src = "package main\ntype Pair[K, V any] struct {\n\tKey K\n\tVal V\n}\n";
+        let stripped = strip_type_params(src);
+        assert!(!stripped.contains('['));
+        assert!(stripped.contains("type Pair"));
+        assert!(stripped.contains("struct {"));
+    }
+
+    #[test]
+    fn leaves_non_generic_declarations_untouched() {
+        let src = "package main\nfunc F(x int) int {\n\treturn x\n}\n";
+        assert_eq!(strip_type_params(src), src);
+    }
+
+    #[test]
+    fn leaves_array_types_untouched() {
+        let src = "package main\ntype Matrix [4][4]int\n";
+        assert_eq!(strip_type_params(src), src);
+    }
+
+    #[test]
+    fn blanks_a_generic_types_type_arguments_in_a_method_receiver() {
+        let src = "package main\ntype Pair[K, V any] struct {\n\tKey K\n\tVal V\n}\nfunc (p Pair[K, V]) Swap() {\n}\n";
+        let stripped = strip_type_params(src);
+        assert!(!stripped.contains('['));
+        assert!(stripped.contains("func (p Pair"));
+        assert!(stripped.contains(") Swap()"));
+        assert_eq!(stripped.lines().count(), src.lines().count());
+    }
+}
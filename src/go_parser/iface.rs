@@ -0,0 +1,97 @@
+//! Plugin-function wrapper around [`parse_dir_with_options`], for use as
+//! the shared dependency root every downstream analysis (CFG
+//! construction, complexity, coverage, lints, …) parses off of, instead
+//! of each independently walking and re-parsing the project from disk.
+
+use std::path::PathBuf;
+
+use crate::kernel::{PluginFunction, QualPfId, UserParamSpec};
+
+use super::{ParseOptions, filter_to_package, parse_dir_with_options};
+
+/// `project.parse`: parses the project at the user-supplied `path` into a
+/// [`super::ParseDirResult`]. The dependency root for every plugin
+/// function in this crate that needs a parsed project — depend on this
+/// id rather than parsing `path` again.
+pub fn parse_project_id() -> QualPfId {
+    QualPfId::new("project", "parse")
+}
+
+pub fn parse_project_pf() -> PluginFunction {
+    PluginFunction::new(parse_project_id(), vec![], |_results, params| {
+        let path = params
+            .get::<PathBuf>("path")
+            .ok_or_else(|| "missing user parameter `path`".to_string())?;
+        let fail_on_parse_error =
+            params.get::<String>("fail_on_parse_error").is_some_and(|v| v == "true");
+        let exclude_generated =
+            params.get::<String>("exclude_generated").is_none_or(|v| v != "false");
+        let include_test_files =
+            params.get::<String>("include_test_files").is_some_and(|v| v == "true");
+        let max_functions_per_file = match params.get::<String>("max_functions_per_file") {
+            Some(value) => {
+                Some(value.parse().map_err(|_| format!("invalid max_functions_per_file `{value}`"))?)
+            }
+            None => None,
+        };
+        let options = ParseOptions {
+            fail_on_parse_error,
+            exclude_generated,
+            include_test_files,
+            max_functions_per_file,
+            ..ParseOptions::default()
+        };
+        let parsed = parse_dir_with_options(path, &options).map_err(|err| err.to_string())?;
+        match params.get::<String>("package") {
+            Some(selector) => filter_to_package(parsed, selector),
+            None => Ok(parsed),
+        }
+    })
+    .with_user_params(vec![
+        UserParamSpec::required("path"),
+        UserParamSpec::optional("fail_on_parse_error"),
+        UserParamSpec::optional("exclude_generated"),
+        UserParamSpec::optional("include_test_files"),
+        UserParamSpec::optional("max_functions_per_file"),
+        UserParamSpec::optional("package"),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::{Pipeline, UserParams};
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn tempdir_with(src: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-parse-iface-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::File::create(dir.join("a.go")).unwrap().write_all(src.as_bytes()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn a_syntax_error_is_lenient_by_default_but_fails_the_pipeline_when_the_flag_is_set() {
+        let dir = tempdir_with("package main\nfunc f( {\n");
+
+        let lenient = Pipeline::new(vec![parse_project_pf()]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+        assert!(lenient.run(&params).is_ok());
+
+        let strict = Pipeline::new(vec![parse_project_pf()]);
+        let mut params = UserParams::new();
+        params.set("path", dir.clone());
+        params.set("fail_on_parse_error", "true".to_string());
+        let err = strict.run(&params).unwrap_err().to_string();
+        assert!(err.contains("syntax error"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
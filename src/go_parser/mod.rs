@@ -0,0 +1,72 @@
+//! Thin framework-facing layer on top of `goscript_parser`'s Go 1.12-era
+//! scanner/parser/AST, which is where the heavy lifting of turning Go source
+//! into an analyzable form happens.
+//!
+//! The submodules here, apart from `iface` and `print`, stay private on
+//! purpose: callers go through the
+//! re-exports below so we have a single seam to widen (or narrow) the public
+//! surface as the framework's needs grow, without every caller reaching
+//! straight into `goscript_parser`.
+
+mod build_constraints;
+mod dir;
+mod generated;
+mod generics;
+pub mod iface;
+mod normalize;
+pub mod print;
+mod scanner;
+mod span;
+mod token;
+
+pub use build_constraints::ParseOptions;
+pub use dir::{
+    Package, ParseDirError, ParseDirResult, ParsedFile, StreamedFile, filter_to_package,
+    parse_dir, parse_dir_streaming, parse_dir_with_options, parse_file_contents, parse_source,
+    reparse_files, select_package,
+};
+pub(crate) use dir::collect_go_files;
+pub use generics::functions_with_type_params;
+pub use goscript_parser::objects::Objects as AstObjects;
+pub use goscript_parser::{ast, position::FileSet};
+pub use scanner::tokenize;
+pub use span::{Span, line_of, source_text};
+pub use token::{Pos, Token};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_a_small_snippet() {
+        let src = "func add(a, b int) int {\n\treturn a + b\n}\n";
+        let toks: Vec<(Pos, Token, String)> = tokenize(src).collect();
+
+        let kinds: Vec<&Token> = toks.iter().map(|(_, t, _)| t).collect();
+        assert!(matches!(kinds[0], Token::FUNC));
+        assert!(matches!(kinds[1], Token::IDENT(_)));
+        assert_eq!(toks[1].2, "add");
+        assert!(matches!(kinds[2], Token::LPAREN));
+
+        // `return a + b` should come out as RETURN IDENT ADD IDENT.
+        let return_idx = toks
+            .iter()
+            .position(|(_, t, _)| matches!(t, Token::RETURN))
+            .expect("a RETURN token");
+        assert!(matches!(toks[return_idx + 1].1, Token::IDENT(_)));
+        assert!(matches!(toks[return_idx + 2].1, Token::ADD));
+        assert!(matches!(toks[return_idx + 3].1, Token::IDENT(_)));
+
+        // A semicolon should be inserted after `b` at the end of the
+        // `return` line, even though the source has none.
+        assert!(matches!(toks[return_idx + 4].1, Token::SEMICOLON(_)));
+
+        // Positions are non-decreasing and point at real offsets in `src`.
+        let mut prev_pos = 0;
+        for (pos, _, _) in &toks {
+            assert!(*pos <= src.chars().count());
+            assert!(*pos >= prev_pos, "expected non-decreasing positions");
+            prev_pos = *pos;
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Normalizes raw file text into the form the rest of this crate expects
+//! to see: no leading UTF-8 BOM, and `\n` line endings only.
+//!
+//! Go source is legal with a leading BOM (`gofmt` strips it, but `go
+//! build` tolerates it) and with either LF or CRLF line endings — a file
+//! checked out on Windows, or saved by an editor that adds one, isn't
+//! unusual. Neither `goscript_parser` nor this crate's own [`scanner`]
+//! account for either, so skipping this step would leave a BOM to be
+//! scanned as a stray token and let a `\r` before every `\n` throw off
+//! anything measuring source by character offset. Applied once, wherever
+//! source text first enters this crate (see [`dir::parse_file_contents`],
+//! [`dir::parse_source`]), so everything downstream — the parser,
+//! `Pos`-to-source-text mapping, [`dir::ParsedFile::source`] — only ever
+//! sees the normalized form.
+//!
+//! [`scanner`]: super::scanner
+//! [`dir::parse_file_contents`]: super::dir::parse_file_contents
+//! [`dir::parse_source`]: super::dir::parse_source
+//! [`dir::ParsedFile::source`]: super::dir::ParsedFile::source
+
+pub fn normalize_source(src: &str) -> String {
+    src.strip_prefix('\u{feff}').unwrap_or(src).replace("\r\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_a_leading_bom() {
+        assert_eq!(normalize_source("\u{feff}package main\n"), "package main\n");
+    }
+
+    #[test]
+    fn normalizes_crlf_to_lf() {
+        assert_eq!(normalize_source("package main\r\nfunc f() {}\r\n"), "package main\nfunc f() {}\n");
+    }
+
+    #[test]
+    fn leaves_an_already_normalized_file_untouched() {
+        let src = "package main\nfunc f() {}\n";
+        assert_eq!(normalize_source(src), src);
+    }
+}
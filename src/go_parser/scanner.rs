@@ -0,0 +1,274 @@
+//! Standalone lexer used by [`tokenize`](super::tokenize).
+//!
+//! `goscript_parser`'s own scanner is a private implementation detail of its
+//! `Parser`, so it can't be reused directly for cases where callers only
+//! want a token stream (syntax highlighting, simple grep-like tools) without
+//! paying for a full parse. This module re-implements just the scanning
+//! rules we need, emitting the same [`Token`] type the parser itself
+//! produces so downstream code never has to care which scanner a token came
+//! from.
+
+use super::token::{Pos, Token};
+
+/// Go automatically inserts a semicolon at the end of a line if the last
+/// token could end a statement. We mirror that rule so a plain token stream
+/// still lines up with what the parser sees.
+fn ends_statement(tok: &Token) -> bool {
+    matches!(
+        tok,
+        Token::IDENT(_)
+            | Token::INT(_)
+            | Token::FLOAT(_)
+            | Token::IMAG(_)
+            | Token::CHAR(_)
+            | Token::STRING(_)
+            | Token::BREAK
+            | Token::CONTINUE
+            | Token::FALLTHROUGH
+            | Token::RETURN
+            | Token::INC
+            | Token::DEC
+            | Token::RPAREN
+            | Token::RBRACK
+            | Token::RBRACE
+    )
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    last: Option<Token>,
+}
+
+impl Lexer {
+    fn new(src: &str) -> Self {
+        Lexer {
+            chars: src.chars().collect(),
+            pos: 0,
+            last: None,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    /// Scans up to (but not including) the next newline or EOF, skipping
+    /// whitespace, and decides whether an implicit semicolon must be
+    /// inserted first. Returns `None` once the source is exhausted.
+    fn next_token(&mut self) -> Option<(Pos, Token, String)> {
+        loop {
+            match self.peek() {
+                Some('\n') => {
+                    self.bump();
+                    if let Some(tok) = &self.last
+                        && ends_statement(tok)
+                    {
+                        self.last = None;
+                        return Some((self.pos, Token::SEMICOLON(false.into()), ";".into()));
+                    }
+                }
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('/') if self.peek_at(1) == Some('/') => {
+                    let start = self.pos;
+                    let mut s = String::new();
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        s.push(self.bump().unwrap());
+                    }
+                    let tok = Token::COMMENT(s.clone().into());
+                    return Some((start, tok, s));
+                }
+                Some('/') if self.peek_at(1) == Some('*') => {
+                    let start = self.pos;
+                    let mut s = String::new();
+                    s.push(self.bump().unwrap());
+                    s.push(self.bump().unwrap());
+                    while !(self.peek().is_none()
+                        || (self.peek() == Some('*') && self.peek_at(1) == Some('/')))
+                    {
+                        s.push(self.bump().unwrap());
+                    }
+                    if let Some(c) = self.bump() {
+                        s.push(c);
+                    }
+                    if let Some(c) = self.bump() {
+                        s.push(c);
+                    }
+                    let tok = Token::COMMENT(s.clone().into());
+                    return Some((start, tok, s));
+                }
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        let c = self.bump()?;
+
+        let (tok, text) = if c.is_alphabetic() || c == '_' {
+            let mut s = String::from(c);
+            while matches!(self.peek(), Some(c2) if c2.is_alphanumeric() || c2 == '_') {
+                s.push(self.bump().unwrap());
+            }
+            (Token::ident_token(s.clone()), s)
+        } else if c.is_ascii_digit() {
+            let mut s = String::from(c);
+            let mut is_float = false;
+            while matches!(self.peek(), Some(c2) if c2.is_ascii_digit() || c2 == '.') {
+                if self.peek() == Some('.') {
+                    is_float = true;
+                }
+                s.push(self.bump().unwrap());
+            }
+            let tok = if is_float {
+                Token::FLOAT(s.clone().into())
+            } else {
+                Token::INT(s.clone().into())
+            };
+            (tok, s)
+        } else if c == '"' || c == '`' {
+            let quote = c;
+            let mut s = String::from(c);
+            while let Some(c2) = self.bump() {
+                s.push(c2);
+                if c2 == quote {
+                    break;
+                }
+                if quote == '"'
+                    && c2 == '\\'
+                    && let Some(esc) = self.bump()
+                {
+                    s.push(esc);
+                }
+            }
+            (Token::STRING(s.clone().into()), s)
+        } else if c == '\'' {
+            let mut s = String::from(c);
+            while let Some(c2) = self.bump() {
+                s.push(c2);
+                if c2 == '\'' {
+                    break;
+                }
+                if c2 == '\\'
+                    && let Some(esc) = self.bump()
+                {
+                    s.push(esc);
+                }
+            }
+            (Token::CHAR(s.clone().into()), s)
+        } else {
+            self.scan_operator(c)
+        };
+
+        self.last = Some(tok.clone());
+        Some((start, tok, text))
+    }
+
+    fn scan_operator(&mut self, c: char) -> (Token, String) {
+        macro_rules! two {
+            ($second:expr, $two_tok:expr, $one_tok:expr) => {
+                if self.peek() == Some($second) {
+                    self.bump();
+                    $two_tok
+                } else {
+                    $one_tok
+                }
+            };
+        }
+        let tok = match c {
+            '+' => two!('+', Token::INC, two!('=', Token::ADD_ASSIGN, Token::ADD)),
+            '-' => two!('-', Token::DEC, two!('=', Token::SUB_ASSIGN, Token::SUB)),
+            '*' => two!('=', Token::MUL_ASSIGN, Token::MUL),
+            '/' => two!('=', Token::QUO_ASSIGN, Token::QUO),
+            '%' => two!('=', Token::REM_ASSIGN, Token::REM),
+            '&' => {
+                if self.peek() == Some('&') {
+                    self.bump();
+                    Token::LAND
+                } else if self.peek() == Some('^') {
+                    self.bump();
+                    two!('=', Token::AND_NOT_ASSIGN, Token::AND_NOT)
+                } else {
+                    two!('=', Token::AND_ASSIGN, Token::AND)
+                }
+            }
+            '|' => two!('|', Token::LOR, two!('=', Token::OR_ASSIGN, Token::OR)),
+            '^' => two!('=', Token::XOR_ASSIGN, Token::XOR),
+            '<' => {
+                if self.peek() == Some('-') {
+                    self.bump();
+                    Token::ARROW
+                } else if self.peek() == Some('<') {
+                    self.bump();
+                    two!('=', Token::SHL_ASSIGN, Token::SHL)
+                } else {
+                    two!('=', Token::LEQ, Token::LSS)
+                }
+            }
+            '>' => {
+                if self.peek() == Some('>') {
+                    self.bump();
+                    two!('=', Token::SHR_ASSIGN, Token::SHR)
+                } else {
+                    two!('=', Token::GEQ, Token::GTR)
+                }
+            }
+            '=' => two!('=', Token::EQL, Token::ASSIGN),
+            '!' => two!('=', Token::NEQ, Token::NOT),
+            ':' => two!('=', Token::DEFINE, Token::COLON),
+            '.' => {
+                if self.peek() == Some('.') && self.peek_at(1) == Some('.') {
+                    self.bump();
+                    self.bump();
+                    Token::ELLIPSIS
+                } else {
+                    Token::PERIOD
+                }
+            }
+            '(' => Token::LPAREN,
+            ')' => Token::RPAREN,
+            '[' => Token::LBRACK,
+            ']' => Token::RBRACK,
+            '{' => Token::LBRACE,
+            '}' => Token::RBRACE,
+            ',' => Token::COMMA,
+            ';' => Token::SEMICOLON(true.into()),
+            _ => Token::ILLEGAL(c.to_string().into()),
+        };
+        let text = tok.text().to_string();
+        (tok, text)
+    }
+}
+
+impl Iterator for Lexer {
+    type Item = (Pos, Token, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token()
+    }
+}
+
+/// Tokenizes `src` independently of the full parser, yielding `(position,
+/// token, literal text)` triples in source order. Positions are 0-based
+/// char offsets into `src`, matching `goscript_parser::position::Pos`'s
+/// convention when used with a [`FileSet`](super::FileSet) whose file starts
+/// at base 0.
+///
+/// This is primarily meant for lightweight consumers (syntax highlighters,
+/// grep-like tools) that want tokens without the cost of a full parse.
+pub fn tokenize(src: &str) -> impl Iterator<Item = (Pos, Token, String)> + '_ {
+    Lexer::new(src)
+}
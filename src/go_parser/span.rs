@@ -0,0 +1,89 @@
+use goscript_parser::ast::Node;
+use goscript_parser::objects::Objects as AstObjects;
+
+use super::token::Pos;
+
+/// A half-open `[start, end)` range of character positions into the
+/// `FileSet` a node was parsed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub fn new(start: Pos, end: Pos) -> Self {
+        Span { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+}
+
+/// The slice of `source` a node was parsed from — the exact original
+/// text, unlike the approximate reformatting [`crate::go_parser::print`]
+/// produces from the AST. `base` is the node's file's own offset into
+/// the shared `FileSet` (a [`crate::go_parser::ParsedFile::base`]).
+///
+/// `Pos`s count characters, not bytes ([`FileSet::add_file`]'s `size` is
+/// a char count), so a node spanning multi-byte characters is walked
+/// char-by-char here rather than sliced by its raw `Pos` value.
+pub fn source_text<'a>(source: &'a str, base: Pos, node: &impl Node, objects: &AstObjects) -> &'a str {
+    let start = char_offset_to_byte(source, node.pos(objects).saturating_sub(base));
+    let end = char_offset_to_byte(source, node.end(objects).saturating_sub(base));
+    &source[start..end]
+}
+
+fn char_offset_to_byte(source: &str, char_offset: usize) -> usize {
+    source.char_indices().nth(char_offset).map_or(source.len(), |(byte, _)| byte)
+}
+
+/// The 1-based source line `pos` falls on within `source`, given `base` —
+/// the file's own offset into the shared `FileSet`. Uses
+/// [`saturating_sub`](usize::saturating_sub) rather than checked
+/// subtraction so a `pos` that lies outside this file entirely (a
+/// synthesized AST node with no real position, say) degrades to line 1
+/// instead of panicking — the caller is expected to treat that as "no
+/// useful line number" rather than a real location.
+pub fn line_of(source: &str, base: usize, pos: usize) -> usize {
+    let offset = pos.saturating_sub(base);
+    source.chars().take(offset).filter(|&c| c == '\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use goscript_parser::ast::Decl;
+
+    #[test]
+    fn extracts_the_exact_source_of_an_if_statement() {
+        let src = "package main\n\nfunc f(x int) int {\n\tif x > 0 {\n\t\treturn x\n\t}\n\treturn 0\n}\n";
+        let parsed = crate::go_parser::parse_source("a.go", src).unwrap();
+        let pkg = parsed.packages.values().next().unwrap();
+        let pf = pkg.files.values().next().unwrap();
+
+        let Decl::Func(key) = &pf.ast.decls[0] else { unreachable!() };
+        let body = parsed.objects.fdecls[*key].body.as_ref().unwrap();
+        let goscript_parser::ast::Stmt::If(if_stmt) = &body.list[0] else { unreachable!() };
+
+        let text = super::source_text(&pf.source, pf.base, &body.list[0], &parsed.objects);
+        assert_eq!(text, "if x > 0 {\n\t\treturn x\n\t}");
+
+        let cond_text = super::source_text(&pf.source, pf.base, &if_stmt.cond, &parsed.objects);
+        assert_eq!(cond_text, "x > 0");
+    }
+
+    #[test]
+    fn line_of_a_pos_outside_the_file_does_not_panic() {
+        let src = "package main\n\nfunc f() {}\n";
+
+        // A `Pos` smaller than `base` can't happen for a real parsed node,
+        // but a synthesized one (no genuine source position) could carry
+        // one — this must degrade gracefully rather than panic.
+        assert_eq!(super::line_of(src, 100, 0), 1);
+    }
+}
@@ -0,0 +1,8 @@
+//! Re-exports of the token vocabulary shared with the underlying parser.
+//!
+//! Kept as its own module (rather than a blanket `pub use`) so that if we
+//! ever need to layer framework-specific token kinds on top of the ones
+//! borrowed from `goscript_parser`, there's already a seam for it.
+
+pub use goscript_parser::token::Token;
+pub use goscript_parser::position::Pos;
@@ -0,0 +1,217 @@
+//! Rendering a generated DOT graph (from [`crate::cfg_plugin::to_dot`],
+//! [`crate::cfg_plugin::to_dot_combined`], or [`crate::imports::to_dot`])
+//! to an actual image via the `dot` binary, for a caller that expects a
+//! PNG/SVG rather than raw DOT text.
+//!
+//! Graphviz isn't a dependency of this crate — it's a system binary a
+//! user may or may not have installed — so rendering is best-effort: the
+//! `.dot` source is always written first, and a missing or failing `dot`
+//! only downgrades the result to a warning rather than an error, since
+//! the caller still has something useful (the `.dot` file) either way.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// An image format `dot` can render a graph to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DotImageFormat {
+    Png,
+    Svg,
+}
+
+impl DotImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            DotImageFormat::Png => "png",
+            DotImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// What [`export_dot_graph`] actually managed to produce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotExportOutcome {
+    /// The `.dot` source file, always written.
+    pub dot_path: PathBuf,
+    /// The rendered image, set only if `dot` was found on `PATH` and
+    /// rendered successfully.
+    pub image_path: Option<PathBuf>,
+    /// Explains why there's no `image_path` — `dot` missing from `PATH`,
+    /// or the subprocess itself failing — `None` if rendering wasn't
+    /// requested or succeeded.
+    pub warning: Option<String>,
+}
+
+/// Writes `dot_source` to `<path_without_ext>.dot`, then, if `render` is
+/// set, tries to additionally render `<path_without_ext>.<format>` by
+/// piping `dot_source` through the `dot` binary on `PATH`. If `dot`
+/// isn't installed or the subprocess fails, the `.dot` file written
+/// above is still there — `image_path` is `None` and `warning` explains
+/// why, rather than this returning an `Err` or panicking.
+pub fn export_dot_graph(
+    dot_source: &str,
+    path_without_ext: &Path,
+    format: DotImageFormat,
+    render: bool,
+) -> std::io::Result<DotExportOutcome> {
+    export_dot_graph_with_binary(dot_source, path_without_ext, format, render, "dot")
+}
+
+/// [`export_dot_graph`], with the Graphviz binary name injectable so
+/// tests can exercise the "not installed" path deterministically
+/// without depending on whether the machine running them actually has
+/// Graphviz.
+fn export_dot_graph_with_binary(
+    dot_source: &str,
+    path_without_ext: &Path,
+    format: DotImageFormat,
+    render: bool,
+    dot_binary: &str,
+) -> std::io::Result<DotExportOutcome> {
+    let dot_path = path_without_ext.with_extension("dot");
+    std::fs::write(&dot_path, dot_source)?;
+
+    if !render {
+        return Ok(DotExportOutcome {
+            dot_path,
+            image_path: None,
+            warning: None,
+        });
+    }
+
+    let image_path = path_without_ext.with_extension(format.extension());
+    match run_graphviz(dot_binary, dot_source, format, &image_path) {
+        Ok(()) => Ok(DotExportOutcome {
+            dot_path,
+            image_path: Some(image_path),
+            warning: None,
+        }),
+        Err(warning) => Ok(DotExportOutcome {
+            dot_path,
+            image_path: None,
+            warning: Some(warning),
+        }),
+    }
+}
+
+/// Pipes `dot_source` into `dot_binary -T<format> -o <image_path>`,
+/// turning a missing binary, a spawn failure, or a non-zero exit into a
+/// human-readable `Err` string rather than letting any of them panic.
+fn run_graphviz(
+    dot_binary: &str,
+    dot_source: &str,
+    format: DotImageFormat,
+    image_path: &Path,
+) -> Result<(), String> {
+    let mut child = Command::new(dot_binary)
+        .arg(format!("-T{}", format.extension()))
+        .arg("-o")
+        .arg(image_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            format!("couldn't run `{dot_binary}` (is Graphviz installed and on PATH?): {err}")
+        })?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin was piped")
+        .write_all(dot_source.as_bytes())
+        .map_err(|err| format!("couldn't write DOT source to `{dot_binary}`'s stdin: {err}"))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("`{dot_binary}` didn't finish running: {err}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("`{dot_binary}` exited with {}: {}", output.status, stderr.trim()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tempfile_dir() -> PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "skanujkod-graphviz-test-{}-{n}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn the_dot_file_is_written_with_no_image_or_warning_when_rendering_is_not_requested() {
+        let dir = tempfile_dir();
+        let path_without_ext = dir.join("graph");
+
+        let outcome =
+            export_dot_graph("digraph cfg { a -> b; }", &path_without_ext, DotImageFormat::Svg, false)
+                .unwrap();
+
+        assert!(outcome.dot_path.is_file());
+        assert_eq!(outcome.image_path, None);
+        assert_eq!(outcome.warning, None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn the_dot_file_is_written_with_a_helpful_warning_when_graphviz_is_not_installed() {
+        let dir = tempfile_dir();
+        let path_without_ext = dir.join("graph");
+
+        let outcome = export_dot_graph_with_binary(
+            "digraph cfg { a -> b; }",
+            &path_without_ext,
+            DotImageFormat::Png,
+            true,
+            "skanujkod-definitely-not-a-real-graphviz-binary",
+        )
+        .unwrap();
+
+        assert!(outcome.dot_path.is_file());
+        assert_eq!(std::fs::read_to_string(&outcome.dot_path).unwrap(), "digraph cfg { a -> b; }");
+        assert_eq!(outcome.image_path, None);
+        let warning = outcome.warning.expect("missing dot binary should produce a warning");
+        assert!(warning.contains("skanujkod-definitely-not-a-real-graphviz-binary"));
+        assert!(warning.to_lowercase().contains("graphviz") || warning.to_lowercase().contains("path"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn rendering_succeeds_when_a_stand_in_dot_binary_is_present() {
+        // A `true`-like stand-in for `dot`: doesn't produce an image, but
+        // exits 0, so this exercises the success path without depending
+        // on Graphviz actually being installed.
+        let dir = tempfile_dir();
+        let path_without_ext = dir.join("graph");
+
+        let outcome = export_dot_graph_with_binary(
+            "digraph cfg { a -> b; }",
+            &path_without_ext,
+            DotImageFormat::Png,
+            true,
+            "true",
+        )
+        .unwrap();
+
+        assert!(outcome.dot_path.is_file());
+        assert_eq!(outcome.image_path, Some(path_without_ext.with_extension("png")));
+        assert_eq!(outcome.warning, None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
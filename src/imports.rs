@@ -0,0 +1,400 @@
+//! Package-level import graph: which of the project's own packages import
+//! which others, plus a bucket for everything outside the project
+//! (stdlib and third-party imports).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::go_parser::{ParseDirResult, ast};
+
+/// An edge from one of the project's own packages to another, both
+/// identified by the directory key `ParseDirResult::packages` uses.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub struct ImportEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// The import graph for a parsed project: edges between its own
+/// packages, plus every import path that didn't resolve to one of them
+/// (stdlib and third-party dependencies), bucketed by the importing
+/// package's directory key.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ImportGraph {
+    pub edges: BTreeSet<ImportEdge>,
+    pub external: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Controls which external (non-project) imports [`analyze`] records in
+/// [`ImportGraph::external`]. Third-party imports are always included —
+/// they're almost always what someone building this graph actually
+/// wants to see; stdlib and vendored imports are noisy by comparison
+/// (dozens of `fmt`/`strings`-style edges per package) so they default
+/// to excluded and have to be opted back in.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportAnalysisOptions {
+    pub include_stdlib: bool,
+    pub include_vendored: bool,
+}
+
+/// How an external import path is classified for filtering under
+/// [`ImportAnalysisOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExternalKind {
+    /// Under a `vendor/` directory anywhere in the path — checked ahead
+    /// of the stdlib heuristic below, since a vendored path's first
+    /// element (`vendor`) would otherwise look exactly like a stdlib one.
+    Vendored,
+    /// No dot in the first path element, Go's own convention for
+    /// distinguishing a standard-library import (`fmt`, `net/http`) from
+    /// one rooted at a domain (`example.com/proj/a`).
+    Stdlib,
+    ThirdParty,
+}
+
+fn classify_external(path: &str) -> ExternalKind {
+    if path.split('/').any(|segment| segment == "vendor") {
+        return ExternalKind::Vendored;
+    }
+    let first = path.split('/').next().unwrap_or(path);
+    if first.contains('.') {
+        ExternalKind::ThirdParty
+    } else {
+        ExternalKind::Stdlib
+    }
+}
+
+fn import_path(spec: &ast::ImportSpec) -> String {
+    spec.path.token.to_string().trim_matches('"').to_string()
+}
+
+/// Resolves an import path to one of `parsed`'s own package directory
+/// keys, matching the import path's last component against each
+/// package directory's own last component. There's no `go.mod`-derived
+/// module path here to resolve full import paths properly, so this is a
+/// heuristic rather than exact resolution — good enough to spot edges
+/// (and cycles) between the project's own packages, which is the point.
+fn resolve_internal<'a>(parsed: &'a ParseDirResult, path: &str) -> Option<&'a str> {
+    let last = path.rsplit('/').next().unwrap_or(path);
+    parsed
+        .packages
+        .keys()
+        .find(|dir_key| {
+            dir_key
+                .rsplit(std::path::MAIN_SEPARATOR)
+                .next()
+                .unwrap_or(dir_key.as_str())
+                == last
+        })
+        .map(String::as_str)
+}
+
+/// Builds the import graph for every file in `parsed`. `options`
+/// controls which external imports are kept in [`ImportGraph::external`]
+/// — see [`ImportAnalysisOptions`].
+pub fn analyze(parsed: &ParseDirResult, options: &ImportAnalysisOptions) -> ImportGraph {
+    let mut graph = ImportGraph::default();
+    for (dir_key, pkg) in &parsed.packages {
+        for pf in pkg.files.values() {
+            for spec_key in &pf.ast.imports {
+                let ast::Spec::Import(spec) = &parsed.objects.specs[*spec_key] else {
+                    continue;
+                };
+                let path = import_path(spec);
+                match resolve_internal(parsed, &path) {
+                    Some(to) if to != dir_key => {
+                        graph.edges.insert(ImportEdge {
+                            from: dir_key.clone(),
+                            to: to.to_string(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        let keep = match classify_external(&path) {
+                            ExternalKind::Vendored => options.include_vendored,
+                            ExternalKind::Stdlib => options.include_stdlib,
+                            ExternalKind::ThirdParty => true,
+                        };
+                        if keep {
+                            graph
+                                .external
+                                .entry(dir_key.clone())
+                                .or_default()
+                                .insert(path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// Renders the graph's internal edges as a Graphviz DOT digraph. External
+/// imports aren't included: there can be dozens per package, and spotting
+/// import cycles between the project's own packages is the point of this
+/// view, not cataloguing every dependency.
+pub fn to_dot(graph: &ImportGraph) -> String {
+    let mut out = String::from("digraph imports {\n");
+    for edge in &graph.edges {
+        out.push_str(&format!("    {:?} -> {:?};\n", edge.from, edge.to));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Strongly-connected components of size greater than one, or
+/// self-imports, among `graph`'s internal edges. Go itself rejects import
+/// cycles, but this is run over partial or generated trees that haven't
+/// necessarily gone through `go build`, so cycles can still turn up.
+///
+/// A simple cycle is returned as an ordered walk through its packages
+/// (`a -> b -> c -> a`, reported as `[a, b, c]`). A component holding
+/// several overlapping cycles lists its packages in the order the
+/// depth-first search first reached them, which is not necessarily a
+/// single walk along import edges.
+pub fn find_cycles(graph: &ImportGraph) -> Vec<Vec<String>> {
+    let mut adj: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut nodes: BTreeSet<&str> = BTreeSet::new();
+    for edge in &graph.edges {
+        adj.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+        nodes.insert(edge.from.as_str());
+        nodes.insert(edge.to.as_str());
+    }
+
+    let mut finder = TarjanScc {
+        adj: &adj,
+        index: BTreeMap::new(),
+        low_link: BTreeMap::new(),
+        on_stack: BTreeSet::new(),
+        stack: Vec::new(),
+        next_index: 0,
+        sccs: Vec::new(),
+    };
+    for node in &nodes {
+        if !finder.index.contains_key(node) {
+            finder.visit(node);
+        }
+    }
+
+    finder
+        .sccs
+        .into_iter()
+        .filter(|scc| scc.len() > 1 || adj.get(&scc[0]).is_some_and(|tos| tos.contains(&scc[0])))
+        // Tarjan pops a component off its stack in reverse discovery
+        // order; reversing it puts a simple cycle back in edge order.
+        .map(|scc| scc.into_iter().rev().map(str::to_string).collect())
+        .collect()
+}
+
+/// Tarjan's strongly-connected-components algorithm, run over the
+/// internal import graph's adjacency list.
+struct TarjanScc<'a> {
+    adj: &'a BTreeMap<&'a str, Vec<&'a str>>,
+    index: BTreeMap<&'a str, usize>,
+    low_link: BTreeMap<&'a str, usize>,
+    on_stack: BTreeSet<&'a str>,
+    stack: Vec<&'a str>,
+    next_index: usize,
+    sccs: Vec<Vec<&'a str>>,
+}
+
+impl<'a> TarjanScc<'a> {
+    fn visit(&mut self, node: &'a str) {
+        self.index.insert(node, self.next_index);
+        self.low_link.insert(node, self.next_index);
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack.insert(node);
+
+        for &succ in self.adj.get(node).into_iter().flatten() {
+            if !self.index.contains_key(succ) {
+                self.visit(succ);
+                let lower = self.low_link[succ];
+                let slot = self.low_link.get_mut(node).unwrap();
+                *slot = (*slot).min(lower);
+            } else if self.on_stack.contains(succ) {
+                let lower = self.index[succ];
+                let slot = self.low_link.get_mut(node).unwrap();
+                *slot = (*slot).min(lower);
+            }
+        }
+
+        if self.low_link[node] == self.index[node] {
+            let mut scc = Vec::new();
+            loop {
+                let member = self.stack.pop().expect("node's own SCC is on the stack");
+                self.on_stack.remove(member);
+                scc.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.sccs.push(scc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn finds_edge_between_two_internal_packages_and_no_false_externals() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-imports-test-{}", std::process::id()));
+        let pkg_a = dir.join("a");
+        let pkg_b = dir.join("b");
+        fs::create_dir_all(&pkg_a).unwrap();
+        fs::create_dir_all(&pkg_b).unwrap();
+        fs::write(pkg_a.join("a.go"), "package a\n\nfunc F() {}\n").unwrap();
+        fs::write(
+            pkg_b.join("b.go"),
+            "package b\n\nimport (\n\t\"fmt\"\n\t\"example.com/proj/a\"\n)\n\nfunc G() {\n\tfmt.Println(a.F)\n}\n",
+        )
+        .unwrap();
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let graph = analyze(
+            &parsed,
+            &ImportAnalysisOptions {
+                include_stdlib: true,
+                include_vendored: true,
+            },
+        );
+        assert_eq!(
+            graph.edges,
+            BTreeSet::from([ImportEdge {
+                from: "b".to_string(),
+                to: "a".to_string(),
+            }])
+        );
+        assert_eq!(
+            graph.external.get("b").cloned().unwrap_or_default(),
+            BTreeSet::from(["fmt".to_string()])
+        );
+        assert!(!graph.external.contains_key("a"));
+        assert!(find_cycles(&graph).is_empty());
+    }
+
+    #[test]
+    fn stdlib_imports_are_excluded_by_default_but_included_when_requested() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-imports-stdlib-test-{}", std::process::id()));
+        let pkg_a = dir.join("a");
+        fs::create_dir_all(&pkg_a).unwrap();
+        fs::write(
+            pkg_a.join("a.go"),
+            "package a\n\nimport (\n\t\"fmt\"\n\t\"example.com/proj/other\"\n)\n\nfunc F() {\n\tfmt.Println(\"hi\")\n}\n",
+        )
+        .unwrap();
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let default_graph = analyze(&parsed, &ImportAnalysisOptions::default());
+        let external = default_graph.external.get("a").cloned().unwrap_or_default();
+        assert!(!external.contains("fmt"));
+        assert!(external.contains("example.com/proj/other"));
+
+        let with_stdlib = analyze(
+            &parsed,
+            &ImportAnalysisOptions {
+                include_stdlib: true,
+                include_vendored: false,
+            },
+        );
+        let external = with_stdlib.external.get("a").cloned().unwrap_or_default();
+        assert!(external.contains("fmt"));
+    }
+
+    #[test]
+    fn vendored_imports_are_excluded_by_default_but_included_when_requested() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-imports-vendored-test-{}", std::process::id()));
+        let pkg_a = dir.join("a");
+        fs::create_dir_all(&pkg_a).unwrap();
+        fs::write(
+            pkg_a.join("a.go"),
+            "package a\n\nimport \"vendor/example.com/dep\"\n\nfunc F() {\n\tdep.Do()\n}\n",
+        )
+        .unwrap();
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let default_graph = analyze(&parsed, &ImportAnalysisOptions::default());
+        assert!(!default_graph.external.contains_key("a"));
+
+        let with_vendored = analyze(
+            &parsed,
+            &ImportAnalysisOptions {
+                include_stdlib: false,
+                include_vendored: true,
+            },
+        );
+        assert!(
+            with_vendored
+                .external
+                .get("a")
+                .cloned()
+                .unwrap_or_default()
+                .contains("vendor/example.com/dep")
+        );
+    }
+
+    #[test]
+    fn reports_a_cycle_between_two_packages_that_import_each_other() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-imports-cycle-test-{}", std::process::id()));
+        let pkg_a = dir.join("a");
+        let pkg_b = dir.join("b");
+        fs::create_dir_all(&pkg_a).unwrap();
+        fs::create_dir_all(&pkg_b).unwrap();
+        fs::write(
+            pkg_a.join("a.go"),
+            "package a\n\nimport \"example.com/proj/b\"\n\nfunc F() {\n\tb.G()\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            pkg_b.join("b.go"),
+            "package b\n\nimport \"example.com/proj/a\"\n\nfunc G() {\n\ta.F()\n}\n",
+        )
+        .unwrap();
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let graph = analyze(&parsed, &ImportAnalysisOptions::default());
+        let cycles = find_cycles(&graph);
+        assert_eq!(cycles.len(), 1);
+        let mut members = cycles[0].clone();
+        members.sort();
+        assert_eq!(members, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn a_clean_dag_of_packages_reports_no_cycles() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-imports-dag-test-{}", std::process::id()));
+        let pkg_a = dir.join("a");
+        let pkg_b = dir.join("b");
+        fs::create_dir_all(&pkg_a).unwrap();
+        fs::create_dir_all(&pkg_b).unwrap();
+        fs::write(
+            pkg_a.join("a.go"),
+            "package a\n\nimport \"example.com/proj/b\"\n\nfunc F() {\n\tb.G()\n}\n",
+        )
+        .unwrap();
+        fs::write(pkg_b.join("b.go"), "package b\n\nfunc G() {}\n").unwrap();
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let graph = analyze(&parsed, &ImportAnalysisOptions::default());
+        let cycles = find_cycles(&graph);
+        assert!(cycles.is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+//! Heuristic "who implements this interface" queries.
+//!
+//! Go interfaces are satisfied structurally, so answering "which types
+//! implement `Shape`" properly requires a type checker this crate
+//! doesn't have. What it does have is every declared method set, so this
+//! matches method names and arities instead: good enough to flag likely
+//! implementors and likely-missing methods without claiming full
+//! soundness.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use goscript_parser::ast::{self, Decl, Expr, Spec};
+
+use crate::go_parser::ParseDirResult;
+
+/// A method's name plus how many parameters it takes, the two things
+/// this module can compare without a type checker. Two methods with the
+/// same name but a different number of parameters are treated as a
+/// mismatch, the same way Go's compiler would reject the "implementation".
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct MethodSignature {
+    name: String,
+    arity: usize,
+}
+
+/// Counts the parameters a [`ast::FuncType`] declares, treating a
+/// nameless parameter (`func(int)`) as one and a field that names
+/// several parameters of the same type (`func(a, b int)`) as one per
+/// name, matching how Go counts arguments.
+fn arity(func_type: &ast::FuncType, parsed: &ParseDirResult) -> usize {
+    func_type
+        .params
+        .list
+        .iter()
+        .map(|key| {
+            let field = &parsed.objects.fields[*key];
+            field.names.len().max(1)
+        })
+        .sum()
+}
+
+/// Strips a pointer receiver/type (`*Foo` -> `Foo`) down to the bare
+/// identifier, if the expression is one.
+fn ident_name(expr: &Expr, parsed: &ParseDirResult) -> Option<String> {
+    match expr {
+        Expr::Ident(key) => Some(parsed.objects.idents[*key].name.clone()),
+        Expr::Star(star) => ident_name(&star.expr, parsed),
+        _ => None,
+    }
+}
+
+/// The method set an interface declares, by name — including methods
+/// contributed by embedded interfaces (`type ReadWriter interface {
+/// Reader; Writer }`), resolved recursively against every interface
+/// declared in `parsed`. An embedded interface this heuristic can't find
+/// (declared elsewhere, or from an import it doesn't follow) is silently
+/// skipped, so a type may still be under-reported as not implementing
+/// something whose full method set spans an interface this can't see.
+fn interface_methods(iface: &ast::InterfaceType, parsed: &ParseDirResult) -> BTreeSet<MethodSignature> {
+    let mut methods = BTreeSet::new();
+    let mut seen = BTreeSet::new();
+    collect_interface_methods(iface, parsed, &mut seen, &mut methods);
+    methods
+}
+
+/// Walks `iface`'s own method fields into `methods`, following any
+/// embedded interface field into its own declaration. `seen` guards
+/// against an interface embedding itself (directly or via a cycle),
+/// which would otherwise recurse forever.
+fn collect_interface_methods(
+    iface: &ast::InterfaceType,
+    parsed: &ParseDirResult,
+    seen: &mut BTreeSet<String>,
+    methods: &mut BTreeSet<MethodSignature>,
+) {
+    for key in &iface.methods.list {
+        let field = &parsed.objects.fields[*key];
+        match &field.typ {
+            Expr::Func(ftype_key) => {
+                let Some(name_key) = field.names.first() else { continue };
+                let ftype = &parsed.objects.ftypes[*ftype_key];
+                methods.insert(MethodSignature {
+                    name: parsed.objects.idents[*name_key].name.clone(),
+                    arity: arity(ftype, parsed),
+                });
+            }
+            embedded_typ if field.names.is_empty() => {
+                let Some(embedded_name) = ident_name(embedded_typ, parsed) else { continue };
+                if !seen.insert(embedded_name.clone()) {
+                    continue;
+                }
+                if let Some(embedded) = find_interface(parsed, &embedded_name) {
+                    collect_interface_methods(embedded, parsed, seen, methods);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Finds the interface type named `iface_name` anywhere in `parsed`.
+fn find_interface<'a>(parsed: &'a ParseDirResult, iface_name: &str) -> Option<&'a ast::InterfaceType> {
+    for pkg in parsed.packages.values() {
+        for pf in pkg.files.values() {
+            for decl in &pf.ast.decls {
+                let Decl::Gen(gen_decl) = decl else { continue };
+                for spec_key in &gen_decl.specs {
+                    let Spec::Type(type_spec) = &parsed.objects.specs[*spec_key] else {
+                        continue;
+                    };
+                    if parsed.objects.idents[type_spec.name].name != iface_name {
+                        continue;
+                    }
+                    if let Expr::Interface(iface) = &type_spec.typ {
+                        return Some(iface);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Every method declared with a receiver, grouped by the receiver type's
+/// name (pointer receivers and value receivers are folded together,
+/// since a method on either satisfies an interface for both).
+fn method_sets_by_receiver(parsed: &ParseDirResult) -> BTreeMap<String, BTreeSet<MethodSignature>> {
+    let mut sets: BTreeMap<String, BTreeSet<MethodSignature>> = BTreeMap::new();
+
+    for pkg in parsed.packages.values() {
+        for pf in pkg.files.values() {
+            for decl in &pf.ast.decls {
+                let Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(recv) = &fdecl.recv else { continue };
+                let Some(recv_field_key) = recv.list.first() else {
+                    continue;
+                };
+                let Some(recv_type) = ident_name(&parsed.objects.fields[*recv_field_key].typ, parsed)
+                else {
+                    continue;
+                };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let func_arity = arity(&parsed.objects.ftypes[fdecl.typ], parsed);
+
+                sets.entry(recv_type).or_default().insert(MethodSignature {
+                    name,
+                    arity: func_arity,
+                });
+            }
+        }
+    }
+
+    sets
+}
+
+/// Returns the names of every concrete type in `parsed` whose declared
+/// methods cover every method `iface_name` requires (by name and
+/// arity), `None` if no interface with that name was found.
+pub fn implementors_of(parsed: &ParseDirResult, iface_name: &str) -> Option<Vec<String>> {
+    let iface = find_interface(parsed, iface_name)?;
+    let required = interface_methods(iface, parsed);
+
+    let implementors = method_sets_by_receiver(parsed)
+        .into_iter()
+        .filter(|(_, methods)| required.is_subset(methods))
+        .map(|(type_name, _)| type_name)
+        .collect();
+
+    Some(implementors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(dir: &Path, name: &str, src: &str) {
+        fs::write(dir.join(name), src).unwrap();
+    }
+
+    #[test]
+    fn finds_a_full_implementor_and_skips_a_type_missing_a_method() {
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-interfaces-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package shapes\n\
+             type Shape interface {\n\
+             \tArea() float64\n\
+             \tPerimeter() float64\n\
+             }\n\
+             type Square struct { side float64 }\n\
+             func (s Square) Area() float64 { return s.side * s.side }\n\
+             func (s Square) Perimeter() float64 { return s.side * 4 }\n\
+             type Point struct { x, y float64 }\n\
+             func (p Point) Area() float64 { return 0 }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let implementors = implementors_of(&parsed, "Shape").unwrap();
+        assert_eq!(implementors, vec!["Square".to_string()]);
+    }
+
+    #[test]
+    fn requires_the_methods_of_an_embedded_interface_too() {
+        let dir = std::env::temp_dir()
+            .join(format!("skanujkod-interfaces-embedded-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            "a.go",
+            "package io\n\
+             type Reader interface {\n\
+             \tRead(p []byte) int\n\
+             }\n\
+             type Writer interface {\n\
+             \tWrite(p []byte) int\n\
+             }\n\
+             type ReadWriter interface {\n\
+             \tReader\n\
+             \tWriter\n\
+             }\n\
+             type File struct{}\n\
+             func (f File) Read(p []byte) int { return 0 }\n\
+             func (f File) Write(p []byte) int { return 0 }\n\
+             type ReadOnlyFile struct{}\n\
+             func (f ReadOnlyFile) Read(p []byte) int { return 0 }\n",
+        );
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let implementors = implementors_of(&parsed, "ReadWriter").unwrap();
+        assert_eq!(implementors, vec!["File".to_string()]);
+    }
+}
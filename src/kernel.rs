@@ -0,0 +1,946 @@
+//! The framework core ("kernel") described in `doc/architecture/decisions/model.md`:
+//! orchestrates a pipeline of plugin functions (PFs), each identified by a
+//! [`QualPfId`], resolving the dependencies between them into an
+//! execution order and threading results through a type-erased store.
+//!
+//! This is a first, deliberately small implementation of that design.
+//! PFs are plain Rust closures rather than dynamically loaded plugins —
+//! the dynamic-linking questions the design doc raises (stable ABI, type
+//! erasure across a shared-library boundary) are still open there, so
+//! this sticks to what's needed today: composing the analyses already in
+//! this crate (`complexity`, `imports`, ...) into a user-specified run.
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A plugin function's qualified name: `<plugin>.<function>`, e.g.
+/// `complexity.analyze_function_complexity`. Used both to label a PF and
+/// to name its dependencies, so the kernel can link a pipeline together
+/// by matching names rather than types.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize)]
+pub struct QualPfId(String);
+
+impl QualPfId {
+    pub fn new(plugin: impl AsRef<str>, function: impl AsRef<str>) -> Self {
+        Self(format!("{}.{}", plugin.as_ref(), function.as_ref()))
+    }
+}
+
+impl fmt::Display for QualPfId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// User-supplied parameters for a pipeline run, looked up by name. Like
+/// [`ResultStore`], values are type-erased: a PF declares the type it
+/// expects and downcasts at lookup time, per the design doc's [PF
+/// Output](../../doc/architecture/decisions/model.md#pf-output) section.
+#[derive(Default)]
+pub struct UserParams {
+    values: HashMap<String, Box<dyn Any>>,
+}
+
+impl UserParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Any) -> &mut Self {
+        self.values.insert(name.into(), Box::new(value));
+        self
+    }
+
+    pub fn get<T: 'static>(&self, name: &str) -> Option<&T> {
+        self.values.get(name).and_then(|v| v.downcast_ref())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// The names of every parameter that was supplied, in no particular
+    /// order. Used to check supplied names against what plugins actually
+    /// declare, e.g. in [`Pipeline::unknown_parameter_warnings`].
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Moves every parameter from `other` into `self`, overwriting any
+    /// entry with the same name. Used to merge CLI-supplied `--param`
+    /// entries in on top of the run's "well-known" parameters.
+    pub fn extend(&mut self, other: UserParams) {
+        self.values.extend(other.values);
+    }
+}
+
+/// Parses `key=value` command-line arguments into a [`UserParams`], with
+/// every value stored as a `String` — the kernel has no way to know what
+/// type a given plugin function expects, so it's on the PF to parse the
+/// string into whatever it needs.
+pub fn parse_key_value_params(pairs: &[String]) -> Result<UserParams, String> {
+    let mut params = UserParams::new();
+    for pair in pairs {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected `key=value`, got `{pair}`"))?;
+        params.set(key.to_string(), value.to_string());
+    }
+    Ok(params)
+}
+
+/// A type-erased PF result, tagged with the result type's name so a
+/// lookup that requests the wrong type can say what it actually found
+/// instead of just failing a downcast.
+struct StoredResult {
+    type_name: &'static str,
+    value: Box<dyn Any>,
+}
+
+/// The type-erased results of every PF that has run so far in a
+/// pipeline, keyed by [`QualPfId`].
+#[derive(Default)]
+pub struct ResultStore {
+    results: HashMap<QualPfId, StoredResult>,
+}
+
+impl fmt::Debug for ResultStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResultStore")
+            .field("results", &self.results.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ResultStore {
+    pub fn get<T: 'static>(&self, id: &QualPfId) -> Option<&T> {
+        self.results.get(id).and_then(|stored| stored.value.downcast_ref())
+    }
+
+    /// Like [`get`](Self::get), but instead of `None` on a type mismatch,
+    /// returns a [`KernelError`] naming both the type that was asked for
+    /// and the [`PluginFunction::result_type_name`] actually stored —
+    /// the kernel's own equivalent of the "assert the tag before
+    /// downcasting" check, without resorting to `unsafe`.
+    pub fn try_get<T: 'static>(&self, id: &QualPfId) -> Result<&T, KernelError> {
+        let stored = self
+            .results
+            .get(id)
+            .ok_or_else(|| KernelError::MissingResult { id: id.clone() })?;
+        stored.value.downcast_ref().ok_or_else(|| KernelError::ResultTypeMismatch {
+            id: id.clone(),
+            expected: std::any::type_name::<T>(),
+            actual: stored.type_name,
+        })
+    }
+
+    fn insert(&mut self, id: QualPfId, type_name: &'static str, value: Box<dyn Any>) {
+        self.results.insert(id, StoredResult { type_name, value });
+    }
+}
+
+/// Errors that can occur while resolving or executing a pipeline.
+#[derive(Debug)]
+pub enum KernelError {
+    /// `pf` depends on `dep`, but no plugin function with that id was
+    /// registered in the pipeline.
+    MissingDependency { pf: QualPfId, dep: QualPfId },
+    /// Running the plugin functions in `cycle` would require each to run
+    /// before the next, and the last before the first.
+    CyclicDependency { cycle: Vec<QualPfId> },
+    /// `pf`'s closure returned an error.
+    PluginFailed { pf: QualPfId, message: String },
+    /// `pf` declared `param` as a required user parameter, but the run's
+    /// [`UserParams`] doesn't have an entry for it.
+    MissingUserParameter { pf: QualPfId, param: String },
+    /// [`ResultStore::try_get`] was asked for a result that hasn't run
+    /// (or isn't part of this pipeline) yet.
+    MissingResult { id: QualPfId },
+    /// [`ResultStore::try_get`] was asked for `id`'s result as `expected`,
+    /// but the PF that produced it declared its result type as `actual`.
+    ResultTypeMismatch {
+        id: QualPfId,
+        expected: &'static str,
+        actual: &'static str,
+    },
+}
+
+impl fmt::Display for KernelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KernelError::MissingDependency { pf, dep } => {
+                write!(f, "{pf} depends on {dep}, which is not registered in this pipeline")
+            }
+            KernelError::CyclicDependency { cycle } => {
+                let path = cycle
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                write!(f, "cyclic plugin function dependency: {path}")
+            }
+            KernelError::PluginFailed { pf, message } => write!(f, "{pf} failed: {message}"),
+            KernelError::MissingUserParameter { pf, param } => {
+                write!(f, "{pf} requires user parameter `{param}`, which was not supplied")
+            }
+            KernelError::MissingResult { id } => {
+                write!(f, "no result for {id} is available yet")
+            }
+            KernelError::ResultTypeMismatch { id, expected, actual } => {
+                write!(
+                    f,
+                    "{id}'s result was requested as `{expected}`, but it was declared as `{actual}`"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KernelError {}
+
+/// A PF's closure: given the results of its dependencies and the run's
+/// user parameters, produce this PF's (type-erased) result.
+type PfRun = dyn Fn(&ResultStore, &UserParams) -> Result<Box<dyn Any>, String>;
+
+/// Declares a user parameter a [`PluginFunction`] reads, so the kernel
+/// can check it was supplied before running the pipeline rather than
+/// failing deep inside the PF's closure.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UserParamSpec {
+    pub name: String,
+    pub required: bool,
+}
+
+impl UserParamSpec {
+    pub fn required(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            required: true,
+        }
+    }
+
+    pub fn optional(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            required: false,
+        }
+    }
+}
+
+/// A single plugin function: its id, the ids of the PFs it depends on,
+/// the user parameters it reads, the name of the type it resolves to
+/// (its "PF type", so [`ResultStore::try_get`] can report a clean error
+/// on a mismatch rather than an `unsafe` downcast going wrong), and the
+/// closure that computes its result from the store of already-run
+/// dependencies plus the run's user parameters.
+pub struct PluginFunction {
+    pub id: QualPfId,
+    pub deps: Vec<QualPfId>,
+    pub user_params: Vec<UserParamSpec>,
+    pub result_type_name: &'static str,
+    /// A JSON-schema-ish shape of this PF's result, derived from an
+    /// example value's `serde::Serialize` impl via
+    /// [`with_result_example`](Self::with_result_example) — `None` until
+    /// a PF opts in, since there's no way to produce one from `T` alone
+    /// without an instance to serialize.
+    result_schema: Option<serde_json::Value>,
+    run: Box<PfRun>,
+}
+
+impl PluginFunction {
+    /// `run`'s `Ok` type becomes this PF's declared result type: the
+    /// kernel boxes it for storage and records `type_name::<T>()` as the
+    /// tag [`ResultStore::try_get`] checks a later lookup against.
+    pub fn new<T: Any>(
+        id: QualPfId,
+        deps: Vec<QualPfId>,
+        run: impl Fn(&ResultStore, &UserParams) -> Result<T, String> + 'static,
+    ) -> Self {
+        Self {
+            id,
+            deps,
+            user_params: Vec::new(),
+            result_type_name: std::any::type_name::<T>(),
+            result_schema: None,
+            run: Box::new(move |results, params| {
+                run(results, params).map(|value| Box::new(value) as Box<dyn Any>)
+            }),
+        }
+    }
+
+    /// Declares the user parameters this PF reads, so the kernel can
+    /// validate required ones are present before the pipeline runs.
+    pub fn with_user_params(mut self, user_params: Vec<UserParamSpec>) -> Self {
+        self.user_params = user_params;
+        self
+    }
+
+    /// Records a representative `example` of this PF's result, so
+    /// [`Pipeline::manifest`] can describe the shape of what it produces
+    /// without anyone having to hand-write a schema or run the pipeline.
+    /// `example` is serialized once, immediately, via `serde::Serialize`
+    /// and reduced to a shape (each leaf value replaced by its JSON type
+    /// name, e.g. `"number"`/`"string"`) — see [`describe_shape`]. A
+    /// `Serialize` impl that fails (none of this crate's result types
+    /// do) leaves the schema unset rather than panicking at registration
+    /// time.
+    pub fn with_result_example(mut self, example: &impl serde::Serialize) -> Self {
+        self.result_schema = serde_json::to_value(example).ok().map(|value| describe_shape(&value));
+        self
+    }
+}
+
+/// Reduces a concrete JSON `value` to a schema-ish shape: an object or
+/// array keeps its structure, but every leaf is replaced by its JSON
+/// type name (`"string"`, `"number"`, `"boolean"`, `"null"`) and an
+/// array's shape is taken from its first element only (empty arrays
+/// report `"unknown"`, since there's no element to infer from).
+fn describe_shape(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Object(fields) => {
+            Value::Object(fields.iter().map(|(k, v)| (k.clone(), describe_shape(v))).collect())
+        }
+        Value::Array(items) => Value::Array(vec![
+            items.first().map(describe_shape).unwrap_or_else(|| Value::String("unknown".to_string())),
+        ]),
+        Value::Null => Value::String("null".to_string()),
+        Value::Bool(_) => Value::String("boolean".to_string()),
+        Value::Number(_) => Value::String("number".to_string()),
+        Value::String(_) => Value::String("string".to_string()),
+    }
+}
+
+/// One entry in [`Pipeline::manifest`]: everything a frontend needs to
+/// know about a PF ahead of running it — its id, what it depends on,
+/// what user parameters it reads, and (when available) the name and
+/// shape of what it produces.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PfManifestEntry {
+    pub id: QualPfId,
+    pub deps: Vec<QualPfId>,
+    pub user_params: Vec<UserParamSpec>,
+    pub result_type_name: &'static str,
+    pub result_schema: Option<serde_json::Value>,
+}
+
+/// The outcome of [`Pipeline::run`]: every PF's result, plus — when the
+/// run was profiled — how long each one took.
+#[derive(Debug)]
+pub struct PipelineOutput {
+    pub results: ResultStore,
+    pub timings: Option<BTreeMap<QualPfId, Duration>>,
+}
+
+/// A fixed set of plugin functions and the dependency edges between them,
+/// ready to be executed in topological order.
+pub struct Pipeline {
+    functions: Vec<PluginFunction>,
+}
+
+impl Pipeline {
+    pub fn new(functions: Vec<PluginFunction>) -> Self {
+        Self { functions }
+    }
+
+    /// Runs every registered PF exactly once, in an order that respects
+    /// dependencies, without recording timings. Equivalent to
+    /// `run_pipeline(user_params, false)`.
+    pub fn run(&self, user_params: &UserParams) -> Result<PipelineOutput, KernelError> {
+        self.run_pipeline(user_params, false)
+    }
+
+    /// Runs every registered PF exactly once, in an order that respects
+    /// dependencies. When `profile` is set, records each PF's wall-clock
+    /// execution time (gated behind this flag, rather than always on,
+    /// since `Instant::now()` calls aren't free on every platform and
+    /// most runs don't need the breakdown).
+    pub fn run_pipeline(
+        &self,
+        user_params: &UserParams,
+        profile: bool,
+    ) -> Result<PipelineOutput, KernelError> {
+        self.validate_user_params(user_params)?;
+        let order = self.topological_order()?;
+
+        let mut results = ResultStore::default();
+        let mut timings = profile.then(BTreeMap::new);
+
+        for id in order {
+            let pf = self
+                .functions
+                .iter()
+                .find(|pf| pf.id == id)
+                .expect("id came from this pipeline's own function list");
+
+            let started = profile.then(Instant::now);
+            let value = (pf.run)(&results, user_params).map_err(|message| {
+                KernelError::PluginFailed {
+                    pf: pf.id.clone(),
+                    message,
+                }
+            })?;
+            if let (Some(started), Some(timings)) = (started, timings.as_mut()) {
+                timings.insert(pf.id.clone(), started.elapsed());
+            }
+
+            results.insert(pf.id.clone(), pf.result_type_name, value);
+        }
+
+        Ok(PipelineOutput { results, timings })
+    }
+
+    /// Checks every registered PF's required user parameters are present
+    /// in `user_params`, so a run fails fast with a clear error instead
+    /// of however a PF's closure happens to react to a missing `get`.
+    fn validate_user_params(&self, user_params: &UserParams) -> Result<(), KernelError> {
+        for pf in &self.functions {
+            for spec in &pf.user_params {
+                if spec.required && !user_params.contains(&spec.name) {
+                    return Err(KernelError::MissingUserParameter {
+                        pf: pf.id.clone(),
+                        param: spec.name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares the names supplied in `user_params` against the union of
+    /// every registered PF's declared [`UserParamSpec`]s, returning a
+    /// warning message for each supplied name no PF declares — most
+    /// likely a typo, e.g. `ouput_path` for `output_path`. Unlike
+    /// [`Self::validate_user_params`], this never fails a run: an unknown
+    /// parameter is silently unused by every PF, not a hard error, so the
+    /// caller decides what to do with the warnings (print them, log them,
+    /// ignore them).
+    pub fn unknown_parameter_warnings(&self, user_params: &UserParams) -> Vec<String> {
+        let declared: Vec<&str> = self
+            .functions
+            .iter()
+            .flat_map(|pf| pf.user_params.iter().map(|spec| spec.name.as_str()))
+            .collect();
+        unknown_parameter_warnings_against(&declared, user_params)
+    }
+
+    /// `id`'s declared dependencies, or `None` if no PF with that id is
+    /// registered in this pipeline.
+    pub fn dependencies_of(&self, id: &QualPfId) -> Option<&[QualPfId]> {
+        self.functions.iter().find(|pf| &pf.id == id).map(|pf| pf.deps.as_slice())
+    }
+
+    /// `id`'s declared result type name (see [`PluginFunction::result_type_name`]),
+    /// or `None` if no PF with that id is registered in this pipeline —
+    /// the same tag [`PfManifestEntry`] and [`ResultStore::try_get`] use,
+    /// surfaced for callers that want it without going through the full
+    /// [`Pipeline::manifest`].
+    pub fn result_type_of(&self, id: &QualPfId) -> Option<&'static str> {
+        self.functions.iter().find(|pf| &pf.id == id).map(|pf| pf.result_type_name)
+    }
+
+    /// Every PF's declared [`UserParamSpec`]s, paired with the PF that
+    /// declared it, in [`QualPfId`] order rather than registration order
+    /// (the same reproducibility concern [`Self::topological_order`]
+    /// and [`Self::manifest`] already account for — `--plan` prints this
+    /// directly) — a single parameter name declared by more than one PF
+    /// (unusual, but not forbidden) appears once per declaring PF.
+    pub fn user_param_specs(&self) -> impl Iterator<Item = (&QualPfId, &UserParamSpec)> {
+        let mut functions: Vec<&PluginFunction> = self.functions.iter().collect();
+        functions.sort_by(|a, b| a.id.cmp(&b.id));
+        functions
+            .into_iter()
+            .flat_map(|pf| pf.user_params.iter().map(move |spec| (&pf.id, spec)))
+    }
+
+    /// A machine-readable manifest of every registered PF — id,
+    /// dependencies, declared user parameters, result type name, and
+    /// (when registered with [`PluginFunction::with_result_example`]) a
+    /// JSON-schema-ish shape of its result — in [`QualPfId`] order, so a
+    /// frontend can discover what's available and what each PF produces
+    /// without running the pipeline first.
+    pub fn manifest(&self) -> Vec<PfManifestEntry> {
+        let mut functions: Vec<&PluginFunction> = self.functions.iter().collect();
+        functions.sort_by(|a, b| a.id.cmp(&b.id));
+        functions
+            .into_iter()
+            .map(|pf| PfManifestEntry {
+                id: pf.id.clone(),
+                deps: pf.deps.clone(),
+                user_params: pf.user_params.clone(),
+                result_type_name: pf.result_type_name,
+                result_schema: pf.result_schema.clone(),
+            })
+            .collect()
+    }
+
+    /// Orders this pipeline's PFs so each comes after everything it
+    /// depends on, via a depth-first post-order traversal. Both the PFs
+    /// themselves and each PF's dependency list are visited in `QualPfId`
+    /// order rather than however they happen to be stored, so the result
+    /// depends only on the set of PFs and edges, not on the order
+    /// [`Self::new`] was given them — needed for reproducible output
+    /// across runs and machines. Detects both missing dependencies and
+    /// cycles rather than silently dropping or looping on either. Exposed
+    /// (beyond [`Self::run_pipeline`]'s own use of it) for callers like
+    /// `--plan` that want to show the resolved order without running
+    /// anything.
+    pub fn topological_order(&self) -> Result<Vec<QualPfId>, KernelError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            InProgress,
+            Done,
+        }
+
+        let mut marks: HashMap<&QualPfId, Mark> = HashMap::new();
+        let mut order = Vec::new();
+        let mut path = Vec::new();
+
+        fn visit<'a>(
+            pf: &'a PluginFunction,
+            all: &'a [PluginFunction],
+            marks: &mut HashMap<&'a QualPfId, Mark>,
+            path: &mut Vec<&'a QualPfId>,
+            order: &mut Vec<QualPfId>,
+        ) -> Result<(), KernelError> {
+            if let Some(mark) = marks.get(&pf.id) {
+                if *mark == Mark::InProgress {
+                    let start = path.iter().position(|id| *id == &pf.id).unwrap_or(0);
+                    let mut cycle: Vec<QualPfId> = path[start..].iter().map(|id| (*id).clone()).collect();
+                    cycle.push(pf.id.clone());
+                    return Err(KernelError::CyclicDependency { cycle });
+                }
+                return Ok(());
+            }
+
+            marks.insert(&pf.id, Mark::InProgress);
+            path.push(&pf.id);
+
+            let mut deps: Vec<&QualPfId> = pf.deps.iter().collect();
+            deps.sort();
+            for dep in deps {
+                let dep_pf = all.iter().find(|other| &other.id == dep).ok_or_else(|| {
+                    KernelError::MissingDependency {
+                        pf: pf.id.clone(),
+                        dep: dep.clone(),
+                    }
+                })?;
+                visit(dep_pf, all, marks, path, order)?;
+            }
+
+            path.pop();
+            marks.insert(&pf.id, Mark::Done);
+            order.push(pf.id.clone());
+            Ok(())
+        }
+
+        let mut functions: Vec<&PluginFunction> = self.functions.iter().collect();
+        functions.sort_by(|a, b| a.id.cmp(&b.id));
+        for pf in functions {
+            visit(pf, &self.functions, &mut marks, &mut path, &mut order)?;
+        }
+
+        Ok(order)
+    }
+}
+
+/// [`Pipeline::unknown_parameter_warnings`]'s comparison, generalized to
+/// callers that check supplied parameter names against a fixed list of
+/// declared ones without going through a [`Pipeline`] at all — e.g. a
+/// mode that only ever reads a handful of well-known parameters directly
+/// off a [`UserParams`] rather than running plugin functions.
+pub fn unknown_parameter_warnings_against(declared: &[&str], user_params: &UserParams) -> Vec<String> {
+    user_params
+        .keys()
+        .filter(|name| !declared.contains(name))
+        .map(|name| match closest_match(name, declared) {
+            Some(suggestion) => format!("unknown user parameter `{name}` (did you mean `{suggestion}`?)"),
+            None => format!("unknown user parameter `{name}`"),
+        })
+        .collect()
+}
+
+/// The candidate in `candidates` with the smallest [`levenshtein`]
+/// distance to `name`, as long as it's close enough to plausibly be a
+/// typo of `name` rather than an unrelated word. Ties keep the first
+/// candidate encountered.
+fn closest_match<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+    candidates
+        .iter()
+        .map(|candidate| (*candidate, levenshtein(name, candidate)))
+        .min_by_key(|(_, distance)| *distance)
+        .filter(|(_, distance)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .map(|(candidate, _)| candidate)
+}
+
+/// The classic dynamic-programming edit distance between two strings:
+/// the minimum number of single-character insertions, deletions, and
+/// substitutions to turn one into the other.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut prev_diagonal = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let above = row[j];
+            row[j] = if a[i - 1] == b[j - 1] {
+                prev_diagonal
+            } else {
+                1 + prev_diagonal.min(row[j - 1]).min(above)
+            };
+            prev_diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_dependencies_before_dependents() {
+        let a = QualPfId::new("test", "a");
+        let b = QualPfId::new("test", "b");
+
+        let dep = a.clone();
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(b.clone(), vec![a.clone()], move |results, _| {
+                let prior: i32 = *results.get::<i32>(&dep).unwrap();
+                Ok(prior + 1)
+            }),
+            PluginFunction::new(a.clone(), vec![], |_, _| Ok(41)),
+        ]);
+
+        let output = pipeline.run(&UserParams::new()).unwrap();
+        assert_eq!(*output.results.get::<i32>(&a).unwrap(), 41);
+        assert_eq!(*output.results.get::<i32>(&b).unwrap(), 42);
+    }
+
+    #[test]
+    fn try_get_with_the_wrong_type_returns_a_clean_error_instead_of_none() {
+        let a = QualPfId::new("test", "a");
+        let pipeline = Pipeline::new(vec![PluginFunction::new(a.clone(), vec![], |_, _| Ok(41i32))]);
+
+        let output = pipeline.run(&UserParams::new()).unwrap();
+
+        assert_eq!(*output.results.try_get::<i32>(&a).unwrap(), 41);
+
+        let err = output.results.try_get::<String>(&a).unwrap_err();
+        assert!(matches!(
+            err,
+            KernelError::ResultTypeMismatch { ref id, .. } if *id == a
+        ));
+        assert!(err.to_string().contains("i32"));
+
+        let missing = QualPfId::new("test", "missing");
+        let err = output.results.try_get::<i32>(&missing).unwrap_err();
+        assert!(matches!(err, KernelError::MissingResult { ref id } if *id == missing));
+    }
+
+    #[test]
+    fn result_type_of_names_a_registered_pfs_declared_type() {
+        let a = QualPfId::new("test", "a");
+        let pipeline = Pipeline::new(vec![PluginFunction::new(a.clone(), vec![], |_, _| Ok(41i32))]);
+
+        assert!(pipeline.result_type_of(&a).unwrap().contains("i32"));
+        assert!(pipeline.result_type_of(&QualPfId::new("test", "missing")).is_none());
+    }
+
+    #[test]
+    fn dependencies_of_lists_a_registered_pfs_declared_deps() {
+        let a = QualPfId::new("test", "a");
+        let b = QualPfId::new("test", "b");
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(a.clone(), vec![], |_, _| Ok(())),
+            PluginFunction::new(b.clone(), vec![a.clone()], |_, _| Ok(())),
+        ]);
+
+        assert_eq!(pipeline.dependencies_of(&b).unwrap(), std::slice::from_ref(&a));
+        assert!(pipeline.dependencies_of(&a).unwrap().is_empty());
+        assert!(pipeline.dependencies_of(&QualPfId::new("test", "missing")).is_none());
+    }
+
+    /// A PF that reads its dependency's result via `try_get` (rather than
+    /// `get().expect(...)`) turns a would-be type mismatch into an
+    /// ordinary [`KernelError::PluginFailed`] the caller can handle, not
+    /// an `unsafe` downcast going wrong or a panic — the failure mode a
+    /// dependency's declared result type ever diverging from what a
+    /// consumer expects (e.g. two plugins independently redefining what's
+    /// meant to be one shared result type) should hit.
+    #[test]
+    fn a_plugin_reading_its_dependency_as_the_wrong_type_via_try_get_fails_cleanly_instead_of_panicking() {
+        let a = QualPfId::new("test", "a");
+        let b = QualPfId::new("test", "b");
+        let dep = a.clone();
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(a.clone(), vec![], |_, _| Ok(41i32)),
+            PluginFunction::new(b.clone(), vec![a.clone()], move |results, _| {
+                let prior = results.try_get::<String>(&dep).map_err(|e| e.to_string())?;
+                Ok(format!("{prior}!"))
+            }),
+        ]);
+
+        let err = pipeline.run(&UserParams::new()).unwrap_err();
+        assert!(matches!(err, KernelError::PluginFailed { ref pf, .. } if *pf == b));
+        assert!(err.to_string().contains("i32"));
+    }
+
+    #[test]
+    fn topological_order_lists_parse_project_before_build_cfg() {
+        let parse_project = QualPfId::new("cfg", "parse_project");
+        let build_cfg = QualPfId::new("cfg", "build_cfg");
+
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(build_cfg.clone(), vec![parse_project.clone()], |_, _| Ok(())),
+            PluginFunction::new(parse_project.clone(), vec![], |_, _| Ok(())),
+        ]);
+
+        let order = pipeline.topological_order().unwrap();
+        let parse_index = order.iter().position(|id| *id == parse_project).unwrap();
+        let build_index = order.iter().position(|id| *id == build_cfg).unwrap();
+        assert!(parse_index < build_index);
+    }
+
+    #[test]
+    fn topological_order_is_independent_of_the_order_functions_were_registered_in() {
+        let a = QualPfId::new("test", "a");
+        let b = QualPfId::new("test", "b");
+        let c = QualPfId::new("test", "c");
+
+        let make = |functions: Vec<PluginFunction>| Pipeline::new(functions).topological_order().unwrap();
+
+        let first = make(vec![
+            PluginFunction::new(c.clone(), vec![a.clone(), b.clone()], |_, _| Ok(())),
+            PluginFunction::new(b.clone(), vec![], |_, _| Ok(())),
+            PluginFunction::new(a.clone(), vec![], |_, _| Ok(())),
+        ]);
+        let second = make(vec![
+            PluginFunction::new(a.clone(), vec![], |_, _| Ok(())),
+            PluginFunction::new(b.clone(), vec![], |_, _| Ok(())),
+            PluginFunction::new(c.clone(), vec![b.clone(), a.clone()], |_, _| Ok(())),
+        ]);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn user_param_specs_lists_every_pfs_declared_params() {
+        let a = QualPfId::new("test", "a");
+        let pipeline = Pipeline::new(vec![PluginFunction::new(a.clone(), vec![], |_, _| Ok(()))
+            .with_user_params(vec![UserParamSpec::required("path")])]);
+
+        let specs: Vec<_> = pipeline.user_param_specs().collect();
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].0, &a);
+        assert_eq!(specs[0].1.name, "path");
+        assert!(specs[0].1.required);
+    }
+
+    #[test]
+    fn user_param_specs_order_is_independent_of_registration_order() {
+        let a = QualPfId::new("test", "a");
+        let b = QualPfId::new("test", "b");
+
+        let make = |functions: Vec<PluginFunction>| {
+            Pipeline::new(functions)
+                .user_param_specs()
+                .map(|(id, spec)| (id.clone(), spec.name.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let first = make(vec![
+            PluginFunction::new(b.clone(), vec![], |_, _| Ok(()))
+                .with_user_params(vec![UserParamSpec::required("y")]),
+            PluginFunction::new(a.clone(), vec![], |_, _| Ok(()))
+                .with_user_params(vec![UserParamSpec::required("x")]),
+        ]);
+        let second = make(vec![
+            PluginFunction::new(a.clone(), vec![], |_, _| Ok(()))
+                .with_user_params(vec![UserParamSpec::required("x")]),
+            PluginFunction::new(b.clone(), vec![], |_, _| Ok(()))
+                .with_user_params(vec![UserParamSpec::required("y")]),
+        ]);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn reports_a_cyclic_dependency() {
+        let a = QualPfId::new("test", "a");
+        let b = QualPfId::new("test", "b");
+
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(a.clone(), vec![b.clone()], |_, _| Ok(())),
+            PluginFunction::new(b.clone(), vec![a.clone()], |_, _| Ok(())),
+        ]);
+
+        let err = pipeline.run(&UserParams::new()).unwrap_err();
+        assert!(matches!(err, KernelError::CyclicDependency { .. }));
+    }
+
+    #[test]
+    fn reports_a_missing_dependency() {
+        let a = QualPfId::new("test", "a");
+        let missing = QualPfId::new("test", "missing");
+
+        let pipeline = Pipeline::new(vec![PluginFunction::new(
+            a.clone(),
+            vec![missing.clone()],
+            |_, _| Ok(()),
+        )]);
+
+        let err = pipeline.run(&UserParams::new()).unwrap_err();
+        assert!(matches!(err, KernelError::MissingDependency { .. }));
+    }
+
+    #[test]
+    fn profiling_records_a_timing_entry_per_plugin_function() {
+        let a = QualPfId::new("test", "a");
+        let pipeline = Pipeline::new(vec![PluginFunction::new(a.clone(), vec![], |_, _| {
+            Ok(())
+        })]);
+
+        let output = pipeline.run_pipeline(&UserParams::new(), true).unwrap();
+        let timings = output.timings.expect("profiling was requested");
+        assert!(timings.contains_key(&a));
+    }
+
+    #[test]
+    fn rejects_a_param_argument_with_no_equals_sign() {
+        let Err(err) = parse_key_value_params(&["project_path".to_string()]) else {
+            panic!("expected an error for a `key=value` argument with no `=`")
+        };
+        assert!(err.contains("project_path"), "error should name the malformed argument: {err}");
+    }
+
+    #[test]
+    fn runs_a_plugin_that_needs_a_project_path_user_parameter() {
+        let read = QualPfId::new("test", "read_project");
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(read.clone(), vec![], |_results, params| {
+                let project_path = params
+                    .get::<String>("project_path")
+                    .ok_or_else(|| "missing project_path".to_string())?;
+                Ok(project_path.clone())
+            })
+            .with_user_params(vec![UserParamSpec::required("project_path")]),
+        ]);
+
+        let params = parse_key_value_params(&["project_path=/tmp/example".to_string()]).unwrap();
+        let output = pipeline.run(&params).unwrap();
+        assert_eq!(
+            output.results.get::<String>(&read).unwrap(),
+            "/tmp/example"
+        );
+    }
+
+    #[test]
+    fn missing_a_required_user_parameter_fails_before_any_plugin_runs() {
+        let read = QualPfId::new("test", "read_project");
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(read.clone(), vec![], |_results, _params| -> Result<(), String> {
+                panic!("should not run: required parameter was never supplied")
+            })
+            .with_user_params(vec![UserParamSpec::required("project_path")]),
+        ]);
+
+        let err = pipeline.run(&UserParams::new()).unwrap_err();
+        assert!(matches!(
+            err,
+            KernelError::MissingUserParameter { param, .. } if param == "project_path"
+        ));
+    }
+
+    #[test]
+    fn manifest_describes_a_registered_pfs_result_shape_from_its_example() {
+        #[derive(serde::Serialize)]
+        struct Inner {
+            count: usize,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Example {
+            functions: Vec<Inner>,
+            cyclomatic_complexity: usize,
+        }
+
+        let complexity = QualPfId::new("complexity", "analyze");
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(complexity.clone(), vec![], |_, _| Ok(()))
+                .with_result_example(&Example {
+                    functions: vec![Inner { count: 1 }],
+                    cyclomatic_complexity: 3,
+                }),
+        ]);
+
+        let manifest = pipeline.manifest();
+        assert_eq!(manifest.len(), 1);
+        let entry = &manifest[0];
+        assert_eq!(entry.id, complexity);
+
+        let schema = entry.result_schema.as_ref().expect("example was registered");
+        assert_eq!(schema["cyclomatic_complexity"], "number");
+        assert_eq!(schema["functions"][0]["count"], "number");
+    }
+
+    #[test]
+    fn manifest_describes_an_empty_array_field_as_unknown_shape() {
+        #[derive(serde::Serialize)]
+        struct Example {
+            functions: Vec<usize>,
+        }
+
+        let pf = QualPfId::new("test", "a");
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(pf, vec![], |_, _| Ok(()))
+                .with_result_example(&Example { functions: Vec::new() }),
+        ]);
+
+        let manifest = pipeline.manifest();
+        let schema = manifest[0].result_schema.as_ref().expect("example was registered");
+        assert_eq!(schema["functions"][0], "unknown");
+    }
+
+    #[test]
+    fn manifest_leaves_the_schema_unset_when_no_example_was_registered() {
+        let a = QualPfId::new("test", "a");
+        let pipeline = Pipeline::new(vec![PluginFunction::new(a, vec![], |_, _| Ok(()))]);
+
+        let manifest = pipeline.manifest();
+        assert!(manifest[0].result_schema.is_none());
+    }
+
+    #[test]
+    fn warns_about_a_misspelled_user_parameter_with_a_suggestion() {
+        let pf = QualPfId::new("test", "write_report");
+        let pipeline = Pipeline::new(vec![
+            PluginFunction::new(pf, vec![], |_results, _params| Ok(()))
+                .with_user_params(vec![UserParamSpec::optional("output_path")]),
+        ]);
+
+        let params = parse_key_value_params(&["ouput_path=/tmp/report.txt".to_string()]).unwrap();
+        let warnings = pipeline.unknown_parameter_warnings(&params);
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("ouput_path"));
+        assert!(warnings[0].contains("output_path"));
+    }
+
+    #[test]
+    fn warns_about_a_misspelled_parameter_without_a_pipeline() {
+        let params = parse_key_value_params(&["fial_on_level=high".to_string()]).unwrap();
+        let warnings = unknown_parameter_warnings_against(&["fail_on_level"], &params);
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("fial_on_level"));
+        assert!(warnings[0].contains("fail_on_level"));
+    }
+}
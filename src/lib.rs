@@ -0,0 +1,30 @@
+//! SkanUJkod: a general-purpose static analysis framework for Go source
+//! code. See `doc/architecture/decisions` for the design rationale.
+
+pub mod ast_export;
+pub mod ast_search;
+pub mod call_graph;
+pub mod cfg_plugin;
+pub mod changed_functions;
+pub mod clones;
+pub mod commands;
+pub mod complexity;
+pub mod config;
+pub mod const_eval;
+pub mod coverage;
+pub mod diagnostics;
+pub mod features;
+pub mod func_counts;
+pub mod git_metrics;
+pub mod go_parser;
+pub mod graphviz;
+pub mod imports;
+pub mod interfaces;
+pub mod kernel;
+pub mod lints;
+pub mod model;
+pub mod parse_cache;
+pub mod result_delta;
+pub mod run_summary;
+pub mod sloc;
+pub mod watch;
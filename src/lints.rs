@@ -0,0 +1,1085 @@
+//! Lint-style checks over a parsed project that flag common bug
+//! patterns rather than measuring anything (that's `complexity`/`sloc`).
+
+use goscript_parser::ast::{self, Expr, Node, Stmt};
+
+use crate::ast_search;
+use crate::cfg_plugin;
+use crate::go_parser::{self, AstObjects, ParseDirResult, Pos, Token, line_of};
+use crate::model::FunctionId;
+
+/// A `for` loop whose condition is missing (`for {}`) or the literal
+/// `true` (`for true {}`) and that has no path out, found in `function`
+/// at `line`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct LoopConditionFinding {
+    pub function: FunctionId,
+    pub line: usize,
+}
+
+/// Whether a `for` loop's condition is missing or the literal `true` —
+/// the two syntactic ways to write a loop with no condition of its own
+/// to ever exit on.
+fn is_unconditionally_true(cond: &Option<Expr>, objects: &AstObjects) -> bool {
+    match cond {
+        None => true,
+        Some(Expr::Ident(key)) => objects.idents[*key].name == "true",
+        Some(_) => false,
+    }
+}
+
+/// The first unconditionally-true `for` loop's position found by
+/// scanning `stmts`, descending into blocks/`if`s/labels but not into
+/// nested loops or switches, whose bodies are not searched.
+fn find_trivial_for(stmts: &[Stmt], objects: &AstObjects) -> Option<Pos> {
+    for stmt in stmts {
+        let found = match stmt {
+            Stmt::For(f) if is_unconditionally_true(&f.cond, objects) => Some(f.for_pos),
+            Stmt::Block(b) => find_trivial_for(&b.list, objects),
+            Stmt::If(i) => find_trivial_for(&i.body.list, objects).or_else(|| {
+                i.els
+                    .as_ref()
+                    .and_then(|els| find_trivial_for(std::slice::from_ref(els), objects))
+            }),
+            Stmt::Labeled(key) => {
+                find_trivial_for(std::slice::from_ref(&objects.l_stmts[*key].stmt), objects)
+            }
+            _ => None,
+        };
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+/// Flags every function whose body contains a `for` loop with no
+/// condition (or the literal `true`) and no path out — `break` included,
+/// since a `return`/`panic` buried in the loop still reaches this
+/// function's own exit, and so isn't reported as unreachable here.
+pub fn empty_or_trivial_loop_conditions(parsed: &ParseDirResult) -> Vec<LoopConditionFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let cfg = cfg_plugin::build_cfg(body, &parsed.objects);
+                if cfg.is_reachable(cfg.exit) {
+                    continue;
+                }
+
+                let Some(pos) = find_trivial_for(&body.list, &parsed.objects) else {
+                    continue;
+                };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                findings.push(LoopConditionFinding {
+                    function: FunctionId::new(pkg.name.clone(), file_name.clone(), name),
+                    line: line_of(&pf.source, pf.base, pos),
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// A variable declared in a nested scope under the same name as one
+/// already visible from an enclosing scope in the same function — the
+/// inner declaration shadows the outer one for the rest of its block
+/// rather than reusing it, a common source of Go bugs where a `:=`
+/// inside an `if`/`for` init was meant to assign to the outer variable.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ShadowedVariableFinding {
+    pub function: FunctionId,
+    pub name: String,
+    pub shadowing_line: usize,
+    pub shadowed_line: usize,
+}
+
+/// The names a `:=` or `var` declaration statement introduces, each
+/// paired with the position to report it at. Anything else (a plain
+/// `=` assignment, `const`/`type` declarations, ...) introduces no new
+/// scope entries and yields nothing. `_` is never reported: it's Go's
+/// blank identifier, not a variable that can be shadowed.
+fn declared_names(stmt: &Stmt, objects: &AstObjects) -> Vec<(String, Pos)> {
+    match stmt {
+        Stmt::Assign(key) => {
+            let assign = &objects.a_stmts[*key];
+            if assign.token != Token::DEFINE {
+                return Vec::new();
+            }
+            assign
+                .lhs
+                .iter()
+                .filter_map(|expr| match expr {
+                    Expr::Ident(id) => Some(&objects.idents[*id]),
+                    _ => None,
+                })
+                .filter(|ident| ident.name != "_")
+                .map(|ident| (ident.name.clone(), ident.pos))
+                .collect()
+        }
+        Stmt::Decl(decl) => match decl.as_ref() {
+            ast::Decl::Gen(gen_decl) if gen_decl.token == Token::VAR => gen_decl
+                .specs
+                .iter()
+                .filter_map(|spec_key| match &objects.specs[*spec_key] {
+                    ast::Spec::Value(value) => Some(&value.names),
+                    _ => None,
+                })
+                .flatten()
+                .map(|id| &objects.idents[*id])
+                .filter(|ident| ident.name != "_")
+                .map(|ident| (ident.name.clone(), ident.pos))
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Walks `stmts` with `scopes` as the stack of scopes enclosing them
+/// (innermost last), recording every `:=`/`var` declaration into the
+/// current (last) scope and reporting one against any name it finds
+/// already declared in an *outer* scope — an inner block reusing an
+/// outer scope's own name via a later `:=`/`var` in the same block is a
+/// re-declaration, not a shadow, and Go itself doesn't allow either
+/// form to redeclare a name already `:=`-bound in the very same block,
+/// so that case can't arise here.
+fn find_shadows_in(
+    stmts: &[Stmt],
+    objects: &AstObjects,
+    scopes: &mut Vec<std::collections::HashMap<String, Pos>>,
+    findings: &mut Vec<(String, Pos, Pos)>,
+) {
+    for stmt in stmts {
+        for (name, pos) in declared_names(stmt, objects) {
+            if let Some(&shadowed_pos) = scopes[..scopes.len() - 1]
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(&name))
+            {
+                findings.push((name.clone(), pos, shadowed_pos));
+            }
+            scopes.last_mut().unwrap().insert(name, pos);
+        }
+
+        match stmt {
+            Stmt::Block(b) => {
+                scopes.push(std::collections::HashMap::new());
+                find_shadows_in(&b.list, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::If(i) => {
+                scopes.push(std::collections::HashMap::new());
+                if let Some(init) = &i.init {
+                    find_shadows_in(std::slice::from_ref(init), objects, scopes, findings);
+                }
+                find_shadows_in(&i.body.list, objects, scopes, findings);
+                if let Some(els) = &i.els {
+                    find_shadows_in(std::slice::from_ref(els), objects, scopes, findings);
+                }
+                scopes.pop();
+            }
+            Stmt::For(f) => {
+                scopes.push(std::collections::HashMap::new());
+                if let Some(init) = &f.init {
+                    find_shadows_in(std::slice::from_ref(init), objects, scopes, findings);
+                }
+                find_shadows_in(&f.body.list, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::Range(r) => {
+                scopes.push(std::collections::HashMap::new());
+                find_shadows_in(&r.body.list, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::Switch(sw) => {
+                scopes.push(std::collections::HashMap::new());
+                find_shadows_in(&sw.body.list, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::TypeSwitch(sw) => {
+                scopes.push(std::collections::HashMap::new());
+                find_shadows_in(&sw.body.list, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::Case(case) => {
+                scopes.push(std::collections::HashMap::new());
+                find_shadows_in(&case.body, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::Select(sel) => {
+                scopes.push(std::collections::HashMap::new());
+                find_shadows_in(&sel.body.list, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::Comm(comm) => {
+                scopes.push(std::collections::HashMap::new());
+                if let Some(comm_stmt) = &comm.comm {
+                    find_shadows_in(std::slice::from_ref(comm_stmt), objects, scopes, findings);
+                }
+                find_shadows_in(&comm.body, objects, scopes, findings);
+                scopes.pop();
+            }
+            Stmt::Labeled(key) => {
+                let labeled = &objects.l_stmts[*key];
+                find_shadows_in(std::slice::from_ref(&labeled.stmt), objects, scopes, findings);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Flags every `:=`/`var` declaration in a function whose name is
+/// already declared in an enclosing scope of that same function —
+/// parameters count as the outermost scope, so shadowing a parameter is
+/// caught too. Doesn't cross function boundaries: a closure literal's
+/// own body is out of scope for this walk (see
+/// [`crate::complexity::analyze_statement_for_decision_point`] for the
+/// same simplification elsewhere in this crate).
+pub fn find_shadowed_declarations(parsed: &ParseDirResult) -> Vec<ShadowedVariableFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let mut params = std::collections::HashMap::new();
+                let ftype = &parsed.objects.ftypes[fdecl.typ];
+                for field_key in &ftype.params.list {
+                    let field = &parsed.objects.fields[*field_key];
+                    for id in &field.names {
+                        let ident = &parsed.objects.idents[*id];
+                        if ident.name != "_" {
+                            params.insert(ident.name.clone(), ident.pos);
+                        }
+                    }
+                }
+
+                let mut scopes = vec![params, std::collections::HashMap::new()];
+                let mut raw_findings = Vec::new();
+                find_shadows_in(&body.list, &parsed.objects, &mut scopes, &mut raw_findings);
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function = FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+                for (var_name, shadowing_pos, shadowed_pos) in raw_findings {
+                    findings.push(ShadowedVariableFinding {
+                        function: function.clone(),
+                        name: var_name,
+                        shadowing_line: line_of(&pf.source, pf.base, shadowing_pos),
+                        shadowed_line: line_of(&pf.source, pf.base, shadowed_pos),
+                    });
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// Controls how aggressively [`find_ignored_errors`] guesses that a
+/// result is error-shaped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IgnoredErrorOptions {
+    /// When set, a result named `err`/`Err` (regardless of its declared
+    /// type) counts as error-shaped too, on top of the always-on check
+    /// for a result whose declared type is literally `error`. This
+    /// catches more real bugs but also more false positives — a result
+    /// named `err` that's actually, say, a `*MyError` wrapper type isn't
+    /// unusual and isn't always meant to be checked at every call site.
+    pub strict: bool,
+}
+
+/// A call to a function declared in the same package whose result
+/// includes an error-shaped return that the caller neither captured nor
+/// checked: a bare call statement (nothing captured at all) or an
+/// assignment that discards it into `_`.
+///
+/// This is a heuristic, not a type-checked lint: this crate parses Go
+/// syntax but never resolves types, so "error-shaped" means "a result
+/// whose declared type is literally `error`" (or, in
+/// [`IgnoredErrorOptions::strict`] mode, also "named `err`/`Err`"), and
+/// only calls to functions declared in the same package are looked at —
+/// a call into another package or into the standard library has no
+/// signature in `parsed` to check, so it's silently skipped rather than
+/// guessed at.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct IgnoredErrorFinding {
+    pub function: FunctionId,
+    pub line: usize,
+    pub called: String,
+}
+
+/// Every result position in `fields`, in declaration order, alongside
+/// its own declared name if it has one — a result field can name
+/// several results of the same type at once (`(a, b int)`), so this
+/// expands each into its own entry the way `field_list_len` counts them.
+fn field_list_names_and_types(fields: &ast::FieldList, objects: &AstObjects) -> Vec<(Option<String>, Expr)> {
+    fields
+        .list
+        .iter()
+        .flat_map(|key| {
+            let field = &objects.fields[*key];
+            if field.names.is_empty() {
+                vec![(None, field.typ.clone())]
+            } else {
+                field
+                    .names
+                    .iter()
+                    .map(|id| (Some(objects.idents[*id].name.clone()), field.typ.clone()))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Whether a result's declared type or name suggests it's an error,
+/// under `options`. See [`IgnoredErrorFinding`] for what "suggests"
+/// means here.
+fn looks_like_error(name: &Option<String>, typ: &Expr, objects: &AstObjects, options: &IgnoredErrorOptions) -> bool {
+    if let Expr::Ident(id) = typ
+        && objects.idents[*id].name == "error"
+    {
+        return true;
+    }
+    options.strict && name.as_deref().is_some_and(|n| n.eq_ignore_ascii_case("err"))
+}
+
+/// For every function declared in `parsed`, the per-result "is this
+/// error-shaped" flags [`looks_like_error`] computes for its declared
+/// results, keyed by function name. Only usable within the same package
+/// a call appears in — see [`IgnoredErrorFinding`]'s doc comment for why
+/// cross-package calls aren't resolved at all.
+fn error_shaped_results_by_function(
+    pkg: &go_parser::Package,
+    objects: &AstObjects,
+    options: &IgnoredErrorOptions,
+) -> std::collections::HashMap<String, Vec<bool>> {
+    let mut index = std::collections::HashMap::new();
+    for pf in pkg.files.values() {
+        for decl in &pf.ast.decls {
+            let ast::Decl::Func(key) = decl else { continue };
+            let fdecl = &objects.fdecls[*key];
+            if fdecl.recv.is_some() {
+                // Heuristic only covers plain function calls (a bare
+                // `Expr::Ident` callee); a method call goes through a
+                // selector expression, which isn't resolved here.
+                continue;
+            }
+            let ftype = &objects.ftypes[fdecl.typ];
+            let Some(results) = &ftype.results else { continue };
+            let flags = field_list_names_and_types(results, objects)
+                .iter()
+                .map(|(name, typ)| looks_like_error(name, typ, objects, options))
+                .collect();
+            let name = objects.idents[fdecl.name].name.clone();
+            index.insert(name, flags);
+        }
+    }
+    index
+}
+
+/// If `expr` is a call to a plain-identifier callee found in `index`
+/// with at least one error-shaped result, its callee name and result
+/// flags — otherwise `None`.
+fn error_returning_call<'a>(
+    expr: &Expr,
+    objects: &AstObjects,
+    index: &'a std::collections::HashMap<String, Vec<bool>>,
+) -> Option<(&'a str, &'a [bool])> {
+    let Expr::Call(call) = expr else { return None };
+    let Expr::Ident(id) = &call.func else { return None };
+    let name = &objects.idents[*id].name;
+    let (key, flags) = index.get_key_value(name.as_str())?;
+    flags.iter().any(|&is_err| is_err).then_some((key.as_str(), flags.as_slice()))
+}
+
+/// Flags every statement-level call in `parsed` to a same-package
+/// function with an error-shaped result that the caller drops — see
+/// [`IgnoredErrorFinding`] for exactly what counts as "drops".
+pub fn find_ignored_errors(parsed: &ParseDirResult, options: &IgnoredErrorOptions) -> Vec<IgnoredErrorFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        let index = error_shaped_results_by_function(pkg, &parsed.objects, options);
+        if index.is_empty() {
+            continue;
+        }
+
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function = FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+
+                crate::ast_search::walk_stmts(&body.list, &parsed.objects, &mut |stmt| {
+                    match stmt {
+                        Stmt::Expr(expr) => {
+                            // A bare call statement: nothing captured at
+                            // all, so any error-shaped result is dropped.
+                            if let Some((called, _)) = error_returning_call(expr, &parsed.objects, &index) {
+                                findings.push(IgnoredErrorFinding {
+                                    function: function.clone(),
+                                    line: line_of(&pf.source, pf.base, expr.pos(&parsed.objects)),
+                                    called: called.to_string(),
+                                });
+                            }
+                        }
+                        Stmt::Assign(akey) => {
+                            let assign = &parsed.objects.a_stmts[*akey];
+                            let [rhs] = assign.rhs.as_slice() else { return };
+                            let Some((called, flags)) = error_returning_call(rhs, &parsed.objects, &index) else {
+                                return;
+                            };
+                            // A multi-value assignment (`val, _ := f()`)
+                            // discards only the positions bound to `_`;
+                            // a single-value one (`_ = f()`) discards
+                            // whichever single result there is.
+                            let discards_an_error = assign.lhs.iter().enumerate().any(|(i, e)| {
+                                matches!(e, Expr::Ident(id) if parsed.objects.idents[*id].name == "_")
+                                    && flags.get(i).copied().unwrap_or(false)
+                            });
+                            if discards_an_error {
+                                findings.push(IgnoredErrorFinding {
+                                    function: function.clone(),
+                                    line: line_of(&pf.source, pf.base, assign.token_pos),
+                                    called: called.to_string(),
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// A marker comment (`TODO`, `FIXME`, ...) found in a source comment,
+/// with its location and the comment's own text.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CommentMarkerFinding {
+    pub file: String,
+    pub line: usize,
+    pub marker: String,
+    pub text: String,
+}
+
+/// The marker comments [`find_comment_markers`] looks for when a caller
+/// doesn't supply its own list — the variants most code-health tooling
+/// treats as interchangeable "someone meant to come back to this".
+pub const DEFAULT_COMMENT_MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Whether `marker` appears in `text` as a whole word — so `FIXME`
+/// matches `// FIXME: retry` but not `// PREFIXME_CONST`.
+fn contains_marker_word(text: &str, marker: &str) -> bool {
+    let bytes = text.as_bytes();
+    let marker_bytes = marker.as_bytes();
+    let is_word_byte = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+
+    text.match_indices(marker).any(|(start, _)| {
+        let end = start + marker_bytes.len();
+        let before_ok = start == 0 || !is_word_byte(bytes[start - 1]);
+        let after_ok = end == bytes.len() || !is_word_byte(bytes[end]);
+        before_ok && after_ok
+    })
+}
+
+/// Scans every comment in `parsed` for any of `markers` appearing as a
+/// whole word, reporting each hit's file, 1-based line, the marker that
+/// matched, and the comment's own text (so a caller can show it without
+/// re-reading the source).
+pub fn find_comment_markers(parsed: &ParseDirResult, markers: &[&str]) -> Vec<CommentMarkerFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for (pos, tok, text) in go_parser::tokenize(&pf.source) {
+                let Token::COMMENT(_) = tok else { continue };
+                for &marker in markers {
+                    if contains_marker_word(&text, marker) {
+                        findings.push(CommentMarkerFinding {
+                            file: file_name.clone(),
+                            line: line_of(&pf.source, 0, pos),
+                            marker: marker.to_string(),
+                            text: text.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// How many of each marker [`find_comment_markers`] found, for a one-
+/// line-per-marker project summary.
+pub fn count_by_marker(findings: &[CommentMarkerFinding]) -> std::collections::BTreeMap<String, usize> {
+    let mut counts = std::collections::BTreeMap::new();
+    for finding in findings {
+        *counts.entry(finding.marker.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// An `if` whose condition [`const_eval::eval_const_bool`] can fold to a
+/// fixed `true`/`false` without running the program — meaning one of its
+/// branches (`then` if `value` is `false`, `else` if `value` is `true`
+/// and an `else` exists) never runs.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ConstantConditionFinding {
+    pub function: FunctionId,
+    pub line: usize,
+    pub value: bool,
+}
+
+/// Scans every function in `parsed` for `if` statements whose condition
+/// folds to a constant, via [`ast_search::walk_stmts`] so nested `if`s
+/// (inside loops, other `if`s, ...) are found too, not just top-level
+/// ones.
+pub fn find_constant_conditions(parsed: &ParseDirResult) -> Vec<ConstantConditionFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+
+                crate::ast_search::walk_stmts(&body.list, &parsed.objects, &mut |stmt| {
+                    let Stmt::If(if_stmt) = stmt else { return };
+                    let Some(value) = crate::const_eval::eval_const_bool(&if_stmt.cond, &parsed.objects)
+                    else {
+                        return;
+                    };
+                    findings.push(ConstantConditionFinding {
+                        function: FunctionId::new(pkg.name.clone(), file_name.clone(), name.clone()),
+                        line: line_of(&pf.source, pf.base, if_stmt.if_pos),
+                        value,
+                    });
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// A function (or method — the receiver lives in [`ast::FuncDecl::recv`],
+/// never in [`ast::FuncType::params`], so it's never counted here)
+/// declaring more than `threshold` parameters, one grouped name (`a, b
+/// int` counting as 2, the same way [`complexity::field_list_len`]
+/// counts them) at a time. A long parameter list makes call sites easy
+/// to get wrong by silently swapping two same-typed arguments; bundling
+/// the parameters into a config/options struct is the usual fix, which
+/// [`suggest_options_struct`] spells out.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct LongParameterListFinding {
+    pub function: FunctionId,
+    pub line: usize,
+    pub parameter_count: usize,
+}
+
+impl LongParameterListFinding {
+    /// A human-readable nudge toward grouping this function's parameters
+    /// into a struct, for callers that print findings as text rather than
+    /// consuming the struct directly.
+    pub fn suggest_options_struct(&self) -> String {
+        format!(
+            "{} takes {} parameters; consider grouping them into a config/options struct",
+            self.function, self.parameter_count
+        )
+    }
+}
+
+/// Flags every function or method in `parsed` whose parameter count
+/// exceeds `threshold`. See [`LongParameterListFinding`].
+pub fn find_long_parameter_lists(parsed: &ParseDirResult, threshold: usize) -> Vec<LongParameterListFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let ftype = &parsed.objects.ftypes[fdecl.typ];
+                let parameter_count = crate::complexity::field_list_len(&ftype.params, &parsed.objects);
+                if parameter_count <= threshold {
+                    continue;
+                }
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                findings.push(LongParameterListFinding {
+                    function: FunctionId::new(pkg.name.clone(), file_name.clone(), name),
+                    line: line_of(&pf.source, pf.base, parsed.objects.idents[fdecl.name].pos),
+                    parameter_count,
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// The `return` count threshold [`find_excessive_returns`] uses when a
+/// caller doesn't supply its own — the same role
+/// [`DEFAULT_COMMENT_MARKERS`] plays for [`find_comment_markers`].
+pub const DEFAULT_MAX_RETURNS: usize = 3;
+
+/// A function whose `return` count exceeds a configured threshold —
+/// beyond a certain point, more exit points make a function harder to
+/// reason about the same way high cyclomatic complexity does, even when
+/// each individual `return` is trivial.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ExcessiveReturnsFinding {
+    pub function: FunctionId,
+    pub line: usize,
+    pub return_count: usize,
+    /// 1-based line number of every `return` in the function, in source
+    /// order — for a verbose report that wants to point at each one;
+    /// most callers only need `return_count`.
+    pub return_lines: Vec<usize>,
+}
+
+/// Flags every function or method in `parsed` with more than
+/// `threshold` `return` statements. See [`ExcessiveReturnsFinding`].
+pub fn find_excessive_returns(parsed: &ParseDirResult, threshold: usize) -> Vec<ExcessiveReturnsFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let mut return_lines = Vec::new();
+                ast_search::walk_stmts(&body.list, &parsed.objects, &mut |stmt| {
+                    if let Stmt::Return(ret) = stmt {
+                        return_lines.push(line_of(&pf.source, pf.base, ret.ret));
+                    }
+                });
+                if return_lines.len() <= threshold {
+                    continue;
+                }
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                findings.push(ExcessiveReturnsFinding {
+                    function: FunctionId::new(pkg.name.clone(), file_name.clone(), name),
+                    line: line_of(&pf.source, pf.base, parsed.objects.idents[fdecl.name].pos),
+                    return_count: return_lines.len(),
+                    return_lines,
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// What kind of body [`find_empty_branches`] found empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum EmptyBranchKind {
+    If,
+    Else,
+    For,
+    Case,
+    /// A `switch`/`type switch`'s `default:` case — split out from
+    /// [`EmptyBranchKind::Case`] so [`EmptyBranchOptions::ignore_empty_default_case`]
+    /// can silence just this one, since an empty `default` is often
+    /// deliberate ("nothing to do for any other value") rather than a
+    /// leftover TODO the way an empty non-default case usually is.
+    DefaultCase,
+}
+
+impl std::fmt::Display for EmptyBranchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EmptyBranchKind::If => "if",
+            EmptyBranchKind::Else => "else",
+            EmptyBranchKind::For => "for",
+            EmptyBranchKind::Case => "case",
+            EmptyBranchKind::DefaultCase => "default case",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// An `if`/`else` body, `for`/`range` loop body, or `switch`/`type
+/// switch` `case` body with no statements — often a bug (the branch was
+/// meant to do something) or a leftover TODO rather than intentional.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct EmptyBranchFinding {
+    pub function: FunctionId,
+    pub line: usize,
+    pub kind: EmptyBranchKind,
+}
+
+/// Controls how aggressively [`find_empty_branches`] reports empty
+/// `case` bodies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmptyBranchOptions {
+    /// When set, an empty `default:` case is not reported — see
+    /// [`EmptyBranchKind::DefaultCase`] for why that one's often
+    /// intentional where an empty non-default case usually isn't.
+    pub ignore_empty_default_case: bool,
+}
+
+/// Flags every empty `if`/`else` body, `for`/`range` loop body, and
+/// `switch`/`type switch` `case` body in `parsed`. See
+/// [`EmptyBranchFinding`] and [`EmptyBranchOptions`].
+pub fn find_empty_branches(parsed: &ParseDirResult, options: &EmptyBranchOptions) -> Vec<EmptyBranchFinding> {
+    let mut findings = Vec::new();
+
+    for pkg in parsed.packages.values() {
+        for (file_name, pf) in &pkg.files {
+            for decl in &pf.ast.decls {
+                let ast::Decl::Func(key) = decl else { continue };
+                let fdecl = &parsed.objects.fdecls[*key];
+                let Some(body) = &fdecl.body else { continue };
+
+                let name = parsed.objects.idents[fdecl.name].name.clone();
+                let function = FunctionId::new(pkg.name.clone(), file_name.clone(), name);
+
+                ast_search::walk_stmts(&body.list, &parsed.objects, &mut |stmt| match stmt {
+                    Stmt::If(i) => {
+                        if i.body.list.is_empty() {
+                            findings.push(EmptyBranchFinding {
+                                function: function.clone(),
+                                line: line_of(&pf.source, pf.base, i.if_pos),
+                                kind: EmptyBranchKind::If,
+                            });
+                        }
+                        if let Some(Stmt::Block(b)) = &i.els
+                            && b.list.is_empty()
+                        {
+                            findings.push(EmptyBranchFinding {
+                                function: function.clone(),
+                                line: line_of(&pf.source, pf.base, b.l_brace),
+                                kind: EmptyBranchKind::Else,
+                            });
+                        }
+                    }
+                    Stmt::For(f) if f.body.list.is_empty() => {
+                        findings.push(EmptyBranchFinding {
+                            function: function.clone(),
+                            line: line_of(&pf.source, pf.base, f.for_pos),
+                            kind: EmptyBranchKind::For,
+                        });
+                    }
+                    Stmt::Range(r) if r.body.list.is_empty() => {
+                        findings.push(EmptyBranchFinding {
+                            function: function.clone(),
+                            line: line_of(&pf.source, pf.base, r.for_pos),
+                            kind: EmptyBranchKind::For,
+                        });
+                    }
+                    Stmt::Case(case) if case.body.is_empty() => {
+                        let is_default = case.list.is_none();
+                        if is_default && options.ignore_empty_default_case {
+                            return;
+                        }
+                        findings.push(EmptyBranchFinding {
+                            function: function.clone(),
+                            line: line_of(&pf.source, pf.base, case.case),
+                            kind: if is_default { EmptyBranchKind::DefaultCase } else { EmptyBranchKind::Case },
+                        });
+                    }
+                    _ => {}
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    fn parse_one(src: &str) -> ParseDirResult {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir =
+            std::env::temp_dir().join(format!("skanujkod-lints-test-{}-{n}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::File::create(dir.join("a.go"))
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        let result = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        result
+    }
+
+    #[test]
+    fn flags_an_infinite_loop_with_no_break() {
+        let parsed = parse_one("package main\nfunc f() {\n\tfor {\n\t\tx := 1\n\t\t_ = x\n\t}\n}\n");
+        let findings = empty_or_trivial_loop_conditions(&parsed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 3);
+    }
+
+    #[test]
+    fn does_not_flag_an_infinite_loop_with_a_break() {
+        let parsed =
+            parse_one("package main\nfunc f() {\n\tfor {\n\t\tbreak\n\t}\n}\n");
+        assert!(empty_or_trivial_loop_conditions(&parsed).is_empty());
+    }
+
+    #[test]
+    fn flags_a_literal_true_condition_with_no_break() {
+        let parsed = parse_one("package main\nfunc f() {\n\tfor true {\n\t\tx := 1\n\t\t_ = x\n\t}\n}\n");
+        let findings = empty_or_trivial_loop_conditions(&parsed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 3);
+    }
+
+    #[test]
+    fn does_not_flag_a_loop_with_a_real_condition() {
+        let parsed =
+            parse_one("package main\nfunc f(n int) {\n\tfor n > 0 {\n\t\tn--\n\t}\n}\n");
+        assert!(empty_or_trivial_loop_conditions(&parsed).is_empty());
+    }
+
+    #[test]
+    fn an_inner_scope_redeclaration_of_an_outer_variable_is_flagged_as_a_shadow() {
+        let parsed = parse_one(
+            "package main\nfunc f() error {\n\tx := 1\n\tif true {\n\t\tx := 2\n\t\t_ = x\n\t}\n\treturn nil\n}\n",
+        );
+        let findings = find_shadowed_declarations(&parsed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].name, "x");
+        assert_eq!(findings[0].shadowed_line, 3);
+        assert_eq!(findings[0].shadowing_line, 5);
+    }
+
+    #[test]
+    fn a_declaration_inside_a_select_case_shadowing_an_outer_variable_is_flagged() {
+        let parsed = parse_one(
+            "package main\nfunc f(ch chan int) {\n\tx := 1\n\tselect {\n\tcase v := <-ch:\n\t\tx := v\n\t\t_ = x\n\t}\n\t_ = x\n}\n",
+        );
+        let findings = find_shadowed_declarations(&parsed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].name, "x");
+    }
+
+    #[test]
+    fn disjoint_sibling_blocks_reusing_a_name_are_not_flagged() {
+        let parsed = parse_one(
+            "package main\nfunc f() {\n\tif true {\n\t\tx := 1\n\t\t_ = x\n\t}\n\tif true {\n\t\tx := 2\n\t\t_ = x\n\t}\n}\n",
+        );
+        assert!(find_shadowed_declarations(&parsed).is_empty());
+    }
+
+    #[test]
+    fn a_bare_call_statement_dropping_an_error_result_is_flagged() {
+        let parsed = parse_one(
+            "package main\n\nfunc doWork() error {\n\treturn nil\n}\n\nfunc f() {\n\tdoWork()\n}\n",
+        );
+        let findings = find_ignored_errors(&parsed, &IgnoredErrorOptions::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].called, "doWork");
+        assert_eq!(findings[0].line, 8);
+    }
+
+    #[test]
+    fn blank_assignment_discarding_the_error_result_is_flagged() {
+        let parsed = parse_one(
+            "package main\n\nfunc doWork() (int, error) {\n\treturn 1, nil\n}\n\nfunc f() {\n\tv, _ := doWork()\n\t_ = v\n}\n",
+        );
+        let findings = find_ignored_errors(&parsed, &IgnoredErrorOptions::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].called, "doWork");
+    }
+
+    #[test]
+    fn an_error_checked_with_if_is_not_flagged() {
+        let parsed = parse_one(
+            "package main\n\nfunc doWork() error {\n\treturn nil\n}\n\nfunc f() error {\n\tif err := doWork(); err != nil {\n\t\treturn err\n\t}\n\treturn nil\n}\n",
+        );
+        assert!(find_ignored_errors(&parsed, &IgnoredErrorOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn a_result_merely_named_err_is_only_flagged_in_strict_mode() {
+        let parsed = parse_one(
+            "package main\n\nfunc doWork() (result int, err string) {\n\treturn 1, \"\"\n}\n\nfunc f() {\n\tdoWork()\n}\n",
+        );
+        assert!(find_ignored_errors(&parsed, &IgnoredErrorOptions::default()).is_empty());
+        let strict = find_ignored_errors(&parsed, &IgnoredErrorOptions { strict: true });
+        assert_eq!(strict.len(), 1);
+    }
+
+    #[test]
+    fn finds_a_todo_comment_at_the_right_line() {
+        let parsed = parse_one("package main\n\n// TODO: fix this\nfunc f() {}\n");
+        let findings = find_comment_markers(&parsed, DEFAULT_COMMENT_MARKERS);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 3);
+        assert_eq!(findings[0].marker, "TODO");
+        assert_eq!(findings[0].text, "// TODO: fix this");
+    }
+
+    #[test]
+    fn a_marker_embedded_in_a_longer_word_is_not_flagged() {
+        let parsed = parse_one("package main\n\n// PREFIXME_CONST is unrelated\nfunc f() {}\n");
+        assert!(find_comment_markers(&parsed, DEFAULT_COMMENT_MARKERS).is_empty());
+    }
+
+    #[test]
+    fn counts_by_marker_tally_each_kind_separately() {
+        let parsed = parse_one(
+            "package main\n\n// TODO: one\n// TODO: two\n// FIXME: three\nfunc f() {}\n",
+        );
+        let findings = find_comment_markers(&parsed, DEFAULT_COMMENT_MARKERS);
+        let counts = count_by_marker(&findings);
+        assert_eq!(counts["TODO"], 2);
+        assert_eq!(counts["FIXME"], 1);
+    }
+
+    #[test]
+    fn flags_a_literal_true_if_condition() {
+        let parsed = parse_one("package main\nfunc f() {\n\tif true {\n\t\t_ = 1\n\t}\n}\n");
+        let findings = find_constant_conditions(&parsed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 3);
+        assert!(findings[0].value);
+    }
+
+    #[test]
+    fn flags_a_literal_false_if_condition() {
+        let parsed = parse_one("package main\nfunc f() {\n\tif false {\n\t\t_ = 1\n\t}\n}\n");
+        let findings = find_constant_conditions(&parsed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 3);
+        assert!(!findings[0].value);
+    }
+
+    #[test]
+    fn flags_a_constant_comparison_if_condition() {
+        let parsed = parse_one("package main\nfunc f() {\n\tif 2 > 1 {\n\t\t_ = 1\n\t}\n}\n");
+        let findings = find_constant_conditions(&parsed);
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].value);
+    }
+
+    #[test]
+    fn does_not_flag_an_if_over_a_variable_condition() {
+        let parsed = parse_one("package main\nfunc f(n int) {\n\tif n > 0 {\n\t\t_ = 1\n\t}\n}\n");
+        assert!(find_constant_conditions(&parsed).is_empty());
+    }
+
+    #[test]
+    fn a_function_with_more_params_than_the_threshold_is_flagged() {
+        let parsed = parse_one("package main\nfunc f(a, b, c, d, e, f int) {}\n");
+        let findings = find_long_parameter_lists(&parsed, 5);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].parameter_count, 6);
+        assert!(findings[0].suggest_options_struct().contains("config/options struct"));
+    }
+
+    #[test]
+    fn a_method_with_params_at_the_threshold_is_not_flagged_by_its_receiver() {
+        let parsed = parse_one(
+            "package main\ntype T struct{}\nfunc (t T) f(a, b, c, d, e int) {}\n",
+        );
+        assert!(find_long_parameter_lists(&parsed, 5).is_empty());
+    }
+
+    #[test]
+    fn a_function_with_four_returns_is_flagged_at_limit_three() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) int {\n\tif x > 3 {\n\t\treturn 3\n\t}\n\tif x > 2 {\n\t\treturn 2\n\t}\n\tif x > 1 {\n\t\treturn 1\n\t}\n\treturn 0\n}\n",
+        );
+        let findings = find_excessive_returns(&parsed, 3);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].return_count, 4);
+        assert_eq!(findings[0].return_lines, vec![4, 7, 10, 12]);
+    }
+
+    #[test]
+    fn a_function_with_a_single_return_is_not_flagged() {
+        let parsed = parse_one("package main\nfunc f() int {\n\treturn 0\n}\n");
+        assert!(find_excessive_returns(&parsed, 3).is_empty());
+    }
+
+    #[test]
+    fn flags_an_empty_if_body() {
+        let parsed = parse_one("package main\nfunc f(x int) {\n\tif x > 0 {\n\t}\n}\n");
+        let findings = find_empty_branches(&parsed, &EmptyBranchOptions::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, EmptyBranchKind::If);
+        assert_eq!(findings[0].line, 3);
+    }
+
+    #[test]
+    fn flags_an_empty_else_body() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) {\n\tif x > 0 {\n\t\t_ = x\n\t} else {\n\t}\n}\n",
+        );
+        let findings = find_empty_branches(&parsed, &EmptyBranchOptions::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, EmptyBranchKind::Else);
+    }
+
+    #[test]
+    fn a_non_empty_if_body_is_not_flagged() {
+        let parsed = parse_one("package main\nfunc f(x int) {\n\tif x > 0 {\n\t\t_ = x\n\t}\n}\n");
+        assert!(find_empty_branches(&parsed, &EmptyBranchOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn flags_an_empty_for_body_but_not_a_non_empty_range_body() {
+        let parsed = parse_one(
+            "package main\nfunc f(xs []int) {\n\tfor _, x := range xs {\n\t\t_ = x\n\t}\n\tfor {\n\t}\n}\n",
+        );
+        let findings = find_empty_branches(&parsed, &EmptyBranchOptions::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, EmptyBranchKind::For);
+    }
+
+    #[test]
+    fn an_empty_default_case_is_flagged_unless_the_option_ignores_it() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) {\n\tswitch x {\n\tcase 1:\n\t\t_ = x\n\tdefault:\n\t}\n}\n",
+        );
+        let findings = find_empty_branches(&parsed, &EmptyBranchOptions::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, EmptyBranchKind::DefaultCase);
+
+        let ignoring = EmptyBranchOptions { ignore_empty_default_case: true };
+        assert!(find_empty_branches(&parsed, &ignoring).is_empty());
+    }
+
+    #[test]
+    fn an_empty_non_default_case_is_flagged() {
+        let parsed = parse_one(
+            "package main\nfunc f(x int) {\n\tswitch x {\n\tcase 1:\n\tdefault:\n\t\t_ = x\n\t}\n}\n",
+        );
+        let findings = find_empty_branches(&parsed, &EmptyBranchOptions::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, EmptyBranchKind::Case);
+    }
+}
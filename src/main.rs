@@ -1,4 +1,1756 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use skan_uj_kod::ast_export::{self, AstNode};
+use skan_uj_kod::call_graph::{self, CouplingOptions, CouplingReport};
+use skan_uj_kod::cfg_plugin;
+use skan_uj_kod::changed_functions;
+use skan_uj_kod::clones::{self, CloneReport};
+use skan_uj_kod::commands::all;
+use skan_uj_kod::complexity::{self, ComplexityLevel, ComplexityOptions, ComplexityReport, FunctionComplexity};
+use skan_uj_kod::config;
+use skan_uj_kod::coverage::{
+    self, CoveragePresentationOptions, CoverageSortKey, ProjectBranchCoverage, ProjectStatementCoverage,
+};
+use skan_uj_kod::diagnostics::{self, Diagnostic};
+use skan_uj_kod::features::{self, FeatureSummary};
+use skan_uj_kod::func_counts::{self, FuncCounts};
+use skan_uj_kod::git_metrics;
+use skan_uj_kod::go_parser::{self, ParseDirResult};
+use skan_uj_kod::graphviz::{self, DotImageFormat};
+use skan_uj_kod::imports::{self, ImportGraph};
+use skan_uj_kod::interfaces;
+use skan_uj_kod::kernel::{
+    Pipeline, PluginFunction, QualPfId, UserParamSpec, UserParams, unknown_parameter_warnings_against,
+};
+use skan_uj_kod::model::FunctionId;
+use skan_uj_kod::sloc::LineCounts;
+
+/// Prints the resolved topological order of `pipeline`'s plugin
+/// functions, each with its dependencies, followed by every declared
+/// user parameter and whether `params` actually supplies it — the
+/// `--plan` flag's whole job, with nothing run.
+fn print_execution_plan(pipeline: &Pipeline, params: &UserParams) {
+    let order = match pipeline.topological_order() {
+        Ok(order) => order,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("execution plan:");
+    for id in &order {
+        let deps = pipeline.dependencies_of(id).unwrap_or(&[]);
+        let result_type = pipeline.result_type_of(id).unwrap_or("?");
+        if deps.is_empty() {
+            println!("  {id} -> {result_type}");
+        } else {
+            let deps = deps.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+            println!("  {id} -> {result_type} (depends on: {deps})");
+        }
+    }
+
+    println!("\nresolved user params:");
+    for (pf, spec) in pipeline.user_param_specs() {
+        let kind = if spec.required { "required" } else { "optional" };
+        let status = if params.contains(&spec.name) {
+            "supplied"
+        } else if spec.required {
+            "MISSING"
+        } else {
+            "not supplied"
+        };
+        println!("  {pf}: {} [{kind}] — {status}", spec.name);
+    }
+}
+use skan_uj_kod::lints::{
+    self, CommentMarkerFinding, ConstantConditionFinding, EmptyBranchFinding, EmptyBranchOptions,
+    ExcessiveReturnsFinding, IgnoredErrorFinding, LoopConditionFinding, ShadowedVariableFinding,
+};
+use skan_uj_kod::parse_cache;
+use skan_uj_kod::result_delta;
+use skan_uj_kod::run_summary;
+use skan_uj_kod::watch::{self, RecvOutcome};
+
+/// SkanUJkod: static analysis for Go source code.
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+    /// Directory to analyze. Required unless a subcommand is given.
+    path: Option<PathBuf>,
+    /// Print how long each plugin function took to run.
+    #[arg(long)]
+    profile: bool,
+    /// Extra user parameter for a plugin function, as `key=value`. Can be
+    /// given multiple times; values are always passed through as strings.
+    #[arg(long = "param", value_name = "KEY=VALUE")]
+    params: Vec<String>,
+    /// Read parameters from this `skan.toml` instead of looking for one
+    /// at `path`. See [`skan_uj_kod::config`]. A `--param` flag for the
+    /// same key always overrides the config file's value.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Note whether `path` looks unchanged since the last run with this
+    /// flag, based on a fingerprint cached alongside it. This can't skip
+    /// parsing itself — there's no way to persist the parsed AST across
+    /// processes, see `skan_uj_kod::parse_cache` — it's only a heads-up
+    /// that the run you're about to do probably won't find anything new.
+    #[arg(long)]
+    reuse_parse: bool,
+    /// Print diagnostics (high complexity, unreachable code, missing
+    /// return, infinite loop) as a JSON array of LSP-style
+    /// `{ file, range, severity, code, message }` records, instead of
+    /// the normal human-readable report — for feeding an editor
+    /// integration rather than a terminal.
+    #[arg(long)]
+    diagnostics: bool,
+    /// Print the resolved execution plan — the topological order of
+    /// plugin functions, their dependencies, and which user parameters
+    /// are declared vs. actually supplied — then exit without running
+    /// any plugin function.
+    #[arg(long)]
+    plan: bool,
+    /// Read a single Go source file from stdin instead of walking a
+    /// project directory, and run complexity analysis on it. Useful for
+    /// one-off checks and editor "analyze selection" integrations, where
+    /// there's no project directory to parse. Incompatible with `path`
+    /// and the subcommands, which all name a directory on disk.
+    #[arg(long)]
+    stdin: bool,
+    /// Print a JSON manifest of every plugin function — id, dependencies,
+    /// declared user parameters, and (where known) the shape of its
+    /// result — then exit without parsing anything. For a frontend that
+    /// wants to know ahead of time what's available and what each
+    /// analysis produces, without running a project through the
+    /// pipeline first.
+    #[arg(long)]
+    manifest: bool,
+    /// Stream complexity results as NDJSON (one [`FunctionComplexity`]
+    /// JSON object per line) as each file is parsed, instead of running
+    /// the usual pipeline and printing one buffered report at the end.
+    /// For a huge project where holding every function's result (and
+    /// the whole parsed project) in memory at once is the bottleneck.
+    /// Requires `path`; incompatible with `--stdin` and the subcommands.
+    #[arg(long)]
+    ndjson: bool,
+    /// Print each parsed file's AST as a JSON tree of
+    /// `{ kind, name, start, end, children }` nodes, instead of the
+    /// normal human-readable report — for downstream tools (including
+    /// non-Rust ones) that want the parsed structure without linking
+    /// against this crate or the vendored Go parser.
+    #[arg(long = "ast-json")]
+    ast_json: bool,
+    /// Print the complexity report in `gocyclo`'s own text format
+    /// (`complexity package func file:line:column`, sorted descending by
+    /// complexity), instead of the normal human-readable report — for
+    /// piping into a `gocyclo`-compatible pipeline.
+    #[arg(long)]
+    gocyclo: bool,
+    /// Print each file's [`skan_uj_kod::complexity::FileComplexitySummary`]
+    /// as JSON, instead of the normal human-readable report — a
+    /// types/vars/consts-only file gets `average_complexity: null`
+    /// rather than a misleading `0.0` that would read as "every function
+    /// here is trivial" when there's nothing here to average.
+    #[arg(long = "complexity-by-file")]
+    complexity_by_file: bool,
+    /// Print a machine-readable [`skan_uj_kod::run_summary::RunSummary`]
+    /// (files parsed, functions analyzed, policy violations, wall time)
+    /// as a JSON footer after the normal report — for a CI dashboard
+    /// tracking analysis health over time without having to scrape the
+    /// human-readable output.
+    #[arg(long)]
+    summary: bool,
+    /// Write the package-level import graph (see [`skan_uj_kod::imports`])
+    /// as Graphviz DOT to this path instead of printing it in the normal
+    /// report — for feeding into `dot -Tsvg` or another graph viewer.
+    #[arg(long = "imports-dot")]
+    imports_dot: Option<PathBuf>,
+    /// Write every function's CFG (see [`skan_uj_kod::cfg_plugin::to_dot_combined`])
+    /// as one combined Graphviz DOT file to this path, one cluster per
+    /// function. A block label longer than 60 characters is truncated
+    /// with a trailing `...`; when that happens, a sidecar JSON file is
+    /// also written alongside it (same path, `.json` extension) mapping
+    /// each truncated block's namespaced node id to its full text.
+    #[arg(long = "cfg-dot")]
+    cfg_dot: Option<PathBuf>,
+    /// Also render `--imports-dot`/`--cfg-dot` output to an image via the
+    /// system `dot` binary (see [`skan_uj_kod::graphviz::export_dot_graph`]),
+    /// alongside the `.dot` file it always writes. Silently downgrades to
+    /// a warning on stderr if Graphviz isn't installed or fails to
+    /// run — the `.dot` file is written either way.
+    #[arg(long = "render-graphviz")]
+    render_graphviz: bool,
+    /// Image format for `--render-graphviz`: `svg` (the default) or `png`.
+    #[arg(long = "render-format", value_name = "svg|png")]
+    render_format: Option<String>,
+    /// Write the complexity distribution as a themed HTML bar chart with
+    /// a legend (see [`skan_uj_kod::complexity::render_html_histogram`])
+    /// to this path, instead of the ASCII histogram printed by default.
+    #[arg(long = "complexity-html")]
+    complexity_html: Option<PathBuf>,
+    /// Theme for `--complexity-html`: `light` (the default) or `dark`.
+    #[arg(long = "complexity-html-theme", value_name = "light|dark")]
+    complexity_html_theme: Option<String>,
+    /// A `go test -coverprofile` file to read branch/statement coverage
+    /// from (see [`skan_uj_kod::coverage`]). Without it, coverage
+    /// analyses still run, but every branch and statement is reported
+    /// uncovered.
+    #[arg(long = "coverage-profile")]
+    coverage_profile: Option<PathBuf>,
+    /// Write an annotated HTML source view of statement coverage (see
+    /// [`skan_uj_kod::coverage::write_html_coverage_report`]) to this
+    /// directory, one page per source file plus an `index.html`.
+    #[arg(long = "coverage-html")]
+    coverage_html: Option<PathBuf>,
+    /// How to order the per-function statement coverage table: `least-covered`
+    /// (the default — lowest coverage fraction first) or `name`.
+    #[arg(long = "coverage-sort", value_name = "least-covered|name")]
+    coverage_sort: Option<String>,
+    /// Keep only the first N functions of the (sorted) statement coverage
+    /// table, instead of printing every function in the project.
+    #[arg(long = "coverage-top")]
+    coverage_top: Option<usize>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Watch `project_path` for `.go` file changes and re-run analysis on
+    /// each one, debouncing rapid successive saves.
+    Watch {
+        project_path: PathBuf,
+        /// Extra user parameter for a plugin function, as `key=value`.
+        #[arg(long = "param", value_name = "KEY=VALUE")]
+        params: Vec<String>,
+        /// Read parameters from this `skan.toml` instead of looking for
+        /// one at `project_path`.
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Run every analysis that needs nothing beyond a project path against
+    /// one or more `project_paths` — several modules of a multi-repo/
+    /// multi-module checkout analyzed as one combined report — and write
+    /// each one's report under `output_dir`, plus an `index.json` listing
+    /// what was produced. See `skan_uj_kod::commands::all`.
+    All {
+        project_paths: Vec<PathBuf>,
+        #[arg(long = "output-dir")]
+        output_dir: PathBuf,
+    },
+    /// Report commit-history metrics for `repo_path` — a local repository
+    /// path, or a Git URL to clone into a temporary directory first. See
+    /// `skan_uj_kod::git_metrics`.
+    GitMetrics {
+        repo_path: String,
+        /// Print each metric's result as pretty JSON (see
+        /// [`git_metrics::MetricResultType`]'s `Display` impl) instead of
+        /// the normal human-readable per-line output.
+        #[arg(long)]
+        json: bool,
+        /// When `repo_path` is a URL, clone only its most recent commits
+        /// instead of full history (see [`git_metrics::CloneOptions`]).
+        /// Ignored for a local path.
+        #[arg(long = "shallow-depth")]
+        shallow_depth: Option<i32>,
+        /// Instead of the usual commit-count metrics, print
+        /// [`git_metrics::complexity_trend`]'s average/max complexity at
+        /// each commit (see [`git_metrics::parse_at_commit`]) — the "is
+        /// our code getting worse?" time series.
+        #[arg(long)]
+        trend: bool,
+        /// Limit `--trend` to this many most-recent commits instead of
+        /// walking full history (see [`git_metrics::CommitWalkOptions`]).
+        /// Ignored without `--trend`.
+        #[arg(long = "max-commits")]
+        max_commits: Option<usize>,
+    },
+    /// Run complexity analysis on `project_path`, a git repository, but
+    /// print only the functions a diff of `old_ref` against `new_ref`
+    /// touched — the "only review what changed" workflow. See
+    /// `skan_uj_kod::changed_functions`.
+    ChangedFunctions {
+        project_path: PathBuf,
+        old_ref: String,
+        new_ref: String,
+    },
+}
+
+const PARSE_CACHE_FILE_NAME: &str = ".skanujkod-parse-cache.json";
+
+fn note_whether_unchanged_since_last_run(path: &std::path::Path) {
+    let cache_path = path.join(PARSE_CACHE_FILE_NAME);
+    let fresh = match parse_cache::fingerprint_project(path) {
+        Ok(fingerprint) => fingerprint,
+        Err(err) => {
+            eprintln!("warning: couldn't fingerprint `{}` for --reuse-parse: {err}", path.display());
+            return;
+        }
+    };
+
+    match parse_cache::load_fingerprint(&cache_path) {
+        Some(previous) if previous == fresh => {
+            eprintln!("note: no source files changed since the last --reuse-parse run");
+        }
+        _ => {
+            eprintln!("note: project changed (or no cache yet); parsing fresh");
+        }
+    }
+
+    if let Err(err) = parse_cache::save_fingerprint(&cache_path, &fresh) {
+        eprintln!("warning: couldn't write --reuse-parse cache: {err}");
+    }
+}
+
+/// Every user parameter [`complexity_options_from_params`] reads, plus
+/// `max_function_lines` (read by [`print_analysis_summary`]), shared
+/// with the `complexity` plugin function's own `with_user_params` list so
+/// the two can't drift apart, and with the watch/stdin/ndjson modes below
+/// (which read these same parameters directly, without going through a
+/// [`Pipeline`]) so a misspelled one is still caught there too.
+const COMPLEXITY_USER_PARAM_NAMES: &[&str] = &[
+    "fail_on_level",
+    "function_filter",
+    "max_allowed_complexity",
+    "max_params",
+    "low_max",
+    "moderate_max",
+    "high_max",
+    "switch_counting",
+    "exported_only",
+    "max_function_lines",
+];
+
+/// Reads a `usize` user param, falling back to `default` when it wasn't
+/// supplied.
+fn usize_param(params: &UserParams, name: &str, default: usize) -> Result<usize, String> {
+    match params.get::<String>(name) {
+        Some(value) => value.parse::<usize>().map_err(|_| format!("invalid {name} `{value}`")),
+        None => Ok(default),
+    }
+}
+
+/// Reads an optional `usize` user param, returning `None` when it
+/// wasn't supplied instead of falling back to a default — for a limit
+/// like `max_function_lines` where "not set" and "set to 0" mean
+/// different things.
+fn usize_param_opt(params: &UserParams, name: &str) -> Result<Option<usize>, String> {
+    match params.get::<String>(name) {
+        Some(value) => value.parse::<usize>().map(Some).map_err(|_| format!("invalid {name} `{value}`")),
+        None => Ok(None),
+    }
+}
+
+/// Writes `dot_source` to `dot_path`, then, if `render` is set,
+/// additionally renders it to an image alongside it via
+/// [`graphviz::export_dot_graph`] — a missing or failing `dot` binary
+/// only prints a warning, since `dot_path` was written either way.
+/// `format_name` is `cli.render_format`, defaulting to SVG for anything
+/// other than `"png"`.
+fn write_dot_output(dot_path: &Path, dot_source: &str, render: bool, format_name: Option<&str>) {
+    let format = match format_name {
+        Some("png") => DotImageFormat::Png,
+        _ => DotImageFormat::Svg,
+    };
+    let path_without_ext = dot_path.with_extension("");
+    match graphviz::export_dot_graph(dot_source, &path_without_ext, format, render) {
+        Ok(outcome) => {
+            if let Some(warning) = outcome.warning {
+                eprintln!("warning: {warning}");
+            }
+        }
+        Err(err) => {
+            eprintln!("error: couldn't write DOT to `{}`: {err}", dot_path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// A trailing `" (suppressed)"` for a function annotated with one of
+/// [`complexity::SUPPRESS_COMPLEXITY_MARKERS`], so the text report still
+/// shows its real complexity number but flags that it was excluded from
+/// the threshold checks — empty for a function that wasn't.
+fn suppressed_note(fc: &FunctionComplexity) -> &'static str {
+    if fc.suppressed {
+        " (suppressed)"
+    } else {
+        ""
+    }
+}
+
+/// Prints `report.timings`, when [`ComplexityOptions::profile`] was set —
+/// how long each function's own complexity analysis took, complementing
+/// `--profile`'s per-plugin-function timing breakdown with a per-function
+/// one for the analyzer that usually dominates a run's wall time.
+fn print_complexity_timings(report: &ComplexityReport) {
+    if let Some(timings) = &report.timings {
+        println!("\ncomplexity timings:");
+        for (function, duration) in timings {
+            println!("  {function}: {duration:?}");
+        }
+    }
+}
+
+/// Prints [`complexity::render_ascii_histogram`]'s distribution and
+/// [`ComplexityReport::percentiles`], when there's at least one function
+/// to summarize — `percentiles` returns `None` on an empty report, in
+/// which case a histogram of all-zero bars wouldn't say anything useful
+/// either.
+fn print_complexity_histogram(report: &ComplexityReport) {
+    let Some(percentiles) = report.percentiles() else { return };
+    println!("\ncomplexity distribution:");
+    print!("{}", complexity::render_ascii_histogram(report));
+    println!("median {}, p90 {}", percentiles.median, percentiles.p90);
+}
+
+/// Prints [`ComplexityReport::function_length_report`] — average and
+/// max physical-line length, plus every function over `max_lines` when
+/// one was supplied — when there's at least one function to summarize.
+fn print_function_length_report(report: &ComplexityReport, max_lines: Option<usize>) {
+    let Some(length_report) = report.function_length_report(max_lines) else { return };
+    println!(
+        "\nfunction length: average {:.1} lines, max {} lines",
+        length_report.average_lines, length_report.max_lines
+    );
+    for function in &length_report.violations {
+        println!("  {function} exceeds {} lines", max_lines.unwrap());
+    }
+}
+
+fn complexity_options_from_params(params: &UserParams) -> Result<ComplexityOptions, String> {
+    let fail_on_level = match params.get::<String>("fail_on_level") {
+        Some(level) => Some(
+            complexity::parse_level(level).ok_or_else(|| format!("invalid fail_on_level `{level}`"))?,
+        ),
+        None => None,
+    };
+    let max_allowed_complexity = match params.get::<String>("max_allowed_complexity") {
+        Some(value) => Some(
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("invalid max_allowed_complexity `{value}`"))?,
+        ),
+        None => None,
+    };
+    let max_params = match params.get::<String>("max_params") {
+        Some(value) => Some(
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("invalid max_params `{value}`"))?,
+        ),
+        None => None,
+    };
+    let default_boundaries = complexity::ComplexityLevelBoundaries::default();
+    let boundaries = complexity::ComplexityLevelBoundaries {
+        low_max: usize_param(params, "low_max", default_boundaries.low_max)?,
+        moderate_max: usize_param(params, "moderate_max", default_boundaries.moderate_max)?,
+        high_max: usize_param(params, "high_max", default_boundaries.high_max)?,
+    };
+    let switch_counting = match params.get::<String>("switch_counting") {
+        Some(mode) => complexity::parse_switch_counting(mode)
+            .ok_or_else(|| format!("invalid switch_counting `{mode}` (expected `per_case` or `per_switch`)"))?,
+        None => complexity::SwitchCounting::default(),
+    };
+    Ok(ComplexityOptions {
+        boundaries,
+        fail_on_level,
+        function_filter: params.get::<String>("function_filter").cloned(),
+        max_allowed_complexity,
+        max_params,
+        switch_counting,
+        profile: params.get::<bool>("profile").copied().unwrap_or(false),
+        exported_only: params.get::<String>("exported_only").is_some_and(|v| v == "true"),
+    })
+}
+
+/// Prints the usual human-readable watch-mode report, and returns the
+/// complexity snapshot it was built from — keyed by [`FunctionId`], the
+/// shape [`result_delta::diff`] expects — so a caller across several
+/// runs can diff one snapshot against the next instead of only ever
+/// seeing the full report.
+fn print_analysis_summary(
+    parsed: &ParseDirResult,
+    params: &UserParams,
+) -> BTreeMap<FunctionId, FunctionComplexity> {
+    let options = match complexity_options_from_params(params) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return BTreeMap::new();
+        }
+    };
+    let report = complexity::analyze_function_complexity(parsed, &options);
+    for fc in &report.functions {
+        println!(
+            "{}.{}: complexity {} ({:?}), nesting depth {}, {} lines, longest path {} blocks{}",
+            fc.function.package,
+            fc.function.name,
+            fc.cyclomatic_complexity,
+            fc.level,
+            fc.nesting_depth_max,
+            fc.lines.physical,
+            fc.longest_path_blocks,
+            suppressed_note(fc)
+        );
+    }
+    print_complexity_timings(&report);
+    print_complexity_histogram(&report);
+    match usize_param_opt(params, "max_function_lines") {
+        Ok(max_lines) => print_function_length_report(&report, max_lines),
+        Err(err) => eprintln!("error: {err}"),
+    }
+
+    if options.exported_only {
+        println!("\n{} exported functions/methods analyzed (exported_only)", report.functions.len());
+    }
+
+    let counts = func_counts::count_funcs(parsed);
+    println!("\n{} functions ({} exported, {} methods)", counts.total, counts.exported, counts.methods);
+
+    let loop_findings = lints::empty_or_trivial_loop_conditions(parsed);
+    for finding in &loop_findings {
+        println!(
+            "{}:{}: loop never exits (no condition and no reachable break)",
+            finding.function, finding.line
+        );
+    }
+
+    let marker_findings = lints::find_comment_markers(parsed, lints::DEFAULT_COMMENT_MARKERS);
+    if !marker_findings.is_empty() {
+        println!();
+        for (marker, count) in lints::count_by_marker(&marker_findings) {
+            println!("{marker}: {count}");
+        }
+    }
+
+    report.functions.into_iter().map(|fc| (fc.function.clone(), fc)).collect()
+}
+
+/// Watches `project_path` for `.go` file changes, re-running the analysis
+/// in [`print_analysis_summary`] on each debounced burst. Reuses
+/// [`go_parser::reparse_files`] to re-parse only the files that changed
+/// instead of the whole project, falling back to a full [`go_parser::parse_dir`]
+/// if that fails (e.g. a file was deleted in a way `reparse_files` can't
+/// patch in).
+fn run_watch(project_path: PathBuf, raw_params: Vec<String>, config_path: Option<PathBuf>, profile: bool) {
+    let mut params = match config::resolve_params(config_path.as_deref(), Some(&project_path), &raw_params) {
+        Ok(params) => params,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    params.set("profile", profile);
+    for warning in unknown_parameter_warnings_against(COMPLEXITY_USER_PARAM_NAMES, &params) {
+        eprintln!("warning: {warning}");
+    }
+
+    let mut parsed = Some(match go_parser::parse_dir(&project_path) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    });
+    let emit_delta = params.get::<String>("delta").is_some_and(|v| v == "true");
+    println!("watching `{}` for changes...\n", project_path.display());
+    let mut previous_snapshot = print_analysis_summary(parsed.as_ref().unwrap(), &params);
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        // Only actual content changes, not `Access` events — otherwise
+        // this process reading a file back during its own re-parse would
+        // look like a fresh edit and trigger another re-parse forever.
+        let Ok(event) = res else { return };
+        if !matches!(
+            event.kind,
+            notify::EventKind::Modify(_) | notify::EventKind::Create(_) | notify::EventKind::Remove(_)
+        ) {
+            return;
+        }
+        for path in event.paths {
+            let _ = tx.send(path);
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            eprintln!("error: couldn't start filesystem watcher: {err}");
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = notify::Watcher::watch(&mut watcher, &project_path, notify::RecursiveMode::Recursive) {
+        eprintln!("error: couldn't watch `{}`: {err}", project_path.display());
+        std::process::exit(1);
+    }
+
+    watch::run_debounced(
+        Duration::from_millis(300),
+        |timeout| match timeout {
+            Some(d) => match rx.recv_timeout(d) {
+                Ok(path) => RecvOutcome::Event(path),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => RecvOutcome::TimedOut,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => RecvOutcome::Closed,
+            },
+            None => match rx.recv() {
+                Ok(path) => RecvOutcome::Event(path),
+                Err(_) => RecvOutcome::Closed,
+            },
+        },
+        |paths| {
+            let mut changed: Vec<PathBuf> = paths
+                .iter()
+                .filter(|p| p.extension().is_some_and(|ext| ext == "go"))
+                .cloned()
+                .collect();
+            changed.sort();
+            changed.dedup();
+            if changed.is_empty() {
+                return;
+            }
+
+            println!("\n{} changed, re-analyzing...\n", changed.len());
+            let previous = parsed.take().expect("parsed is Some between every callback run");
+            parsed = Some(match go_parser::reparse_files(&project_path, previous, &changed) {
+                Ok(reparsed) => reparsed,
+                Err(err) => {
+                    eprintln!("warning: incremental re-parse failed ({err}); re-parsing from scratch");
+                    go_parser::parse_dir(&project_path).unwrap_or_else(|err| {
+                        eprintln!("error: {err}");
+                        std::process::exit(1);
+                    })
+                }
+            });
+            let snapshot = print_analysis_summary(parsed.as_ref().unwrap(), &params);
+            if emit_delta {
+                let delta = result_delta::diff(&previous_snapshot, &snapshot);
+                println!();
+                match serde_json::to_string_pretty(&delta) {
+                    Ok(json) => println!("{json}"),
+                    Err(err) => eprintln!("warning: couldn't serialize delta: {err}"),
+                }
+            }
+            previous_snapshot = snapshot;
+        },
+    );
+}
+
+/// Opens `repo_path` and prints its total commit count, per-author commit
+/// counts and percentage share, and each author's first/last commit —
+/// all gathered in the single history walk [`git_metrics::run_metrics`]
+/// streams through every registered [`git_metrics::Metric`] (it never
+/// collects the whole history into memory, unlike [`git_metrics::all_commits`]).
+/// With `json`, each metric's result is printed as pretty JSON instead
+/// of the normal human-readable per-line output. `repo_path` is opened
+/// via [`git_metrics::read_repo_from`], so it may be a local path or a
+/// Git URL; `shallow_depth` is only meaningful for the latter. With
+/// `trend`, prints [`git_metrics::complexity_trend`]'s time series
+/// instead of the usual commit-count metrics, limited to `max_commits`
+/// most-recent commits when given.
+fn run_git_metrics(
+    repo_path: String,
+    json: bool,
+    shallow_depth: Option<i32>,
+    trend: bool,
+    max_commits: Option<usize>,
+) {
+    let clone_options = git_metrics::CloneOptions { shallow_depth };
+    let repo = match git_metrics::read_repo_from(&repo_path, &clone_options) {
+        Ok(repo) => repo,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    if trend {
+        let walk_options = git_metrics::CommitWalkOptions { max_commits };
+        let series = match git_metrics::complexity_trend(
+            &repo,
+            &walk_options,
+            &go_parser::ParseOptions::default(),
+            &ComplexityOptions::default(),
+        ) {
+            Ok(series) => series,
+            Err(err) => {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        };
+
+        if json {
+            match serde_json::to_string_pretty(&series) {
+                Ok(output) => println!("{output}"),
+                Err(err) => {
+                    eprintln!("error: couldn't serialize complexity trend: {err}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+
+        for point in &series {
+            println!(
+                "{}: average {:.1}, max {}",
+                point.commit.hash, point.average_complexity, point.max_complexity
+            );
+        }
+        return;
+    }
+
+    let mut metrics: Vec<Box<dyn git_metrics::Metric>> = vec![
+        Box::new(git_metrics::TotalCommits::default()),
+        Box::new(git_metrics::CommitsByAuthor::default()),
+        Box::new(git_metrics::FirstLastCommit::default()),
+    ];
+    if let Err(err) =
+        git_metrics::run_metrics(&repo, &git_metrics::CommitWalkOptions::default(), &mut metrics)
+    {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+
+    if json {
+        for metric in &metrics {
+            println!("{}: {}", metric.name(), metric.result());
+        }
+        return;
+    }
+
+    let total = metrics[0].result().as_count().expect("TotalCommits always returns a Count");
+    println!("{total} commits");
+
+    let by_author_result = metrics[1].result();
+    let by_author =
+        by_author_result.as_count_map().expect("CommitsByAuthor always returns a CountMap");
+    for (author, count) in by_author {
+        println!("{author}: {count}");
+    }
+
+    let percentages = git_metrics::total_commit_percentage(by_author);
+    for (author, percentage) in &percentages {
+        println!("{author}: {percentage:.1}%");
+    }
+
+    let first_last_result = metrics[2].result();
+    let first_last_by_author = first_last_result
+        .as_first_last_by_author()
+        .expect("FirstLastCommit always returns a FirstLastByAuthor");
+    for (author, (first, last)) in first_last_by_author {
+        println!("{author}: first {} at {}, last {} at {}", first.hash, first.timestamp, last.hash, last.timestamp);
+    }
+}
+
+/// Parses `project_path`, opens it as a git repository, and prints
+/// complexity for only the functions [`changed_functions::changed_functions`]
+/// flags as touched by a diff of `old_ref` against `new_ref` — restricting
+/// the usual complexity report to what a reviewer actually needs to look
+/// at, rather than the whole project.
+fn run_changed_functions(project_path: PathBuf, old_ref: String, new_ref: String) {
+    let parsed = match go_parser::parse_dir(&project_path) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    let repo = match git_metrics::read_repo(&project_path) {
+        Ok(repo) => repo,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    let statuses = match changed_functions::changed_functions(&parsed, &repo, &old_ref, &new_ref) {
+        Ok(statuses) => statuses,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    let touched: std::collections::BTreeSet<FunctionId> =
+        statuses.into_iter().filter(|s| s.touched).map(|s| s.function).collect();
+
+    let report = complexity::analyze_function_complexity(&parsed, &ComplexityOptions::default());
+    for fc in report.functions.iter().filter(|fc| touched.contains(&fc.function)) {
+        println!(
+            "{}.{}: complexity {} ({:?}), nesting depth {}, {} lines, longest path {} blocks{}",
+            fc.function.package,
+            fc.function.name,
+            fc.cyclomatic_complexity,
+            fc.level,
+            fc.nesting_depth_max,
+            fc.lines.physical,
+            fc.longest_path_blocks,
+            suppressed_note(fc)
+        );
+    }
+}
+
+/// A representative [`ComplexityReport`], used only as the schema example
+/// registered via [`PluginFunction::with_result_example`] in
+/// [`build_pipeline`] — never run through the pipeline itself.
+fn complexity_report_example() -> ComplexityReport {
+    ComplexityReport {
+        functions: vec![FunctionComplexity {
+            function: FunctionId {
+                package: "main".to_string(),
+                file: "main.go".to_string(),
+                name: "Example".to_string(),
+            },
+            cyclomatic_complexity: 1,
+            level: ComplexityLevel::Low,
+            lines: LineCounts {
+                physical: 1,
+                logical: 1,
+                comment: 0,
+                blank: 0,
+            },
+            nesting_depth_max: 1,
+            line: 1,
+            statement_count: 1,
+            parameter_count: 0,
+            result_count: 0,
+            longest_path_blocks: 1,
+            suppressed: false,
+        }],
+        violations: Vec::new(),
+        level_violations: Vec::new(),
+        too_many_params: Vec::new(),
+        warnings: Vec::new(),
+        timings: None,
+    }
+}
+
+/// The full default pipeline plus every plugin function id `main()` needs
+/// to pull a specific result back out of `output.results` after running
+/// it — one field per analysis. A named struct instead of a positional
+/// tuple: the list has grown by one every few requests since it was
+/// introduced, and transposing two same-typed `QualPfId`s in a 20-long
+/// tuple is a silent mismatch a call site three lines away would never
+/// catch, where a misspelled field name is a compile error right here.
+struct DefaultPipelineIds {
+    pipeline: Pipeline,
+    parse_id: QualPfId,
+    complexity_id: QualPfId,
+    func_counts_id: QualPfId,
+    implementors_id: QualPfId,
+    loop_conditions_id: QualPfId,
+    comment_markers_id: QualPfId,
+    shadowed_variables_id: QualPfId,
+    ignored_errors_id: QualPfId,
+    empty_branches_id: QualPfId,
+    excessive_returns_id: QualPfId,
+    constant_conditions_id: QualPfId,
+    clones_id: QualPfId,
+    coupling_id: QualPfId,
+    diagnostics_id: QualPfId,
+    features_id: QualPfId,
+    ast_export_id: QualPfId,
+    imports_id: QualPfId,
+    import_cycles_id: QualPfId,
+    branch_coverage_id: QualPfId,
+    statement_coverage_id: QualPfId,
+}
+
+fn build_pipeline() -> DefaultPipelineIds {
+    let parse_id = go_parser::iface::parse_project_id();
+    let complexity_id = QualPfId::new("complexity", "analyze");
+    let func_counts_id = QualPfId::new("func_counts", "count");
+    let implementors_id = QualPfId::new("interfaces", "implementors_of");
+    let loop_conditions_id = QualPfId::new("lints", "empty_or_trivial_loop_conditions");
+    let comment_markers_id = QualPfId::new("lints", "find_comment_markers");
+    let shadowed_variables_id = QualPfId::new("lints", "find_shadowed_declarations");
+    let ignored_errors_id = QualPfId::new("lints", "find_ignored_errors");
+    let empty_branches_id = QualPfId::new("lints", "find_empty_branches");
+    let excessive_returns_id = QualPfId::new("lints", "find_excessive_returns");
+    let constant_conditions_id = QualPfId::new("lints", "find_constant_conditions");
+    let clones_id = QualPfId::new("clones", "analyze");
+    let coupling_id = QualPfId::new("call_graph", "analyze_coupling");
+    let diagnostics_id = QualPfId::new("diagnostics", "collect");
+    let features_id = QualPfId::new("features", "analyze_feature_usage");
+    let ast_export_id = QualPfId::new("ast_export", "export_ast");
+    let imports_id = QualPfId::new("imports", "analyze");
+    let import_cycles_id = QualPfId::new("imports", "find_cycles");
+    let branch_coverage_id = coverage::iface::branch_coverage_id();
+    let statement_coverage_id = coverage::iface::statement_coverage_id();
+
+    let parse = go_parser::iface::parse_project_pf();
+    let branch_coverage_pf = coverage::iface::branch_coverage_pf();
+    let statement_coverage_pf = coverage::iface::statement_coverage_pf();
+
+    let complexity_dep = parse_id.clone();
+    let complexity = PluginFunction::new(
+        complexity_id.clone(),
+        vec![parse_id.clone()],
+        move |results, params| {
+            let parsed = results.try_get::<ParseDirResult>(&complexity_dep).map_err(|e| e.to_string())?;
+            let options = complexity_options_from_params(params)?;
+            Ok(complexity::analyze_function_complexity(parsed, &options))
+        },
+    )
+    .with_user_params(COMPLEXITY_USER_PARAM_NAMES.iter().map(|name| UserParamSpec::optional(*name)).collect())
+    .with_result_example(&complexity_report_example());
+
+    let func_counts_dep = parse_id.clone();
+    let func_counts_pf = PluginFunction::new(
+        func_counts_id.clone(),
+        vec![parse_id.clone()],
+        move |results, _params| {
+            let parsed = results.try_get::<ParseDirResult>(&func_counts_dep).map_err(|e| e.to_string())?;
+            Ok(func_counts::count_funcs(parsed))
+        },
+    );
+
+    let implementors_dep = parse_id.clone();
+    let implementors_pf = PluginFunction::new(
+        implementors_id.clone(),
+        vec![parse_id.clone()],
+        move |results, params| {
+            let Some(iface_name) = params.get::<String>("interface_name") else {
+                // No interface was asked about; report no implementors
+                // rather than failing a run that never requested this.
+                return Ok(Vec::<String>::new());
+            };
+            let parsed = results.try_get::<ParseDirResult>(&implementors_dep).map_err(|e| e.to_string())?;
+            interfaces::implementors_of(parsed, iface_name)
+                .ok_or_else(|| format!("no interface named `{iface_name}` was found"))
+        },
+    )
+    .with_user_params(vec![UserParamSpec::optional("interface_name")]);
+
+    let loop_conditions_dep = parse_id.clone();
+    let loop_conditions_pf = PluginFunction::new(
+        loop_conditions_id.clone(),
+        vec![parse_id.clone()],
+        move |results, _params| {
+            let parsed =
+                results.try_get::<ParseDirResult>(&loop_conditions_dep).map_err(|e| e.to_string())?;
+            Ok(lints::empty_or_trivial_loop_conditions(parsed))
+        },
+    );
+
+    let comment_markers_dep = parse_id.clone();
+    let comment_markers_pf = PluginFunction::new(
+        comment_markers_id.clone(),
+        vec![parse_id.clone()],
+        move |results, _params| {
+            let parsed =
+                results.try_get::<ParseDirResult>(&comment_markers_dep).map_err(|e| e.to_string())?;
+            Ok(lints::find_comment_markers(parsed, lints::DEFAULT_COMMENT_MARKERS))
+        },
+    );
+
+    let shadowed_variables_dep = parse_id.clone();
+    let shadowed_variables_pf = PluginFunction::new(
+        shadowed_variables_id.clone(),
+        vec![parse_id.clone()],
+        move |results, _params| {
+            let parsed =
+                results.try_get::<ParseDirResult>(&shadowed_variables_dep).map_err(|e| e.to_string())?;
+            Ok(lints::find_shadowed_declarations(parsed))
+        },
+    );
+
+    let ignored_errors_dep = parse_id.clone();
+    let ignored_errors_pf = PluginFunction::new(
+        ignored_errors_id.clone(),
+        vec![parse_id.clone()],
+        move |results, params| {
+            let parsed =
+                results.try_get::<ParseDirResult>(&ignored_errors_dep).map_err(|e| e.to_string())?;
+            let strict = params.get::<String>("strict").is_some_and(|v| v == "true");
+            Ok(lints::find_ignored_errors(parsed, &lints::IgnoredErrorOptions { strict }))
+        },
+    )
+    .with_user_params(vec![UserParamSpec::optional("strict")]);
+
+    let empty_branches_dep = parse_id.clone();
+    let empty_branches_pf = PluginFunction::new(
+        empty_branches_id.clone(),
+        vec![parse_id.clone()],
+        move |results, params| {
+            let parsed =
+                results.try_get::<ParseDirResult>(&empty_branches_dep).map_err(|e| e.to_string())?;
+            let ignore_empty_default_case =
+                params.get::<String>("ignore_empty_default_case").is_some_and(|v| v == "true");
+            Ok(lints::find_empty_branches(parsed, &EmptyBranchOptions { ignore_empty_default_case }))
+        },
+    )
+    .with_user_params(vec![UserParamSpec::optional("ignore_empty_default_case")]);
+
+    let excessive_returns_dep = parse_id.clone();
+    let excessive_returns_pf = PluginFunction::new(
+        excessive_returns_id.clone(),
+        vec![parse_id.clone()],
+        move |results, params| {
+            let parsed =
+                results.try_get::<ParseDirResult>(&excessive_returns_dep).map_err(|e| e.to_string())?;
+            let threshold = match params.get::<String>("max_returns") {
+                Some(value) => {
+                    value.parse::<usize>().map_err(|_| format!("invalid max_returns `{value}`"))?
+                }
+                None => lints::DEFAULT_MAX_RETURNS,
+            };
+            Ok(lints::find_excessive_returns(parsed, threshold))
+        },
+    )
+    .with_user_params(vec![UserParamSpec::optional("max_returns")]);
+
+    let constant_conditions_dep = parse_id.clone();
+    let constant_conditions_pf = PluginFunction::new(
+        constant_conditions_id.clone(),
+        vec![parse_id.clone()],
+        move |results, _params| {
+            let parsed =
+                results.try_get::<ParseDirResult>(&constant_conditions_dep).map_err(|e| e.to_string())?;
+            Ok(lints::find_constant_conditions(parsed))
+        },
+    );
+
+    let clones_dep = parse_id.clone();
+    let clones_pf = PluginFunction::new(clones_id.clone(), vec![parse_id.clone()], move |results, _params| {
+        let parsed = results.try_get::<ParseDirResult>(&clones_dep).map_err(|e| e.to_string())?;
+        Ok(clones::analyze_clones(parsed))
+    });
+
+    let coupling_dep = parse_id.clone();
+    let coupling_pf = PluginFunction::new(
+        coupling_id.clone(),
+        vec![parse_id.clone()],
+        move |results, params| {
+            let parsed = results.try_get::<ParseDirResult>(&coupling_dep).map_err(|e| e.to_string())?;
+            let parse_threshold = |name: &str| -> Result<Option<usize>, String> {
+                match params.get::<String>(name) {
+                    Some(value) => {
+                        Ok(Some(value.parse::<usize>().map_err(|_| format!("invalid {name} `{value}`"))?))
+                    }
+                    None => Ok(None),
+                }
+            };
+            let options = CouplingOptions {
+                max_fan_out: parse_threshold("max_fan_out")?,
+                max_fan_in: parse_threshold("max_fan_in")?,
+            };
+            Ok(call_graph::analyze_coupling(parsed, &options))
+        },
+    )
+    .with_user_params(vec![UserParamSpec::optional("max_fan_out"), UserParamSpec::optional("max_fan_in")]);
+
+    let diagnostics_dep_parse = parse_id.clone();
+    let diagnostics_dep_complexity = complexity_id.clone();
+    let diagnostics_dep_loops = loop_conditions_id.clone();
+    let diagnostics_pf = PluginFunction::new(
+        diagnostics_id.clone(),
+        vec![parse_id.clone(), complexity_id.clone(), loop_conditions_id.clone()],
+        move |results, _params| {
+            let parsed = results.try_get::<ParseDirResult>(&diagnostics_dep_parse).map_err(|e| e.to_string())?;
+            let complexity_report = results
+                .try_get::<ComplexityReport>(&diagnostics_dep_complexity)
+                .map_err(|e| e.to_string())?;
+            let loop_findings = results
+                .try_get::<Vec<LoopConditionFinding>>(&diagnostics_dep_loops)
+                .map_err(|e| e.to_string())?;
+            Ok(diagnostics::collect_diagnostics(parsed, complexity_report, loop_findings))
+        },
+    );
+
+    let features_dep = parse_id.clone();
+    let features_pf = PluginFunction::new(
+        features_id.clone(),
+        vec![parse_id.clone()],
+        move |results, _params| {
+            let parsed = results.try_get::<ParseDirResult>(&features_dep).map_err(|e| e.to_string())?;
+            Ok(features::analyze_feature_usage(parsed))
+        },
+    );
+
+    let ast_export_dep = parse_id.clone();
+    let ast_export_pf = PluginFunction::new(
+        ast_export_id.clone(),
+        vec![parse_id.clone()],
+        move |results, _params| {
+            let parsed = results.try_get::<ParseDirResult>(&ast_export_dep).map_err(|e| e.to_string())?;
+            Ok(ast_export::export_ast(parsed))
+        },
+    );
+
+    let imports_dep = parse_id.clone();
+    let imports_pf = PluginFunction::new(
+        imports_id.clone(),
+        vec![parse_id.clone()],
+        move |results, params| {
+            let parsed = results.try_get::<ParseDirResult>(&imports_dep).map_err(|e| e.to_string())?;
+            let options = imports::ImportAnalysisOptions {
+                include_stdlib: params.get::<String>("include_stdlib").is_some_and(|v| v == "true"),
+                include_vendored: params.get::<String>("include_vendored").is_some_and(|v| v == "true"),
+            };
+            Ok(imports::analyze(parsed, &options))
+        },
+    )
+    .with_user_params(vec![
+        UserParamSpec::optional("include_stdlib"),
+        UserParamSpec::optional("include_vendored"),
+    ]);
+
+    let import_cycles_dep = imports_id.clone();
+    let import_cycles_pf = PluginFunction::new(
+        import_cycles_id.clone(),
+        vec![imports_id.clone()],
+        move |results, _params| {
+            let graph = results.try_get::<ImportGraph>(&import_cycles_dep).map_err(|e| e.to_string())?;
+            Ok(imports::find_cycles(graph))
+        },
+    );
+
+    DefaultPipelineIds {
+        pipeline: Pipeline::new(vec![
+            parse,
+            complexity,
+            func_counts_pf,
+            implementors_pf,
+            loop_conditions_pf,
+            comment_markers_pf,
+            shadowed_variables_pf,
+            ignored_errors_pf,
+            empty_branches_pf,
+            excessive_returns_pf,
+            constant_conditions_pf,
+            clones_pf,
+            coupling_pf,
+            diagnostics_pf,
+            features_pf,
+            ast_export_pf,
+            imports_pf,
+            import_cycles_pf,
+            branch_coverage_pf,
+            statement_coverage_pf,
+        ]),
+        parse_id,
+        complexity_id,
+        func_counts_id,
+        implementors_id,
+        loop_conditions_id,
+        comment_markers_id,
+        shadowed_variables_id,
+        ignored_errors_id,
+        empty_branches_id,
+        excessive_returns_id,
+        constant_conditions_id,
+        clones_id,
+        coupling_id,
+        diagnostics_id,
+        features_id,
+        ast_export_id,
+        imports_id,
+        import_cycles_id,
+        branch_coverage_id,
+        statement_coverage_id,
+    }
+}
+
+/// Reads a single Go source file from stdin and runs complexity analysis
+/// on it, printing the same report `main`'s default path would for a
+/// project directory (or the same diagnostics JSON, with `--diagnostics`)
+/// — the `--stdin` flag's whole job. Never touches the filesystem: the
+/// source is parsed in memory via [`go_parser::parse_source`], so there's
+/// no project to also run `func_counts`/`implementors_of`/the comment-
+/// marker scan against.
+fn run_stdin(diagnostics: bool, raw_params: &[String], config_path: Option<&Path>, profile: bool) {
+    let mut source = String::new();
+    if let Err(err) = std::io::stdin().read_to_string(&mut source) {
+        eprintln!("error: couldn't read stdin: {err}");
+        std::process::exit(1);
+    }
+
+    let parsed = match go_parser::parse_source("stdin.go", &source) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut params = match config::resolve_params(config_path, None, raw_params) {
+        Ok(params) => params,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    params.set("profile", profile);
+    for warning in unknown_parameter_warnings_against(COMPLEXITY_USER_PARAM_NAMES, &params) {
+        eprintln!("warning: {warning}");
+    }
+
+    let options = match complexity_options_from_params(&params) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    let report = complexity::analyze_function_complexity(&parsed, &options);
+
+    if diagnostics {
+        let loop_findings = lints::empty_or_trivial_loop_conditions(&parsed);
+        let found = diagnostics::collect_diagnostics(&parsed, &report, &loop_findings);
+        match serde_json::to_string_pretty(&found) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("error: couldn't serialize diagnostics: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    for fc in &report.functions {
+        println!(
+            "{}.{}: complexity {} ({:?}), nesting depth {}, {} lines, longest path {} blocks{}",
+            fc.function.package,
+            fc.function.name,
+            fc.cyclomatic_complexity,
+            fc.level,
+            fc.nesting_depth_max,
+            fc.lines.physical,
+            fc.longest_path_blocks,
+            suppressed_note(fc)
+        );
+    }
+    print_complexity_timings(&report);
+    print_complexity_histogram(&report);
+
+    for finding in lints::empty_or_trivial_loop_conditions(&parsed) {
+        println!(
+            "{}:{}: loop never exits (no condition and no reachable break)",
+            finding.function, finding.line
+        );
+    }
+
+    if report.fails_policy() {
+        eprintln!("\ncomplexity policy violations:");
+        for function in report.violations.iter().chain(&report.level_violations).chain(&report.too_many_params) {
+            eprintln!("  {function}");
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Streams one NDJSON (newline-delimited JSON) line per function as it's
+/// computed, via [`complexity::analyze_function_complexity_streaming`],
+/// instead of running the usual pipeline and buffering every function's
+/// result into one JSON document — the `--ndjson` flag's whole job.
+fn run_ndjson(path: &Path, raw_params: &[String], config_path: Option<&Path>) {
+    let params = match config::resolve_params(config_path, Some(path), raw_params) {
+        Ok(params) => params,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    for warning in unknown_parameter_warnings_against(COMPLEXITY_USER_PARAM_NAMES, &params) {
+        eprintln!("warning: {warning}");
+    }
+
+    let options = match complexity_options_from_params(&params) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let result = complexity::analyze_function_complexity_streaming(path, &options, |fc| {
+        match serde_json::to_string(fc) {
+            Ok(json) => println!("{json}"),
+            Err(err) => eprintln!("error: couldn't serialize {}: {err}", fc.function),
+        }
+    });
+
+    if let Err(err) = result {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
 fn main() {
-    let greeting = "Hello, World!".to_string();
-    println!("{greeting}");
+    let cli = Cli::parse();
+
+    if cli.stdin {
+        run_stdin(cli.diagnostics, &cli.params, cli.config.as_deref(), cli.profile);
+        return;
+    }
+
+    if cli.manifest {
+        let DefaultPipelineIds { pipeline, .. } = build_pipeline();
+        match serde_json::to_string_pretty(&pipeline.manifest()) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("error: couldn't serialize manifest: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if cli.ndjson {
+        let path = cli.path.unwrap_or_else(|| {
+            eprintln!("error: a path is required for --ndjson");
+            std::process::exit(1);
+        });
+        run_ndjson(&path, &cli.params, cli.config.as_deref());
+        return;
+    }
+
+    if let Some(Commands::Watch { project_path, params, config }) = cli.command {
+        run_watch(project_path, params, config, cli.profile);
+        return;
+    }
+
+    if let Some(Commands::GitMetrics { repo_path, json, shallow_depth, trend, max_commits }) = cli.command {
+        run_git_metrics(repo_path, json, shallow_depth, trend, max_commits);
+        return;
+    }
+
+    if let Some(Commands::ChangedFunctions { project_path, old_ref, new_ref }) = cli.command {
+        run_changed_functions(project_path, old_ref, new_ref);
+        return;
+    }
+
+    if let Some(Commands::All { project_paths, output_dir }) = cli.command {
+        match all::run_all_analyses(&project_paths, &output_dir) {
+            Ok(index) => {
+                for report in &index.reports {
+                    println!("{}: {}", report.analysis, output_dir.join(&report.path).display());
+                }
+                println!("index: {}", output_dir.join("index.json").display());
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let path = cli.path.unwrap_or_else(|| {
+        eprintln!("error: a path is required unless a subcommand (e.g. `watch`) is given");
+        std::process::exit(1);
+    });
+
+    if cli.reuse_parse {
+        note_whether_unchanged_since_last_run(&path);
+    }
+
+    let DefaultPipelineIds {
+        pipeline,
+        parse_id,
+        complexity_id,
+        func_counts_id,
+        implementors_id,
+        loop_conditions_id,
+        comment_markers_id,
+        shadowed_variables_id,
+        ignored_errors_id,
+        empty_branches_id,
+        excessive_returns_id,
+        constant_conditions_id,
+        clones_id,
+        coupling_id,
+        diagnostics_id,
+        features_id,
+        ast_export_id,
+        imports_id,
+        import_cycles_id,
+        branch_coverage_id,
+        statement_coverage_id,
+    } = build_pipeline();
+    let mut params = match config::resolve_params(cli.config.as_deref(), Some(&path), &cli.params) {
+        Ok(params) => params,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    params.set("path", path);
+    params.set("profile", cli.profile);
+    if let Some(coverage_profile) = cli.coverage_profile {
+        params.set("coverage_profile_path", coverage_profile);
+    }
+
+    for warning in pipeline.unknown_parameter_warnings(&params) {
+        eprintln!("warning: {warning}");
+    }
+
+    if cli.plan {
+        print_execution_plan(&pipeline, &params);
+        return;
+    }
+
+    let run_started = std::time::Instant::now();
+    let output = match pipeline.run_pipeline(&params, cli.profile) {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    let wall_time = run_started.elapsed();
+
+    if cli.diagnostics {
+        let diagnostics = output
+            .results
+            .get::<Vec<Diagnostic>>(&diagnostics_id)
+            .expect("diagnostics.collect always runs");
+        match serde_json::to_string_pretty(diagnostics) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("error: couldn't serialize diagnostics: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if cli.ast_json {
+        let files = output
+            .results
+            .get::<Vec<(String, String, AstNode)>>(&ast_export_id)
+            .expect("ast_export.export_ast always runs");
+        match serde_json::to_string_pretty(files) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("error: couldn't serialize AST: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let report = output
+        .results
+        .get::<complexity::ComplexityReport>(&complexity_id)
+        .expect("complexity.analyze always runs");
+
+    if cli.gocyclo {
+        print!("{}", complexity::render_gocyclo(report));
+        return;
+    }
+
+    if cli.complexity_by_file {
+        let parsed = output.results.get::<ParseDirResult>(&parse_id).expect("project.parse always runs");
+        let by_file = complexity::complexity_by_file(parsed, report);
+        match serde_json::to_string_pretty(&by_file) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("error: couldn't serialize complexity-by-file: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    for fc in &report.functions {
+        println!(
+            "{}.{}: complexity {} ({:?}), nesting depth {}, {} lines, longest path {} blocks{}",
+            fc.function.package,
+            fc.function.name,
+            fc.cyclomatic_complexity,
+            fc.level,
+            fc.nesting_depth_max,
+            fc.lines.physical,
+            fc.longest_path_blocks,
+            suppressed_note(fc)
+        );
+    }
+    print_complexity_timings(report);
+    print_complexity_histogram(report);
+
+    if let Some(html_path) = &cli.complexity_html {
+        let theme = match cli.complexity_html_theme.as_deref() {
+            Some("dark") => complexity::HtmlTheme::Dark,
+            Some("light") | None => complexity::HtmlTheme::Light,
+            Some(other) => {
+                eprintln!("error: invalid complexity-html-theme `{other}` (expected `light` or `dark`)");
+                std::process::exit(1);
+            }
+        };
+        let options = complexity::HtmlThemeOptions { theme, ..Default::default() };
+        let html = complexity::render_html_histogram(report, &options);
+        if let Err(err) = std::fs::write(html_path, html) {
+            eprintln!("error: couldn't write complexity HTML report to `{}`: {err}", html_path.display());
+            std::process::exit(1);
+        }
+        println!("\ncomplexity HTML report: {}", html_path.display());
+    }
+
+    let counts = output
+        .results
+        .get::<FuncCounts>(&func_counts_id)
+        .expect("func_counts.count always runs");
+    println!(
+        "\n{} functions ({} exported, {} methods)",
+        counts.total, counts.exported, counts.methods
+    );
+
+    let implementors = output
+        .results
+        .get::<Vec<String>>(&implementors_id)
+        .expect("interfaces.implementors_of always runs");
+    if let Some(iface_name) = params.get::<String>("interface_name") {
+        println!("\nimplementors of {iface_name}: {implementors:?}");
+    }
+
+    let loop_findings = output
+        .results
+        .get::<Vec<LoopConditionFinding>>(&loop_conditions_id)
+        .expect("lints.empty_or_trivial_loop_conditions always runs");
+    for finding in loop_findings {
+        println!(
+            "{}:{}: loop never exits (no condition and no reachable break)",
+            finding.function, finding.line
+        );
+    }
+
+    let marker_findings = output
+        .results
+        .get::<Vec<CommentMarkerFinding>>(&comment_markers_id)
+        .expect("lints.find_comment_markers always runs");
+    if !marker_findings.is_empty() {
+        println!();
+        for (marker, count) in lints::count_by_marker(marker_findings) {
+            println!("{marker}: {count}");
+        }
+    }
+
+    let shadowed_variables = output
+        .results
+        .get::<Vec<ShadowedVariableFinding>>(&shadowed_variables_id)
+        .expect("lints.find_shadowed_declarations always runs");
+    for finding in shadowed_variables {
+        println!(
+            "{}:{}: `{}` shadows the declaration at line {}",
+            finding.function, finding.shadowing_line, finding.name, finding.shadowed_line
+        );
+    }
+
+    let ignored_errors = output
+        .results
+        .get::<Vec<IgnoredErrorFinding>>(&ignored_errors_id)
+        .expect("lints.find_ignored_errors always runs");
+    for finding in ignored_errors {
+        println!("{}:{}: error from `{}` is ignored", finding.function, finding.line, finding.called);
+    }
+
+    let empty_branches = output
+        .results
+        .get::<Vec<EmptyBranchFinding>>(&empty_branches_id)
+        .expect("lints.find_empty_branches always runs");
+    for finding in empty_branches {
+        println!("{}:{}: empty {} body", finding.function, finding.line, finding.kind);
+    }
+
+    let excessive_returns = output
+        .results
+        .get::<Vec<ExcessiveReturnsFinding>>(&excessive_returns_id)
+        .expect("lints.find_excessive_returns always runs");
+    for finding in excessive_returns {
+        println!(
+            "{}:{}: {} return statements ({})",
+            finding.function,
+            finding.line,
+            finding.return_count,
+            finding
+                .return_lines
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let constant_conditions = output
+        .results
+        .get::<Vec<ConstantConditionFinding>>(&constant_conditions_id)
+        .expect("lints.find_constant_conditions always runs");
+    for finding in constant_conditions {
+        println!(
+            "{}:{}: condition always {} ({} branch never runs)",
+            finding.function,
+            finding.line,
+            finding.value,
+            if finding.value { "else" } else { "then" }
+        );
+    }
+
+    let clone_report =
+        output.results.get::<CloneReport>(&clones_id).expect("clones.analyze always runs");
+    for cluster in &clone_report.clusters {
+        let functions =
+            cluster.functions.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        println!("clone cluster ({}): {functions}", cluster.signature);
+    }
+
+    let coupling_report =
+        output.results.get::<CouplingReport>(&coupling_id).expect("call_graph.analyze_coupling always runs");
+    for function in &coupling_report.high_fan_out {
+        println!("{function}: high fan-out");
+    }
+    for function in &coupling_report.high_fan_in {
+        println!("{function}: high fan-in");
+    }
+
+    let branch_coverage = output
+        .results
+        .get::<ProjectBranchCoverage>(&branch_coverage_id)
+        .expect("coverage.branch_coverage always runs");
+    for branch in &branch_coverage.branches {
+        println!(
+            "{}:{}: if branch — then {}, else {}",
+            branch.function,
+            branch.line,
+            if branch.true_taken { "taken" } else { "not taken" },
+            if branch.false_taken { "taken" } else { "not taken" },
+        );
+    }
+    for case in &branch_coverage.switch_cases {
+        let label = if case.is_default { "default".to_string() } else { format!("case at {}", case.case_line) };
+        println!(
+            "{}:{}: switch {label} — {}",
+            case.function,
+            case.switch_line,
+            if case.taken { "taken" } else { "not taken" },
+        );
+    }
+
+    let statement_coverage = output
+        .results
+        .get::<ProjectStatementCoverage>(&statement_coverage_id)
+        .expect("coverage.statement_coverage always runs");
+    let coverage_sort_by = match cli.coverage_sort.as_deref() {
+        None | Some("least-covered") => CoverageSortKey::LeastCovered,
+        Some("name") => CoverageSortKey::Name,
+        Some(other) => {
+            eprintln!("error: invalid --coverage-sort `{other}` (expected `least-covered` or `name`)");
+            std::process::exit(1);
+        }
+    };
+    let coverage_presentation =
+        CoveragePresentationOptions { sort_by: coverage_sort_by, top_n: cli.coverage_top };
+    for fc in coverage::sort_and_limit_statement_coverage(&statement_coverage.functions, &coverage_presentation) {
+        println!(
+            "{}: {} lines hit {:?}, {} lines missed {:?}",
+            fc.function,
+            fc.covered_lines.len(),
+            fc.covered_lines,
+            fc.uncovered_lines.len(),
+            fc.uncovered_lines,
+        );
+    }
+    for warning in branch_coverage.warnings.iter().chain(&statement_coverage.warnings) {
+        eprintln!("warning: {warning}");
+    }
+
+    if let Some(html_dir) = &cli.coverage_html {
+        let parsed =
+            output.results.get::<ParseDirResult>(&parse_id).expect("project.parse always runs");
+        if let Err(err) = coverage::write_html_coverage_report(parsed, statement_coverage, html_dir) {
+            eprintln!("error: couldn't write coverage HTML report to `{}`: {err}", html_dir.display());
+            std::process::exit(1);
+        }
+        println!("\ncoverage HTML report: {}", html_dir.join("index.html").display());
+    }
+
+    let feature_summary = output
+        .results
+        .get::<FeatureSummary>(&features_id)
+        .expect("features.analyze_feature_usage always runs");
+    if !feature_summary.counts.is_empty() {
+        println!();
+        for (feature, count) in &feature_summary.counts {
+            println!("{feature:?}: {count}");
+        }
+    }
+
+    let import_graph =
+        output.results.get::<ImportGraph>(&imports_id).expect("imports.analyze always runs");
+    println!("\n{} import edges between project packages", import_graph.edges.len());
+    let import_cycles = output
+        .results
+        .get::<Vec<Vec<String>>>(&import_cycles_id)
+        .expect("imports.find_cycles always runs");
+    for cycle in import_cycles {
+        println!("import cycle: {}", cycle.join(" -> "));
+    }
+    if let Some(dot_path) = &cli.imports_dot {
+        write_dot_output(
+            dot_path,
+            &imports::to_dot(import_graph),
+            cli.render_graphviz,
+            cli.render_format.as_deref(),
+        );
+    }
+
+    if let Some(dot_path) = &cli.cfg_dot {
+        let parsed = output.results.get::<ParseDirResult>(&parse_id).expect("project.parse always runs");
+        let cfgs: BTreeMap<String, cfg_plugin::ControlFlowGraph> = cfg_plugin::build_cfgs_for_project(parsed)
+            .into_iter()
+            .map(|(function, cfg)| (function.to_string(), cfg))
+            .collect();
+        const CFG_DOT_MAX_LABEL_LEN: usize = 60;
+        let (dot, full_text) = cfg_plugin::to_dot_combined(&cfgs, CFG_DOT_MAX_LABEL_LEN);
+        write_dot_output(dot_path, &dot, cli.render_graphviz, cli.render_format.as_deref());
+        if !full_text.is_empty() {
+            let sidecar_path = dot_path.with_extension("json");
+            match serde_json::to_string_pretty(&full_text) {
+                Ok(json) => {
+                    if let Err(err) = std::fs::write(&sidecar_path, json) {
+                        eprintln!(
+                            "error: couldn't write CFG DOT sidecar to `{}`: {err}",
+                            sidecar_path.display()
+                        );
+                        std::process::exit(1);
+                    }
+                }
+                Err(err) => {
+                    eprintln!("error: couldn't serialize CFG DOT sidecar: {err}");
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    if let Some(timings) = output.timings {
+        println!();
+        println!("timings:");
+        for (id, duration) in timings {
+            println!("  {id}: {duration:?}");
+        }
+    }
+
+    if cli.summary {
+        let parsed = output
+            .results
+            .get::<ParseDirResult>(&parse_id)
+            .expect("project.parse always runs");
+        let summary = run_summary::summarize(parsed, report, wall_time);
+        println!();
+        match serde_json::to_string_pretty(&summary) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("error: couldn't serialize run summary: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if report.fails_policy() {
+        eprintln!("\ncomplexity policy violations:");
+        for function in report.violations.iter().chain(&report.level_violations).chain(&report.too_many_params) {
+            eprintln!("  {function}");
+        }
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,69 @@
+//! Identifiers shared by every analysis plugin.
+//!
+//! Nearly every plugin function (CFG construction, complexity, coverage,
+//! ...) needs to name "the function it's talking about" in a way that's
+//! stable across a whole project. [`FunctionId`] is that shared key.
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// Identifies a single function or method declaration within a parsed
+/// project: which package it lives in, which file, and its name (the
+/// receiver type, for methods, is folded into `name` by the CFG plugin so
+/// that e.g. `(*Foo).Bar` and `Bar` don't collide).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct FunctionId {
+    pub package: String,
+    pub file: String,
+    pub name: String,
+}
+
+impl FunctionId {
+    pub fn new(package: impl Into<String>, file: impl Into<String>, name: impl Into<String>) -> Self {
+        FunctionId {
+            package: package.into(),
+            file: file.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl fmt::Display for FunctionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.package, self.name)
+    }
+}
+
+/// Whether `id` is the function named by a `function_filter` user
+/// parameter, shared by every per-function analysis (complexity,
+/// coverage, ...) that wants to let a caller narrow a run to a single
+/// hotspot instead of the whole project. Accepts either `package.Function`
+/// (matching [`FunctionId::package`]) or `file::Function` (matching
+/// [`FunctionId::file`]) — the latter for when two packages declare a
+/// function of the same name.
+pub fn matches_function_filter(id: &FunctionId, filter: &str) -> bool {
+    if let Some((file, name)) = filter.split_once("::") {
+        return id.file == file && id.name == name;
+    }
+    if let Some((package, name)) = filter.rsplit_once('.') {
+        return id.package == package && id.name == name;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_either_the_package_or_file_qualified_form() {
+        let id = FunctionId::new("mypkg", "mypkg/foo.go", "Foo");
+
+        assert!(matches_function_filter(&id, "mypkg.Foo"));
+        assert!(matches_function_filter(&id, "mypkg/foo.go::Foo"));
+        assert!(!matches_function_filter(&id, "mypkg.Bar"));
+        assert!(!matches_function_filter(&id, "otherpkg.Foo"));
+        assert!(!matches_function_filter(&id, "Foo"));
+    }
+}
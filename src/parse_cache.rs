@@ -0,0 +1,207 @@
+//! Skips re-parsing a project that hasn't changed.
+//!
+//! The obvious way to do this would be to serialize a [`ParseDirResult`]
+//! to a file and reload it on the next run. That doesn't work here:
+//! `goscript_parser`'s `AstObjects`/`FileSet` are `slotmap`-backed arenas
+//! with no `serde` support, so there's no way to write out an AST and read
+//! it back into valid `IdentKey`/`Pos` values without forking that crate.
+//!
+//! What *is* cheap to serialize is a fingerprint of the project's source
+//! files — enough to tell whether anything changed since a previous parse.
+//! [`ParseCache`] uses that fingerprint to skip calling [`parse_dir`]
+//! entirely when nothing changed, but only within the process that holds
+//! it: the fingerprint can cross a process boundary (see
+//! [`save_fingerprint`]/[`load_fingerprint`]), the parsed AST can't.
+
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::go_parser::{ParseDirError, ParseDirResult, collect_go_files, parse_dir};
+
+/// A single file's staleness check: modification time plus a hash of its
+/// contents, so neither a clock rollback nor a touch-without-editing is
+/// mistaken for "unchanged".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    pub modified_unix_nanos: u128,
+    pub content_hash: u64,
+}
+
+/// Every `.go` file under a project's root, by path relative to that root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectFingerprint(pub BTreeMap<String, FileFingerprint>);
+
+fn hash_contents(contents: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fingerprints every `.go` file under `root`.
+pub fn fingerprint_project(root: &Path) -> Result<ProjectFingerprint, ParseDirError> {
+    let mut paths = Vec::new();
+    collect_go_files(root, &mut paths)?;
+
+    let mut files = BTreeMap::new();
+    for path in paths {
+        let metadata =
+            std::fs::metadata(&path).map_err(|e| ParseDirError::Io(path.clone(), e))?;
+        let modified = metadata
+            .modified()
+            .map_err(|e| ParseDirError::Io(path.clone(), e))?;
+        let modified_unix_nanos = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let contents =
+            std::fs::read_to_string(&path).map_err(|e| ParseDirError::Io(path.clone(), e))?;
+
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        files.insert(
+            rel.to_string_lossy().into_owned(),
+            FileFingerprint {
+                modified_unix_nanos,
+                content_hash: hash_contents(&contents),
+            },
+        );
+    }
+
+    Ok(ProjectFingerprint(files))
+}
+
+/// Writes `fingerprint` to `path` as JSON, so a later process can compare
+/// against it with [`load_fingerprint`].
+pub fn save_fingerprint(path: &Path, fingerprint: &ProjectFingerprint) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string(fingerprint).expect("ProjectFingerprint only holds plain data");
+    std::fs::write(path, json)
+}
+
+/// Reads back a fingerprint written by [`save_fingerprint`]. Returns `None`
+/// for a missing or unreadable file — "no cache yet" is the expected
+/// first-run case, not a failure worth reporting as one.
+pub fn load_fingerprint(path: &Path) -> Option<ProjectFingerprint> {
+    let json = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Whether [`parse_dir_cached`] actually called [`parse_dir`], for tests
+/// (and callers) to confirm a skip really happened rather than just
+/// trusting it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseOutcome {
+    Reused,
+    Reparsed,
+}
+
+/// An in-process cache of a project's most recent [`ParseDirResult`],
+/// keyed by the [`ProjectFingerprint`] it was parsed at.
+pub struct ParseCache {
+    fingerprint: ProjectFingerprint,
+    result: ParseDirResult,
+}
+
+impl ParseCache {
+    pub fn result(&self) -> &ParseDirResult {
+        &self.result
+    }
+}
+
+/// Parses `root`, unless `previous` was cached from the same `root` and
+/// its fingerprint still matches the project's current state — in which
+/// case `previous` is handed straight back without calling [`parse_dir`]
+/// at all.
+pub fn parse_dir_cached(
+    root: &Path,
+    previous: Option<ParseCache>,
+) -> Result<(ParseCache, ParseOutcome), ParseDirError> {
+    let fingerprint = fingerprint_project(root)?;
+
+    if let Some(cache) = previous
+        && cache.fingerprint == fingerprint
+    {
+        return Ok((cache, ParseOutcome::Reused));
+    }
+
+    let result = parse_dir(root)?;
+    Ok((
+        ParseCache { fingerprint, result },
+        ParseOutcome::Reparsed,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tempdir() -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("skanujkod-parse-cache-test-{}-{n}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn an_unchanged_project_is_reused_without_reparsing() {
+        let dir = tempdir();
+        std::fs::write(dir.join("a.go"), "package main\nfunc a() {\n\tx := 1\n}\n").unwrap();
+
+        let (cache, first) = parse_dir_cached(&dir, None).unwrap();
+        assert_eq!(first, ParseOutcome::Reparsed);
+        assert!(cache.result().packages[""].files.contains_key("a.go"));
+
+        let (cache, second) = parse_dir_cached(&dir, Some(cache)).unwrap();
+        assert_eq!(second, ParseOutcome::Reused);
+        assert!(cache.result().packages[""].files.contains_key("a.go"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn editing_a_file_forces_a_reparse() {
+        let dir = tempdir();
+        std::fs::write(dir.join("a.go"), "package main\nfunc a() {\n\tx := 1\n}\n").unwrap();
+
+        let (cache, _) = parse_dir_cached(&dir, None).unwrap();
+
+        std::fs::write(dir.join("a.go"), "package main\nfunc a() {\n\tx := 2\n}\n").unwrap();
+        let (_, outcome) = parse_dir_cached(&dir, Some(cache)).unwrap();
+        assert_eq!(outcome, ParseOutcome::Reparsed);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn adding_a_new_file_to_the_project_forces_a_reparse() {
+        let dir = tempdir();
+        std::fs::write(dir.join("a.go"), "package main\nfunc a() {}\n").unwrap();
+
+        let (cache, _) = parse_dir_cached(&dir, None).unwrap();
+
+        std::fs::write(dir.join("b.go"), "package main\nfunc b() {}\n").unwrap();
+        let (cache, outcome) = parse_dir_cached(&dir, Some(cache)).unwrap();
+        assert_eq!(outcome, ParseOutcome::Reparsed);
+        assert!(cache.result().packages[""].files.contains_key("b.go"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn fingerprint_round_trips_through_a_file() {
+        let dir = tempdir();
+        std::fs::write(dir.join("a.go"), "package main\nfunc a() {}\n").unwrap();
+        let fingerprint = fingerprint_project(&dir).unwrap();
+
+        let cache_path = dir.join("cache.json");
+        save_fingerprint(&cache_path, &fingerprint).unwrap();
+        let loaded = load_fingerprint(&cache_path).unwrap();
+
+        assert_eq!(fingerprint, loaded);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
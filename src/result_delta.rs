@@ -0,0 +1,118 @@
+//! Diffing two keyed snapshots of a per-function result — e.g. two
+//! consecutive watch-mode complexity reports — down to what actually
+//! changed between them.
+//!
+//! Watch mode re-runs a full analysis on every save and, today, prints
+//! the whole result again; for a live UI following along, sending only
+//! the functions that were added, removed, or whose metrics changed is
+//! far cheaper than re-sending everything. This is generic over the
+//! per-function value being compared (`T: PartialEq`), so it works for
+//! [`crate::complexity::FunctionComplexity`] as well as any other
+//! `FunctionId`-keyed result a plugin function produces.
+
+use std::collections::BTreeMap;
+
+use crate::model::FunctionId;
+
+/// What changed about one function between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FunctionDelta<T> {
+    Added { after: T },
+    Removed,
+    Changed { before: T, after: T },
+}
+
+/// One function's change, keyed by its canonical [`FunctionId`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ResultDeltaEntry<T> {
+    pub function: FunctionId,
+    #[serde(flatten)]
+    pub delta: FunctionDelta<T>,
+}
+
+/// Compares `before` and `after` — two snapshots of the same kind of
+/// per-function result, keyed by [`FunctionId`] — and returns one
+/// [`ResultDeltaEntry`] per function that was added, removed, or whose
+/// value changed (by `PartialEq`) between them, sorted by function for a
+/// deterministic order. A function present in both snapshots with an
+/// unchanged value produces no entry — that's the point of a delta over
+/// re-sending the whole result set.
+pub fn diff<T: Clone + PartialEq>(
+    before: &BTreeMap<FunctionId, T>,
+    after: &BTreeMap<FunctionId, T>,
+) -> Vec<ResultDeltaEntry<T>> {
+    let mut entries: Vec<ResultDeltaEntry<T>> = after
+        .iter()
+        .filter_map(|(id, after_value)| match before.get(id) {
+            None => Some(ResultDeltaEntry {
+                function: id.clone(),
+                delta: FunctionDelta::Added { after: after_value.clone() },
+            }),
+            Some(before_value) if before_value != after_value => Some(ResultDeltaEntry {
+                function: id.clone(),
+                delta: FunctionDelta::Changed { before: before_value.clone(), after: after_value.clone() },
+            }),
+            Some(_) => None,
+        })
+        .chain(before.keys().filter(|id| !after.contains_key(id)).map(|id| ResultDeltaEntry {
+            function: id.clone(),
+            delta: FunctionDelta::Removed,
+        }))
+        .collect();
+    entries.sort_by(|a, b| a.function.cmp(&b.function));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, u32)]) -> BTreeMap<FunctionId, u32> {
+        entries
+            .iter()
+            .map(|(name, value)| (FunctionId::new("main", "a.go", *name), *value))
+            .collect()
+    }
+
+    #[test]
+    fn a_single_changed_function_is_the_only_entry_in_the_delta() {
+        let before = snapshot(&[("a", 1), ("b", 2)]);
+        let after = snapshot(&[("a", 1), ("b", 5)]);
+
+        let delta = diff(&before, &after);
+
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].function, FunctionId::new("main", "a.go", "b"));
+        assert_eq!(delta[0].delta, FunctionDelta::Changed { before: 2, after: 5 });
+    }
+
+    #[test]
+    fn an_added_and_a_removed_function_are_both_reported() {
+        let before = snapshot(&[("a", 1), ("gone", 9)]);
+        let after = snapshot(&[("a", 1), ("new", 3)]);
+
+        let delta = diff(&before, &after);
+
+        assert_eq!(delta.len(), 2);
+        assert_eq!(
+            delta,
+            vec![
+                ResultDeltaEntry {
+                    function: FunctionId::new("main", "a.go", "gone"),
+                    delta: FunctionDelta::Removed,
+                },
+                ResultDeltaEntry {
+                    function: FunctionId::new("main", "a.go", "new"),
+                    delta: FunctionDelta::Added { after: 3 },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_snapshots_produce_an_empty_delta() {
+        let snap = snapshot(&[("a", 1), ("b", 2)]);
+        assert!(diff(&snap, &snap).is_empty());
+    }
+}
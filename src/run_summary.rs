@@ -0,0 +1,78 @@
+//! A structured, machine-readable summary of one analysis run — meant
+//! for a CI dashboard tracking analysis health over time, where the
+//! CLI's normal human-readable output (one line per finding, printed as
+//! the pipeline runs) isn't something a script wants to parse.
+
+use std::time::Duration;
+
+use crate::complexity::ComplexityReport;
+use crate::go_parser::ParseDirResult;
+
+/// Counts and timing for one run, built from a [`ParseDirResult`] and
+/// the [`ComplexityReport`] the pipeline already produces for it — every
+/// other plugin function's findings are additional detail on top of
+/// these two, not a different measure of "how big was this run" or
+/// "did it pass policy".
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RunSummary {
+    pub files_parsed: usize,
+    pub functions_analyzed: usize,
+    /// `violations.len() + level_violations.len() + too_many_params.len()`
+    /// from the [`ComplexityReport`] — every function complexity policy
+    /// flagged, regardless of which specific policy it tripped.
+    pub policy_violations: usize,
+    pub wall_time: Duration,
+}
+
+/// Builds the [`RunSummary`] for a run that parsed `parsed` and analyzed
+/// it into `complexity_report`, having taken `wall_time` end to end.
+pub fn summarize(
+    parsed: &ParseDirResult,
+    complexity_report: &ComplexityReport,
+    wall_time: Duration,
+) -> RunSummary {
+    RunSummary {
+        files_parsed: parsed.packages.values().map(|pkg| pkg.files.len()).sum(),
+        functions_analyzed: complexity_report.functions.len(),
+        policy_violations: complexity_report.violations.len()
+            + complexity_report.level_violations.len()
+            + complexity_report.too_many_params.len(),
+        wall_time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::complexity::{self, ComplexityOptions};
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_dir() -> std::path::PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!("skanujkod-run-summary-test-{}-{n}", std::process::id()))
+    }
+
+    #[test]
+    fn the_summarys_function_count_matches_the_fixtures_function_count() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("a.go"),
+            "package main\n\nfunc a() {}\nfunc b() {}\nfunc c() {}\n",
+        )
+        .unwrap();
+
+        let parsed = crate::go_parser::parse_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let report = complexity::analyze_function_complexity(&parsed, &ComplexityOptions::default());
+        let summary = summarize(&parsed, &report, Duration::from_millis(5));
+
+        assert_eq!(summary.functions_analyzed, 3);
+        assert_eq!(summary.files_parsed, 1);
+        assert_eq!(summary.policy_violations, 0);
+        assert_eq!(summary.wall_time, Duration::from_millis(5));
+    }
+}
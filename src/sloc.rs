@@ -0,0 +1,99 @@
+//! Physical/logical source line counting.
+//!
+//! Counting statements in a CFG tells you how "busy" a function is, but not
+//! how long it is on the page: a single multi-line call or a struct literal
+//! spanning ten lines is one CFG statement but ten physical lines. This
+//! module counts lines the way a human skimming the file would, operating
+//! on the token stream rather than the AST so it doesn't need to guess at
+//! statement boundaries.
+
+use crate::go_parser::{Token, tokenize};
+
+/// Line counts for a single function (or any other source span).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct LineCounts {
+    /// Every line in the span, blank or not.
+    pub physical: usize,
+    /// Lines that contain at least one non-comment token.
+    pub logical: usize,
+    /// Lines whose only tokens are comments.
+    pub comment: usize,
+    /// Lines with no tokens at all.
+    pub blank: usize,
+}
+
+/// Counts physical/logical/comment/blank lines in `src`, which is expected
+/// to be the exact source text of a function (or any other span) sliced
+/// out by the caller.
+pub fn count_lines(src: &str) -> LineCounts {
+    let physical = if src.is_empty() {
+        0
+    } else {
+        src.lines().count()
+    };
+
+    let mut code_lines = vec![false; physical];
+    let mut comment_lines = vec![false; physical];
+
+    let mut line_starts = vec![0usize];
+    for (i, c) in src.chars().enumerate() {
+        if c == '\n' {
+            line_starts.push(i + 1);
+        }
+    }
+
+    let line_of = |offset: usize| -> usize {
+        match line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i.saturating_sub(1),
+        }
+    };
+
+    for (pos, tok, text) in tokenize(src) {
+        let lines_spanned = text.matches('\n').count() + 1;
+        let start_line = line_of(pos).min(physical.saturating_sub(1));
+        for l in start_line..(start_line + lines_spanned).min(physical) {
+            if matches!(tok, Token::COMMENT(_)) {
+                comment_lines[l] = true;
+            } else if !matches!(tok, Token::SEMICOLON(_)) {
+                code_lines[l] = true;
+            }
+        }
+    }
+
+    let mut logical = 0;
+    let mut comment = 0;
+    let mut blank = 0;
+    for i in 0..physical {
+        if code_lines[i] {
+            logical += 1;
+        } else if comment_lines[i] {
+            comment += 1;
+        } else {
+            blank += 1;
+        }
+    }
+
+    LineCounts {
+        physical,
+        logical,
+        comment,
+        blank,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_comments_and_blanks() {
+        let src = "func f() {\n\t// a comment\n\n\tx := 1\n\t_ = x\n}\n";
+        let counts = count_lines(src);
+        assert_eq!(counts.physical, 6);
+        assert_eq!(counts.comment, 1);
+        assert_eq!(counts.blank, 1);
+        // "func f() {", "x := 1", "_ = x", "}" are code lines.
+        assert_eq!(counts.logical, 4);
+    }
+}
@@ -0,0 +1,125 @@
+//! Debounce logic for a continuous "watch" workflow: coalesce a burst of
+//! rapid file-change notifications into a single re-analysis instead of
+//! running one per save.
+//!
+//! The actual filesystem watcher lives in `main`, since it needs a real OS
+//! notification backend. What's here only needs *something* that can hand
+//! over change events one at a time with a timeout, which is exactly the
+//! shape of [`std::sync::mpsc::Receiver::recv_timeout`] — so a test can
+//! inject a scripted sequence of [`RecvOutcome`]s instead of writing real
+//! files and waiting on a real watcher.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// One poll of the event source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecvOutcome {
+    /// A file changed.
+    Event(PathBuf),
+    /// No event arrived within the requested timeout.
+    TimedOut,
+    /// The event source is gone; stop watching.
+    Closed,
+}
+
+/// Watches for changes via `recv`, debouncing any that arrive within
+/// `debounce` of the previous one in the same burst, and calls `on_change`
+/// once per burst with every path that changed during it (in arrival
+/// order, duplicates included — callers that care can dedupe).
+///
+/// `recv(None)` should block until an event arrives or the source closes;
+/// `recv(Some(d))` should wait at most `d` before reporting
+/// [`RecvOutcome::TimedOut`]. Returns once `recv` reports
+/// [`RecvOutcome::Closed`].
+pub fn run_debounced(
+    debounce: Duration,
+    mut recv: impl FnMut(Option<Duration>) -> RecvOutcome,
+    mut on_change: impl FnMut(&[PathBuf]),
+) {
+    loop {
+        let first = match recv(None) {
+            RecvOutcome::Event(path) => path,
+            RecvOutcome::Closed => return,
+            RecvOutcome::TimedOut => continue,
+        };
+
+        let mut batch = vec![first];
+        loop {
+            match recv(Some(debounce)) {
+                RecvOutcome::Event(path) => batch.push(path),
+                RecvOutcome::TimedOut => break,
+                RecvOutcome::Closed => {
+                    on_change(&batch);
+                    return;
+                }
+            }
+        }
+        on_change(&batch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    fn scripted(outcomes: Vec<RecvOutcome>) -> impl FnMut(Option<Duration>) -> RecvOutcome {
+        let queue = Mutex::new(VecDeque::from(outcomes));
+        move |_timeout| queue.lock().unwrap().pop_front().unwrap_or(RecvOutcome::Closed)
+    }
+
+    #[test]
+    fn a_single_write_triggers_one_call() {
+        let recv = scripted(vec![
+            RecvOutcome::Event(PathBuf::from("a.go")),
+            RecvOutcome::TimedOut,
+            RecvOutcome::Closed,
+        ]);
+
+        let mut batches: Vec<Vec<PathBuf>> = Vec::new();
+        run_debounced(Duration::from_millis(1), recv, |paths| batches.push(paths.to_vec()));
+
+        assert_eq!(batches, vec![vec![PathBuf::from("a.go")]]);
+    }
+
+    #[test]
+    fn rapid_successive_saves_are_coalesced_into_one_call() {
+        let recv = scripted(vec![
+            RecvOutcome::Event(PathBuf::from("a.go")),
+            RecvOutcome::Event(PathBuf::from("a.go")),
+            RecvOutcome::Event(PathBuf::from("b.go")),
+            RecvOutcome::TimedOut,
+            RecvOutcome::Closed,
+        ]);
+
+        let mut batches: Vec<Vec<PathBuf>> = Vec::new();
+        run_debounced(Duration::from_millis(1), recv, |paths| batches.push(paths.to_vec()));
+
+        assert_eq!(
+            batches,
+            vec![vec![
+                PathBuf::from("a.go"),
+                PathBuf::from("a.go"),
+                PathBuf::from("b.go"),
+            ]]
+        );
+    }
+
+    #[test]
+    fn saves_separated_by_a_timeout_are_reported_as_separate_bursts() {
+        let recv = scripted(vec![
+            RecvOutcome::Event(PathBuf::from("a.go")),
+            RecvOutcome::TimedOut,
+            RecvOutcome::Event(PathBuf::from("b.go")),
+            RecvOutcome::TimedOut,
+            RecvOutcome::Closed,
+        ]);
+
+        let mut call_count = 0;
+        run_debounced(Duration::from_millis(1), recv, |_paths| call_count += 1);
+
+        assert_eq!(call_count, 2);
+    }
+}